@@ -0,0 +1,497 @@
+//! Static checks over clause sets: function clauses sharing a name and arity
+//! within a scope, and the arms of each `match`.
+//!
+//! Clauses are tried in source order, so a clause is unreachable when the
+//! unguarded clauses before it already match every value it could match. A
+//! clause set is non-exhaustive when some value of the types its patterns
+//! mention (every bool, every list shape, ints other than the listed literals)
+//! is matched by no clause. Guarded clauses never count as covering anything.
+
+use crate::ast::{Expression, FunctionClause, Pattern, Statement};
+use crate::diagnostic::Diagnostic;
+use crate::token::{Token, TokenType};
+use crate::value::Value;
+
+pub fn analyze(ast: &[Statement]) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    check_block(ast, &mut diagnostics);
+    diagnostics
+}
+
+fn check_block(block: &[Statement], diagnostics: &mut Vec<Diagnostic>) {
+    let mut functions: Vec<(&str, usize, Vec<&FunctionClause>)> = Vec::new();
+
+    for statement in block {
+        match statement {
+            Statement::Function(clause) => {
+                if let TokenType::Identity(name) = &clause.name.token_type {
+                    let arity = clause.parameters.len();
+                    match functions
+                        .iter_mut()
+                        .find(|(n, a, _)| *n == name.as_str() && *a == arity)
+                    {
+                        Some((_, _, clauses)) => clauses.push(clause),
+                        None => functions.push((name, arity, vec![clause])),
+                    }
+                }
+
+                if let Some(guard) = &clause.guard {
+                    check_expression(guard, diagnostics);
+                }
+                check_block(&clause.body, diagnostics);
+            }
+            Statement::Let(ls) => check_expression(&ls.expression, diagnostics),
+            Statement::Assign(assign) => check_expression(&assign.expression, diagnostics),
+            Statement::IndexAssign(assign) => {
+                check_expression(&assign.target.left, diagnostics);
+                check_expression(&assign.target.index, diagnostics);
+                check_expression(&assign.expression, diagnostics);
+            }
+            Statement::While(while_statement) => {
+                check_expression(&while_statement.condition, diagnostics);
+                check_block(&while_statement.body, diagnostics);
+            }
+            Statement::For(for_statement) => {
+                check_expression(&for_statement.iterable, diagnostics);
+                check_block(&for_statement.body, diagnostics);
+            }
+            Statement::Return(expression) | Statement::Expr(expression) => {
+                check_expression(expression, diagnostics)
+            }
+            Statement::Break(_)
+            | Statement::Continue(_)
+            | Statement::Import(_)
+            | Statement::End => {}
+        }
+    }
+
+    for (name, arity, clauses) in functions {
+        let rows: Vec<Row> = clauses
+            .iter()
+            .map(|clause| Row {
+                token: &clause.name,
+                shapes: clause.parameters.iter().map(Shape::of).collect(),
+                guarded: clause.guard.is_some(),
+            })
+            .collect();
+
+        check_rows(&rows, arity, diagnostics, |witness| {
+            format!("{}({})", name, witness.join(", "))
+        });
+    }
+}
+
+fn check_expression(expression: &Expression, diagnostics: &mut Vec<Diagnostic>) {
+    match expression {
+        Expression::Value(_) => {}
+        Expression::Prefix(prefix) => check_expression(&prefix.right, diagnostics),
+        Expression::Infix(infix) => {
+            check_expression(&infix.left, diagnostics);
+            check_expression(&infix.right, diagnostics);
+        }
+        Expression::Call(call) => {
+            check_expression(&call.function, diagnostics);
+            for argument in &call.arguments {
+                check_expression(argument, diagnostics);
+            }
+        }
+        Expression::If(if_expr) => {
+            for (condition, block) in &if_expr.branches {
+                check_expression(condition, diagnostics);
+                check_block(block, diagnostics);
+            }
+            if let Some(block) = &if_expr.alternative {
+                check_block(block, diagnostics);
+            }
+        }
+        Expression::Match(match_expr) => {
+            check_expression(&match_expr.subject, diagnostics);
+
+            let rows: Vec<Row> = match_expr
+                .arms
+                .iter()
+                .map(|arm| Row {
+                    token: arm.pattern.token(),
+                    shapes: vec![Shape::of(&arm.pattern)],
+                    guarded: arm.guard.is_some(),
+                })
+                .collect();
+
+            check_rows(&rows, 1, diagnostics, |witness| {
+                format!("match on {}", witness.join(", "))
+            });
+
+            for arm in &match_expr.arms {
+                if let Some(guard) = &arm.guard {
+                    check_expression(guard, diagnostics);
+                }
+                check_expression(&arm.body, diagnostics);
+            }
+        }
+        Expression::List(list) => {
+            for element in &list.elements {
+                check_expression(element, diagnostics);
+            }
+            if let Some(tail) = &list.tail {
+                check_expression(tail, diagnostics);
+            }
+        }
+        Expression::Array(array) => {
+            for element in &array.elements {
+                check_expression(element, diagnostics);
+            }
+        }
+        Expression::Access(access) => check_expression(&access.left, diagnostics),
+        Expression::Dict(dict) => {
+            for (key, value) in &dict.entries {
+                check_expression(key, diagnostics);
+                check_expression(value, diagnostics);
+            }
+        }
+        Expression::Index(index) => {
+            check_expression(&index.left, diagnostics);
+            check_expression(&index.index, diagnostics);
+        }
+        Expression::Function(clause) => check_block(&clause.body, diagnostics),
+    }
+}
+
+struct Row<'a> {
+    token: &'a Token,
+    shapes: Vec<Shape>,
+    guarded: bool,
+}
+
+fn check_rows<F>(rows: &[Row], arity: usize, diagnostics: &mut Vec<Diagnostic>, describe: F)
+where
+    F: Fn(&[String]) -> String,
+{
+    let mut covering: Vec<Vec<Shape>> = Vec::new();
+
+    for row in rows {
+        if useful(&covering, &row.shapes, Mode::Reachability).is_none() {
+            let shapes: Vec<String> = row.shapes.iter().map(Shape::render).collect();
+            diagnostics.push(Diagnostic::warning(
+                format!(
+                    "Unreachable clause, {} is always matched by an earlier clause",
+                    describe(&shapes)
+                ),
+                row.token,
+            ));
+        }
+
+        if !row.guarded {
+            covering.push(row.shapes.clone());
+        }
+    }
+
+    let query = vec![Shape::Wild; arity];
+    if let Some(witness) = useful(&covering, &query, Mode::Exhaustiveness) {
+        if let Some(first) = rows.first() {
+            let witness: Vec<String> = witness.iter().map(Shape::render).collect();
+            diagnostics.push(Diagnostic::warning(
+                format!(
+                    "Non-exhaustive clauses, {} is not matched",
+                    describe(&witness)
+                ),
+                first.token,
+            ));
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+enum Kind {
+    Bool,
+    Int,
+    Str,
+    Char,
+    List,
+    Dict,
+    Other,
+}
+
+const ALL_KINDS: [Kind; 7] = [
+    Kind::Bool,
+    Kind::Int,
+    Kind::Str,
+    Kind::Char,
+    Kind::List,
+    Kind::Dict,
+    Kind::Other,
+];
+
+/// A pattern reduced to what matters for coverage.
+#[derive(Clone, Debug)]
+enum Shape {
+    /// `_` or an untyped binding.
+    Wild,
+    /// A binding with a type annotation, covering every value of that type.
+    Typed(Kind),
+    Literal(Value),
+    Nil,
+    Cons(Box<Shape>, Box<Shape>),
+    /// Matches only some values (pins, dictionary patterns), so it is never
+    /// treated as covering anything.
+    Opaque(Option<Kind>),
+}
+
+impl Shape {
+    fn of(pattern: &Pattern) -> Self {
+        match pattern {
+            Pattern::Wildcard(_) | Pattern::Binding(_, None) => Shape::Wild,
+            Pattern::Binding(_, Some(var_type)) => match var_type.token_type {
+                TokenType::IntType => Shape::Typed(Kind::Int),
+                TokenType::StringType => Shape::Typed(Kind::Str),
+                TokenType::CharType => Shape::Typed(Kind::Char),
+                TokenType::BoolType => Shape::Typed(Kind::Bool),
+                _ => Shape::Opaque(None),
+            },
+            Pattern::Literal(token) => match Value::from_literal(token) {
+                Some(value) => Shape::Literal(value),
+                None => Shape::Opaque(None),
+            },
+            Pattern::Pin(_) => Shape::Opaque(None),
+            Pattern::List(list) => {
+                let mut shape = match &list.tail {
+                    Some(tail) => Shape::of(tail),
+                    None => Shape::Nil,
+                };
+
+                for element in list.elements.iter().rev() {
+                    shape = Shape::Cons(Box::new(Shape::of(element)), Box::new(shape));
+                }
+
+                shape
+            }
+            Pattern::Dict(dict) if dict.entries.is_empty() => Shape::Typed(Kind::Dict),
+            Pattern::Dict(_) => Shape::Opaque(Some(Kind::Dict)),
+        }
+    }
+
+    fn kind(&self) -> Option<Kind> {
+        match self {
+            Shape::Wild => None,
+            Shape::Typed(kind) => Some(*kind),
+            Shape::Literal(value) => Some(match value {
+                Value::Bool(_) => Kind::Bool,
+                Value::Int(_) => Kind::Int,
+                Value::Str(_) => Kind::Str,
+                Value::Char(_) => Kind::Char,
+                _ => Kind::Other,
+            }),
+            Shape::Nil | Shape::Cons(_, _) => Some(Kind::List),
+            Shape::Opaque(kind) => *kind,
+        }
+    }
+
+    fn render(&self) -> String {
+        match self {
+            Shape::Wild | Shape::Opaque(_) => String::from("_"),
+            Shape::Typed(Kind::Int) => String::from("_: int"),
+            Shape::Typed(Kind::Str) => String::from("_: string"),
+            Shape::Typed(Kind::Char) => String::from("_: char"),
+            Shape::Typed(Kind::Bool) => String::from("_: bool"),
+            Shape::Typed(_) => String::from("_"),
+            Shape::Literal(value) => value.repr(),
+            Shape::Nil => String::from("[]"),
+            Shape::Cons(head, tail) => {
+                let mut elements = vec![head.render()];
+                let mut tail = tail.as_ref();
+
+                while let Shape::Cons(head, rest) = tail {
+                    elements.push(head.render());
+                    tail = rest;
+                }
+
+                match tail {
+                    Shape::Nil => format!("[{}]", elements.join(", ")),
+                    tail => format!("[{} | {}]", elements.join(", "), tail.render()),
+                }
+            }
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy)]
+enum Mode {
+    /// Wildcards in the query range over every value.
+    Reachability,
+    /// Wildcards in the query range over the types the clauses mention.
+    Exhaustiveness,
+}
+
+#[derive(PartialEq)]
+enum Constructor<'a> {
+    Literal(&'a Value),
+    Nil,
+    Cons,
+}
+
+/// Returns a witness for the columns of `query` when some values matched by
+/// `query` are matched by none of `rows`.
+fn useful(rows: &[Vec<Shape>], query: &[Shape], mode: Mode) -> Option<Vec<Shape>> {
+    let (head, rest) = match query.split_first() {
+        Some(split) => split,
+        None => {
+            return if rows.is_empty() {
+                Some(Vec::new())
+            } else {
+                None
+            }
+        }
+    };
+
+    match head {
+        Shape::Literal(value) => {
+            let rows = specialize(rows, &Constructor::Literal(value));
+            useful(&rows, rest, mode).map(|witness| prepend(head.clone(), witness))
+        }
+        Shape::Nil => {
+            let rows = specialize(rows, &Constructor::Nil);
+            useful(&rows, rest, mode).map(|witness| prepend(Shape::Nil, witness))
+        }
+        Shape::Cons(first, tail) => {
+            let rows = specialize(rows, &Constructor::Cons);
+            let mut query = vec![*first.clone(), *tail.clone()];
+            query.extend_from_slice(rest);
+
+            useful(&rows, &query, mode).map(rebuild_cons)
+        }
+        Shape::Opaque(kind) => {
+            let rows = default_rows(rows, *kind);
+            useful(&rows, rest, mode).map(|witness| prepend(head.clone(), witness))
+        }
+        Shape::Typed(kind) => useful_over(rows, rest, &[*kind], mode),
+        Shape::Wild => {
+            let kinds: Vec<Kind> = match mode {
+                Mode::Reachability => ALL_KINDS.to_vec(),
+                Mode::Exhaustiveness => {
+                    let mut kinds = Vec::new();
+                    for kind in rows.iter().filter_map(|row| row[0].kind()) {
+                        if !kinds.contains(&kind) {
+                            kinds.push(kind);
+                        }
+                    }
+                    kinds
+                }
+            };
+
+            if kinds.is_empty() {
+                let rows = default_rows(rows, None);
+                return useful(&rows, rest, mode).map(|witness| prepend(Shape::Wild, witness));
+            }
+
+            useful_over(rows, rest, &kinds, mode)
+        }
+    }
+}
+
+/// Usefulness of a head that covers every value of each of `kinds`.
+fn useful_over(
+    rows: &[Vec<Shape>],
+    rest: &[Shape],
+    kinds: &[Kind],
+    mode: Mode,
+) -> Option<Vec<Shape>> {
+    for kind in kinds {
+        // Constructors are only enumerated for kinds the column mentions;
+        // otherwise only rows covering the whole kind can match.
+        let mentioned = rows.iter().any(|row| row[0].kind() == Some(*kind));
+
+        match kind {
+            Kind::Bool if mentioned => {
+                for b in [true, false].iter() {
+                    let value = Value::Bool(*b);
+                    let specialized = specialize(rows, &Constructor::Literal(&value));
+                    if let Some(witness) = useful(&specialized, rest, mode) {
+                        return Some(prepend(Shape::Literal(value), witness));
+                    }
+                }
+            }
+            Kind::List if mentioned => {
+                let specialized = specialize(rows, &Constructor::Nil);
+                if let Some(witness) = useful(&specialized, rest, mode) {
+                    return Some(prepend(Shape::Nil, witness));
+                }
+
+                let specialized = specialize(rows, &Constructor::Cons);
+                let mut query = vec![Shape::Wild, Shape::Wild];
+                query.extend_from_slice(rest);
+                if let Some(witness) = useful(&specialized, &query, mode) {
+                    return Some(rebuild_cons(witness));
+                }
+            }
+            kind => {
+                let defaults = default_rows(rows, Some(*kind));
+                if let Some(witness) = useful(&defaults, rest, mode) {
+                    return Some(prepend(Shape::Typed(*kind), witness));
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Rows that can match a value built with `constructor`, with the head
+/// replaced by the constructor's fields.
+fn specialize(rows: &[Vec<Shape>], constructor: &Constructor) -> Vec<Vec<Shape>> {
+    let arity = match constructor {
+        Constructor::Cons => 2,
+        _ => 0,
+    };
+    let kind = match constructor {
+        Constructor::Literal(value) => Shape::Literal((*value).clone()).kind(),
+        _ => Some(Kind::List),
+    };
+
+    rows.iter()
+        .filter_map(|row| {
+            let (head, rest) = row.split_first()?;
+
+            let mut fields = match head {
+                Shape::Wild => vec![Shape::Wild; arity],
+                Shape::Typed(k) if Some(*k) == kind => vec![Shape::Wild; arity],
+                Shape::Literal(value) => match constructor {
+                    Constructor::Literal(other) if value == *other => Vec::new(),
+                    _ => return None,
+                },
+                Shape::Nil if *constructor == Constructor::Nil => Vec::new(),
+                Shape::Cons(first, tail) if *constructor == Constructor::Cons => {
+                    vec![*first.clone(), *tail.clone()]
+                }
+                _ => return None,
+            };
+
+            fields.extend_from_slice(rest);
+            Some(fields)
+        })
+        .collect()
+}
+
+/// Rows whose head matches every value of `kind`, or every value at all when
+/// `kind` is unknown, with the head removed.
+fn default_rows(rows: &[Vec<Shape>], kind: Option<Kind>) -> Vec<Vec<Shape>> {
+    rows.iter()
+        .filter(|row| match &row[0] {
+            Shape::Wild => true,
+            Shape::Typed(k) => Some(*k) == kind,
+            _ => false,
+        })
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+fn prepend(head: Shape, mut witness: Vec<Shape>) -> Vec<Shape> {
+    witness.insert(0, head);
+    witness
+}
+
+fn rebuild_cons(mut witness: Vec<Shape>) -> Vec<Shape> {
+    let rest = witness.split_off(2);
+    let tail = witness.pop().unwrap_or(Shape::Wild);
+    let first = witness.pop().unwrap_or(Shape::Wild);
+
+    prepend(Shape::Cons(Box::new(first), Box::new(tail)), rest)
+}
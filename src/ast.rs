@@ -1,53 +1,733 @@
-use crate::token::Token;
+use crate::token::{Token, TokenType};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
 pub type Ast = Vec<Statement>;
+pub type Block = Vec<Statement>;
 
 #[derive(Clone, Debug)]
 pub enum Statement {
     Let(LetStatement),
+    Assign(AssignStatement),
+    IndexAssign(IndexAssignStatement),
+    Function(FunctionClause),
+    While(WhileStatement),
+    For(ForStatement),
+    Break(Token),
+    Continue(Token),
     Return(Expression),
     Expr(Expression),
+    Import(ImportStatement),
     End,
 }
 
 impl Statement {
-    pub fn new_let_statement(name: Token, var_type: Token, expression: Expression) -> Self {
-        Statement::Let(LetStatement::new(name, var_type, expression))
+    pub fn new_let_statement(pattern: Pattern, expression: Expression) -> Self {
+        Statement::Let(LetStatement::new(pattern, expression))
     }
 
     pub fn new_prefix_statement(operator: Token, right: Expression) -> Self {
         let right = Box::<Expression>::new(right);
         Statement::Expr(Expression::Prefix(PrefixExpr { operator, right }))
     }
+
+    /// Calls `f` on every token in the statement, such as to move it to
+    /// other rows.
+    pub fn tokens_mut(&mut self, f: &mut dyn FnMut(&mut Token)) {
+        match self {
+            Statement::Let(ls) => {
+                ls.pattern.tokens_mut(f);
+                ls.expression.tokens_mut(f);
+            }
+            Statement::Assign(assign) => {
+                f(&mut assign.name);
+                assign.expression.tokens_mut(f);
+            }
+            Statement::IndexAssign(assign) => {
+                assign.target.tokens_mut(f);
+                assign.expression.tokens_mut(f);
+            }
+            Statement::Function(clause) => clause.tokens_mut(f),
+            Statement::While(while_statement) => {
+                f(&mut while_statement.token);
+                while_statement.condition.tokens_mut(f);
+                block_tokens_mut(&mut while_statement.body, f);
+            }
+            Statement::For(for_statement) => {
+                f(&mut for_statement.token);
+                for_statement.pattern.tokens_mut(f);
+                for_statement.iterable.tokens_mut(f);
+                block_tokens_mut(&mut for_statement.body, f);
+            }
+            Statement::Break(token) | Statement::Continue(token) => f(token),
+            Statement::Return(expression) | Statement::Expr(expression) => expression.tokens_mut(f),
+            Statement::Import(import) => {
+                f(&mut import.token);
+                f(&mut import.module);
+            }
+            Statement::End => {}
+        }
+    }
+}
+
+fn block_tokens_mut(block: &mut [Statement], f: &mut dyn FnMut(&mut Token)) {
+    for statement in block {
+        statement.tokens_mut(f);
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct LetStatement {
-    pub name: Token,
-    pub var_type: Token,
+    pub pattern: Pattern,
     pub expression: Expression,
+    /// Marked `pub`, so the names it binds are exported from the file.
+    pub public: bool,
 }
 
 impl LetStatement {
-    pub fn new(name: Token, var_type: Token, expression: Expression) -> Self {
+    pub fn new(pattern: Pattern, expression: Expression) -> Self {
         LetStatement {
-            name,
-            var_type,
+            pattern,
             expression,
+            public: false,
+        }
+    }
+}
+
+/// `import "utils.slice"` or `import Utils`, which loads another file and
+/// binds its public definitions to a name.
+#[derive(Clone, Debug)]
+pub struct ImportStatement {
+    pub token: Token,
+    /// A string with the path of the file or the name of the module.
+    pub module: Token,
+}
+
+impl ImportStatement {
+    /// The name the module is bound to: the name given, or the file name
+    /// of the path in CamelCase, so `"string_utils.slice"` is `StringUtils`.
+    pub fn name(&self) -> String {
+        match &self.module.token_type {
+            TokenType::Str(path) => {
+                let stem = Path::new(path)
+                    .file_stem()
+                    .map_or(String::new(), |stem| stem.to_string_lossy().into_owned());
+
+                stem.split(['_', '-'])
+                    .flat_map(|word| {
+                        let mut chars = word.chars();
+                        chars
+                            .next()
+                            .map(|first| first.to_uppercase().chain(chars))
+                            .into_iter()
+                            .flatten()
+                    })
+                    .collect()
+            }
+            TokenType::Identity(name) => name.clone(),
+            _ => String::new(),
+        }
+    }
+
+    /// The path of the file, relative to the importing one. A module name
+    /// is the file name in snake case, so `StringUtils` is
+    /// `string_utils.slice`.
+    pub fn path(&self) -> PathBuf {
+        match &self.module.token_type {
+            TokenType::Str(path) => PathBuf::from(path),
+            TokenType::Identity(name) => {
+                let mut file = String::new();
+                for (i, c) in name.chars().enumerate() {
+                    if c.is_uppercase() && i > 0 {
+                        file.push('_');
+                    }
+                    file.extend(c.to_lowercase());
+                }
+
+                PathBuf::from(file + ".slice")
+            }
+            _ => PathBuf::new(),
         }
     }
 }
 
+#[derive(Clone, Debug)]
+pub struct AssignStatement {
+    pub name: Token,
+    pub expression: Expression,
+}
+
+/// `target[index] = expression`, which writes into an array in place.
+#[derive(Clone, Debug)]
+pub struct IndexAssignStatement {
+    pub target: IndexExpr,
+    pub expression: Expression,
+}
+
+#[derive(Clone, Debug)]
+pub struct WhileStatement {
+    pub token: Token,
+    pub condition: Expression,
+    pub body: Block,
+}
+
+/// `for pattern in iterable:`. Each element is matched against the pattern
+/// in a fresh scope for the iteration.
+#[derive(Clone, Debug)]
+pub struct ForStatement {
+    pub token: Token,
+    pub pattern: Pattern,
+    pub iterable: Expression,
+    pub body: Block,
+}
+
+/// One clause of a possibly multi-clause function. Clauses sharing a name are
+/// tried in the order they appear in the source.
+#[derive(Clone, Debug)]
+pub struct FunctionClause {
+    pub name: Token,
+    pub parameters: Vec<Pattern>,
+    pub return_type: Option<Token>,
+    pub guard: Option<Expression>,
+    pub body: Block,
+    /// Marked `pub`, so the function is exported from the file. A function
+    /// is exported when any of its clauses is.
+    pub public: bool,
+}
+
+impl FunctionClause {
+    fn tokens_mut(&mut self, f: &mut dyn FnMut(&mut Token)) {
+        f(&mut self.name);
+        for parameter in &mut self.parameters {
+            parameter.tokens_mut(f);
+        }
+        if let Some(return_type) = &mut self.return_type {
+            f(return_type);
+        }
+        if let Some(guard) = &mut self.guard {
+            guard.tokens_mut(f);
+        }
+        block_tokens_mut(&mut self.body, f);
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum Pattern {
+    Wildcard(Token),
+    Binding(Token, Option<Token>),
+    Literal(Token),
+    Pin(Token),
+    List(ListPattern),
+    Dict(DictPattern),
+}
+
+impl Pattern {
+    pub fn token(&self) -> &Token {
+        match self {
+            Pattern::Wildcard(token)
+            | Pattern::Binding(token, _)
+            | Pattern::Literal(token)
+            | Pattern::Pin(token) => token,
+            Pattern::List(list) => &list.token,
+            Pattern::Dict(dict) => &dict.token,
+        }
+    }
+
+    fn tokens_mut(&mut self, f: &mut dyn FnMut(&mut Token)) {
+        match self {
+            Pattern::Wildcard(token) | Pattern::Literal(token) | Pattern::Pin(token) => f(token),
+            Pattern::Binding(token, kind) => {
+                f(token);
+                if let Some(kind) = kind {
+                    f(kind);
+                }
+            }
+            Pattern::List(list) => {
+                f(&mut list.token);
+                for element in &mut list.elements {
+                    element.tokens_mut(f);
+                }
+                if let Some(tail) = &mut list.tail {
+                    tail.tokens_mut(f);
+                }
+            }
+            Pattern::Dict(dict) => {
+                f(&mut dict.token);
+                for (key, pattern) in &mut dict.entries {
+                    f(key);
+                    pattern.tokens_mut(f);
+                }
+            }
+        }
+    }
+
+    /// The names the pattern binds.
+    pub fn bound_names(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Binding(token, _) => push_name(token, names),
+            Pattern::List(list) => {
+                for element in &list.elements {
+                    element.bound_names(names);
+                }
+                if let Some(tail) = &list.tail {
+                    tail.bound_names(names);
+                }
+            }
+            Pattern::Dict(dict) => {
+                for (_, pattern) in &dict.entries {
+                    pattern.bound_names(names);
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Literal(_) | Pattern::Pin(_) => {}
+        }
+    }
+
+    fn pinned_names(&self, names: &mut Vec<String>) {
+        match self {
+            Pattern::Pin(token) => push_name(token, names),
+            Pattern::List(list) => {
+                for element in &list.elements {
+                    element.pinned_names(names);
+                }
+                if let Some(tail) = &list.tail {
+                    tail.pinned_names(names);
+                }
+            }
+            Pattern::Dict(dict) => {
+                for (_, pattern) in &dict.entries {
+                    pattern.pinned_names(names);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `[a, b | tail]`. Without a tail the list must have exactly as many
+/// elements as the pattern.
+#[derive(Clone, Debug)]
+pub struct ListPattern {
+    pub token: Token,
+    pub elements: Vec<Pattern>,
+    pub tail: Option<Box<Pattern>>,
+}
+
+/// `{"key": pattern}`. Keys are literals and only the listed keys must be
+/// present in the matched dictionary.
+#[derive(Clone, Debug)]
+pub struct DictPattern {
+    pub token: Token,
+    pub entries: Vec<(Token, Pattern)>,
+}
+
 #[derive(Clone, Debug)]
 pub enum Expression {
     Value(Token),
     Prefix(PrefixExpr),
+    Infix(InfixExpr),
+    Call(CallExpr),
+    If(IfExpr),
+    Match(MatchExpr),
+    List(ListExpr),
+    Array(ArrayExpr),
+    Dict(DictExpr),
+    Index(IndexExpr),
+    Access(AccessExpr),
+    /// An anonymous function, `fn(x) -> x + 1` or `fn(x): ... end`. The
+    /// clause's name is the `fn` token.
+    Function(Rc<FunctionClause>),
+}
+
+impl Expression {
+    /// The token used to report errors for this expression.
+    pub fn token(&self) -> &Token {
+        match self {
+            Expression::Value(token) => token,
+            Expression::Prefix(prefix) => &prefix.operator,
+            Expression::Infix(infix) => &infix.operator,
+            Expression::Call(call) => call.function.token(),
+            Expression::If(if_expr) => &if_expr.token,
+            Expression::Match(match_expr) => &match_expr.token,
+            Expression::List(list) => &list.token,
+            Expression::Array(array) => &array.token,
+            Expression::Dict(dict) => &dict.token,
+            Expression::Index(index) => &index.token,
+            Expression::Access(access) => &access.name,
+            Expression::Function(clause) => &clause.name,
+        }
+    }
+
+    fn tokens_mut(&mut self, f: &mut dyn FnMut(&mut Token)) {
+        match self {
+            Expression::Value(token) => f(token),
+            Expression::Prefix(prefix) => {
+                f(&mut prefix.operator);
+                prefix.right.tokens_mut(f);
+            }
+            Expression::Infix(infix) => {
+                f(&mut infix.operator);
+                infix.left.tokens_mut(f);
+                infix.right.tokens_mut(f);
+            }
+            Expression::Call(call) => {
+                call.function.tokens_mut(f);
+                for argument in &mut call.arguments {
+                    argument.tokens_mut(f);
+                }
+            }
+            Expression::If(if_expr) => {
+                f(&mut if_expr.token);
+                for (condition, block) in &mut if_expr.branches {
+                    condition.tokens_mut(f);
+                    block_tokens_mut(block, f);
+                }
+                if let Some(block) = &mut if_expr.alternative {
+                    block_tokens_mut(block, f);
+                }
+            }
+            Expression::Match(match_expr) => {
+                f(&mut match_expr.token);
+                match_expr.subject.tokens_mut(f);
+                for arm in &mut match_expr.arms {
+                    arm.pattern.tokens_mut(f);
+                    if let Some(guard) = &mut arm.guard {
+                        guard.tokens_mut(f);
+                    }
+                    arm.body.tokens_mut(f);
+                }
+            }
+            Expression::List(list) => {
+                f(&mut list.token);
+                for element in &mut list.elements {
+                    element.tokens_mut(f);
+                }
+                if let Some(tail) = &mut list.tail {
+                    tail.tokens_mut(f);
+                }
+            }
+            Expression::Array(array) => {
+                f(&mut array.token);
+                for element in &mut array.elements {
+                    element.tokens_mut(f);
+                }
+            }
+            Expression::Dict(dict) => {
+                f(&mut dict.token);
+                for (key, value) in &mut dict.entries {
+                    key.tokens_mut(f);
+                    value.tokens_mut(f);
+                }
+            }
+            Expression::Index(index) => index.tokens_mut(f),
+            Expression::Access(access) => {
+                f(&mut access.token);
+                access.left.tokens_mut(f);
+                f(&mut access.name);
+            }
+            Expression::Function(clause) => Rc::make_mut(clause).tokens_mut(f),
+        }
+    }
+
+    /// Every identifier the expression reads, including inside nested blocks
+    /// and pinned patterns. Names bound within the expression are included
+    /// too, so this over-approximates its free variables.
+    pub fn referenced_names(&self, names: &mut Vec<String>) {
+        match self {
+            Expression::Value(token) => push_name(token, names),
+            Expression::Prefix(prefix) => prefix.right.referenced_names(names),
+            Expression::Infix(infix) => {
+                infix.left.referenced_names(names);
+                infix.right.referenced_names(names);
+            }
+            Expression::Call(call) => {
+                call.function.referenced_names(names);
+                for argument in &call.arguments {
+                    argument.referenced_names(names);
+                }
+            }
+            Expression::If(if_expr) => {
+                for (condition, block) in &if_expr.branches {
+                    condition.referenced_names(names);
+                    block_names(block, names);
+                }
+                if let Some(block) = &if_expr.alternative {
+                    block_names(block, names);
+                }
+            }
+            Expression::Match(match_expr) => {
+                match_expr.subject.referenced_names(names);
+                for arm in &match_expr.arms {
+                    arm.pattern.pinned_names(names);
+                    if let Some(guard) = &arm.guard {
+                        guard.referenced_names(names);
+                    }
+                    arm.body.referenced_names(names);
+                }
+            }
+            Expression::List(list) => {
+                for element in &list.elements {
+                    element.referenced_names(names);
+                }
+                if let Some(tail) = &list.tail {
+                    tail.referenced_names(names);
+                }
+            }
+            Expression::Dict(dict) => {
+                for (key, value) in &dict.entries {
+                    key.referenced_names(names);
+                    value.referenced_names(names);
+                }
+            }
+            Expression::Array(array) => {
+                for element in &array.elements {
+                    element.referenced_names(names);
+                }
+            }
+            Expression::Index(index) => index.referenced_names(names),
+            Expression::Access(access) => access.left.referenced_names(names),
+            Expression::Function(clause) => clause_names(clause, names),
+        }
+    }
+}
+
+fn push_name(token: &Token, names: &mut Vec<String>) {
+    if let TokenType::Identity(name) = &token.token_type {
+        if !names.contains(name) {
+            names.push(name.clone());
+        }
+    }
+}
+
+fn clause_names(clause: &FunctionClause, names: &mut Vec<String>) {
+    for parameter in &clause.parameters {
+        parameter.pinned_names(names);
+    }
+    if let Some(guard) = &clause.guard {
+        guard.referenced_names(names);
+    }
+    block_names(&clause.body, names);
+}
+
+fn block_names(block: &[Statement], names: &mut Vec<String>) {
+    for statement in block {
+        match statement {
+            Statement::Let(ls) => {
+                ls.pattern.pinned_names(names);
+                ls.expression.referenced_names(names);
+            }
+            Statement::Assign(assign) => {
+                push_name(&assign.name, names);
+                assign.expression.referenced_names(names);
+            }
+            Statement::IndexAssign(assign) => {
+                assign.target.referenced_names(names);
+                assign.expression.referenced_names(names);
+            }
+            Statement::Function(clause) => clause_names(clause, names),
+            Statement::While(while_statement) => {
+                while_statement.condition.referenced_names(names);
+                block_names(&while_statement.body, names);
+            }
+            Statement::For(for_statement) => {
+                for_statement.pattern.pinned_names(names);
+                for_statement.iterable.referenced_names(names);
+                block_names(&for_statement.body, names);
+            }
+            Statement::Return(expression) | Statement::Expr(expression) => {
+                expression.referenced_names(names)
+            }
+            Statement::Break(_)
+            | Statement::Continue(_)
+            | Statement::Import(_)
+            | Statement::End => {}
+        }
+    }
+}
+
+/// Every import in a block, including those in nested blocks, in the order
+/// they appear.
+pub fn imports(block: &[Statement]) -> Vec<&ImportStatement> {
+    let mut imports = Vec::new();
+    block_imports(block, &mut imports);
+    imports
+}
+
+fn block_imports<'a>(block: &'a [Statement], imports: &mut Vec<&'a ImportStatement>) {
+    for statement in block {
+        match statement {
+            Statement::Import(import) => imports.push(import),
+            Statement::Let(ls) => expression_imports(&ls.expression, imports),
+            Statement::Assign(assign) => expression_imports(&assign.expression, imports),
+            Statement::IndexAssign(assign) => {
+                expression_imports(&assign.target.left, imports);
+                expression_imports(&assign.target.index, imports);
+                expression_imports(&assign.expression, imports);
+            }
+            Statement::Function(clause) => block_imports(&clause.body, imports),
+            Statement::While(while_statement) => {
+                expression_imports(&while_statement.condition, imports);
+                block_imports(&while_statement.body, imports);
+            }
+            Statement::For(for_statement) => {
+                expression_imports(&for_statement.iterable, imports);
+                block_imports(&for_statement.body, imports);
+            }
+            Statement::Return(expression) | Statement::Expr(expression) => {
+                expression_imports(expression, imports)
+            }
+            Statement::Break(_) | Statement::Continue(_) | Statement::End => {}
+        }
+    }
+}
+
+fn expression_imports<'a>(expression: &'a Expression, imports: &mut Vec<&'a ImportStatement>) {
+    match expression {
+        Expression::Value(_) => {}
+        Expression::Prefix(prefix) => expression_imports(&prefix.right, imports),
+        Expression::Infix(infix) => {
+            expression_imports(&infix.left, imports);
+            expression_imports(&infix.right, imports);
+        }
+        Expression::Call(call) => {
+            expression_imports(&call.function, imports);
+            for argument in &call.arguments {
+                expression_imports(argument, imports);
+            }
+        }
+        Expression::If(if_expr) => {
+            for (condition, block) in &if_expr.branches {
+                expression_imports(condition, imports);
+                block_imports(block, imports);
+            }
+            if let Some(block) = &if_expr.alternative {
+                block_imports(block, imports);
+            }
+        }
+        Expression::Match(match_expr) => {
+            expression_imports(&match_expr.subject, imports);
+            for arm in &match_expr.arms {
+                if let Some(guard) = &arm.guard {
+                    expression_imports(guard, imports);
+                }
+                expression_imports(&arm.body, imports);
+            }
+        }
+        Expression::List(list) => {
+            for element in &list.elements {
+                expression_imports(element, imports);
+            }
+            if let Some(tail) = &list.tail {
+                expression_imports(tail, imports);
+            }
+        }
+        Expression::Array(array) => {
+            for element in &array.elements {
+                expression_imports(element, imports);
+            }
+        }
+        Expression::Dict(dict) => {
+            for (key, value) in &dict.entries {
+                expression_imports(key, imports);
+                expression_imports(value, imports);
+            }
+        }
+        Expression::Index(index) => {
+            expression_imports(&index.left, imports);
+            expression_imports(&index.index, imports);
+        }
+        Expression::Access(access) => expression_imports(&access.left, imports),
+        Expression::Function(clause) => block_imports(&clause.body, imports),
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct PrefixExpr {
-    operator: Token,
-    right: Box<Expression>,
+    pub operator: Token,
+    pub right: Box<Expression>,
+}
+
+#[derive(Clone, Debug)]
+pub struct InfixExpr {
+    pub operator: Token,
+    pub left: Box<Expression>,
+    pub right: Box<Expression>,
+}
+
+#[derive(Clone, Debug)]
+pub struct CallExpr {
+    pub function: Box<Expression>,
+    pub arguments: Vec<Expression>,
+}
+
+/// `left[index]`, where the index may be a range to take a slice.
+#[derive(Clone, Debug)]
+pub struct IndexExpr {
+    pub token: Token,
+    pub left: Box<Expression>,
+    pub index: Box<Expression>,
+}
+
+impl IndexExpr {
+    fn tokens_mut(&mut self, f: &mut dyn FnMut(&mut Token)) {
+        f(&mut self.token);
+        self.left.tokens_mut(f);
+        self.index.tokens_mut(f);
+    }
+
+    fn referenced_names(&self, names: &mut Vec<String>) {
+        self.left.referenced_names(names);
+        self.index.referenced_names(names);
+    }
+}
+
+/// `left.name`, a module member or a dictionary entry with a string key.
+#[derive(Clone, Debug)]
+pub struct AccessExpr {
+    pub token: Token,
+    pub left: Box<Expression>,
+    pub name: Token,
+}
+
+#[derive(Clone, Debug)]
+pub struct IfExpr {
+    pub token: Token,
+    pub branches: Vec<(Expression, Block)>,
+    pub alternative: Option<Block>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchExpr {
+    pub token: Token,
+    pub subject: Box<Expression>,
+    pub arms: Vec<MatchArm>,
+}
+
+#[derive(Clone, Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub guard: Option<Expression>,
+    pub body: Expression,
+}
+
+#[derive(Clone, Debug)]
+pub struct ListExpr {
+    pub token: Token,
+    pub elements: Vec<Expression>,
+    /// The list the elements are prepended to, as in `[head | tail]`.
+    pub tail: Option<Box<Expression>>,
+}
+
+/// `[/1, 2, 3/]`.
+#[derive(Clone, Debug)]
+pub struct ArrayExpr {
+    pub token: Token,
+    pub elements: Vec<Expression>,
+}
+
+#[derive(Clone, Debug)]
+pub struct DictExpr {
+    pub token: Token,
+    pub entries: Vec<(Expression, Expression)>,
 }
 
 trait Pratt {
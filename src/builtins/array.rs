@@ -0,0 +1,126 @@
+//! Arrays are passed by reference: every variable, argument and collection
+//! holding an array sees the changes made through any of them. `copy` makes
+//! an independent array.
+
+use super::{expected, Builtin, Module};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+pub const MODULE: Module = Module {
+    name: "Array",
+    functions: &[
+        Builtin {
+            module: "Array",
+            name: "length",
+            arity: 1,
+            function: length,
+        },
+        Builtin {
+            module: "Array",
+            name: "push",
+            arity: 2,
+            function: push,
+        },
+        Builtin {
+            module: "Array",
+            name: "pop",
+            arity: 1,
+            function: pop,
+        },
+        Builtin {
+            module: "Array",
+            name: "copy",
+            arity: 1,
+            function: copy,
+        },
+        Builtin {
+            module: "Array",
+            name: "make",
+            arity: 2,
+            function: make,
+        },
+        Builtin {
+            module: "Array",
+            name: "from",
+            arity: 1,
+            function: from,
+        },
+    ],
+};
+
+fn array<'a>(
+    builtin: &str,
+    value: &'a Value,
+    token: &Token,
+) -> Result<&'a Rc<RefCell<Vec<Value>>>, String> {
+    match value {
+        Value::Array(array) => Ok(array),
+        other => Err(expected(builtin, "array", other, token)),
+    }
+}
+
+fn length(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let array = array("Array.length", &arguments[0], token)?;
+
+    Ok(Value::Int(array.borrow().len() as isize))
+}
+
+fn push(_: &mut Interpreter, mut arguments: Vec<Value>, token: &Token) -> Eval {
+    let value = arguments.pop().unwrap();
+    let array = array("Array.push", &arguments[0], token)?;
+
+    array.borrow_mut().push(value);
+
+    Ok(Value::Unit)
+}
+
+fn pop(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let array = array("Array.pop", &arguments[0], token)?;
+
+    let value = array.borrow_mut().pop();
+    value
+        .ok_or_else(|| format!("Cannot pop from an empty array {}.{}", token.row, token.col).into())
+}
+
+fn copy(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let array = array("Array.copy", &arguments[0], token)?;
+
+    let elements = array.borrow().clone();
+    Ok(Value::Array(Rc::new(RefCell::new(elements))))
+}
+
+/// `make(length, value)`, an array of `length` copies of `value`.
+fn make(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let length = match arguments[0] {
+        Value::Int(length) if length >= 0 => length as usize,
+        Value::Int(length) => {
+            return Err(format!(
+                "Array.make expected a length of at least 0, got {} {}.{}",
+                length, token.row, token.col
+            )
+            .into())
+        }
+        ref other => return Err(expected("Array.make", "int", other, token).into()),
+    };
+
+    Ok(Value::Array(Rc::new(RefCell::new(vec![
+        arguments[1]
+            .clone();
+        length
+    ]))))
+}
+
+/// A new array holding the elements of a list, range or array.
+fn from(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let elements = match &arguments[0] {
+        Value::List(list) => list.iter().cloned().collect(),
+        Value::Range(start, end) => (*start..*end).map(Value::Int).collect(),
+        Value::Array(array) => array.borrow().clone(),
+        other => return Err(expected("Array.from", "list, range or array", other, token).into()),
+    };
+
+    Ok(Value::Array(Rc::new(RefCell::new(elements))))
+}
@@ -0,0 +1,136 @@
+use super::{expected, Builtin, Module};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+
+pub const MODULE: Module = Module {
+    name: "Char",
+    functions: &[
+        Builtin {
+            module: "Char",
+            name: "is_digit?",
+            arity: 1,
+            function: is_digit,
+        },
+        Builtin {
+            module: "Char",
+            name: "is_letter?",
+            arity: 1,
+            function: is_letter,
+        },
+        Builtin {
+            module: "Char",
+            name: "is_whitespace?",
+            arity: 1,
+            function: is_whitespace,
+        },
+        Builtin {
+            module: "Char",
+            name: "is_upper?",
+            arity: 1,
+            function: is_upper,
+        },
+        Builtin {
+            module: "Char",
+            name: "is_lower?",
+            arity: 1,
+            function: is_lower,
+        },
+        Builtin {
+            module: "Char",
+            name: "upcase",
+            arity: 1,
+            function: upcase,
+        },
+        Builtin {
+            module: "Char",
+            name: "downcase",
+            arity: 1,
+            function: downcase,
+        },
+        Builtin {
+            module: "Char",
+            name: "to_int",
+            arity: 1,
+            function: to_int,
+        },
+        Builtin {
+            module: "Char",
+            name: "to_string",
+            arity: 1,
+            function: to_string,
+        },
+    ],
+};
+
+fn character(builtin: &str, value: &Value, token: &Token) -> Result<char, String> {
+    match value {
+        Value::Char(c) => Ok(*c),
+        other => Err(expected(builtin, "char", other, token)),
+    }
+}
+
+/// Digits in any script, such as '٣', and other numeric characters like '½'.
+fn is_digit(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.is_digit?", &arguments[0], token)?;
+
+    Ok(Value::Bool(c.is_numeric()))
+}
+
+fn is_letter(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.is_letter?", &arguments[0], token)?;
+
+    Ok(Value::Bool(c.is_alphabetic()))
+}
+
+fn is_whitespace(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.is_whitespace?", &arguments[0], token)?;
+
+    Ok(Value::Bool(c.is_whitespace()))
+}
+
+fn is_upper(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.is_upper?", &arguments[0], token)?;
+
+    Ok(Value::Bool(c.is_uppercase()))
+}
+
+fn is_lower(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.is_lower?", &arguments[0], token)?;
+
+    Ok(Value::Bool(c.is_lowercase()))
+}
+
+/// Characters whose uppercase form is more than one character, such as 'ß',
+/// are returned unchanged. `String.upcase` handles those.
+fn upcase(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.upcase", &arguments[0], token)?;
+
+    Ok(Value::Char(single(c.to_uppercase()).unwrap_or(c)))
+}
+
+fn downcase(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.downcase", &arguments[0], token)?;
+
+    Ok(Value::Char(single(c.to_lowercase()).unwrap_or(c)))
+}
+
+fn single(mut chars: impl Iterator<Item = char>) -> Option<char> {
+    match (chars.next(), chars.next()) {
+        (Some(c), None) => Some(c),
+        _ => None,
+    }
+}
+
+/// The unicode code point of the character.
+fn to_int(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.to_int", &arguments[0], token)?;
+
+    Ok(Value::Int(c as isize))
+}
+
+fn to_string(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let c = character("Char.to_string", &arguments[0], token)?;
+
+    Ok(Value::Str(c.to_string()))
+}
@@ -0,0 +1,143 @@
+//! Conversions between the basic types, defined in the global scope. The
+//! ones that can fail return `{ok: value}` or `{error: message}`.
+
+use super::{error, expected, ok, Builtin};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+use std::convert::TryFrom;
+
+pub const GLOBALS: &[Builtin] = &[
+    Builtin {
+        module: "",
+        name: "int_of_string",
+        arity: 1,
+        function: int_of_string,
+    },
+    Builtin {
+        module: "",
+        name: "bool_of_string",
+        arity: 1,
+        function: bool_of_string,
+    },
+    Builtin {
+        module: "",
+        name: "char_of_string",
+        arity: 1,
+        function: char_of_string,
+    },
+    Builtin {
+        module: "",
+        name: "char_of_int",
+        arity: 1,
+        function: char_of_int,
+    },
+    Builtin {
+        module: "",
+        name: "int_of_char",
+        arity: 1,
+        function: int_of_char,
+    },
+    Builtin {
+        module: "",
+        name: "string_of_int",
+        arity: 1,
+        function: string_of_int,
+    },
+    Builtin {
+        module: "",
+        name: "string_of_bool",
+        arity: 1,
+        function: string_of_bool,
+    },
+    Builtin {
+        module: "",
+        name: "string_of_char",
+        arity: 1,
+        function: string_of_char,
+    },
+];
+
+fn string<'a>(builtin: &str, value: &'a Value, token: &Token) -> Result<&'a str, String> {
+    match value {
+        Value::Str(s) => Ok(s),
+        other => Err(expected(builtin, "string", other, token)),
+    }
+}
+
+fn cannot_convert(s: &str, to: &str) -> Value {
+    error(format!("Cannot convert {:?} to {}", s, to))
+}
+
+/// Accepts an optional sign followed by decimal digits.
+fn int_of_string(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("int_of_string", &arguments[0], token)?;
+
+    Ok(match s.parse::<isize>() {
+        Ok(i) => ok(Value::Int(i)),
+        Err(_) => cannot_convert(s, "int"),
+    })
+}
+
+/// Accepts `True` and `False`, as they are written in source.
+fn bool_of_string(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("bool_of_string", &arguments[0], token)?;
+
+    Ok(match s {
+        "True" => ok(Value::Bool(true)),
+        "False" => ok(Value::Bool(false)),
+        _ => cannot_convert(s, "bool"),
+    })
+}
+
+fn char_of_string(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("char_of_string", &arguments[0], token)?;
+
+    let mut chars = s.chars();
+    Ok(match (chars.next(), chars.next()) {
+        (Some(c), None) => ok(Value::Char(c)),
+        _ => cannot_convert(s, "char"),
+    })
+}
+
+/// Converts a unicode code point to its character.
+fn char_of_int(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let i = match arguments[0] {
+        Value::Int(i) => i,
+        ref other => return Err(expected("char_of_int", "int", other, token).into()),
+    };
+
+    Ok(match u32::try_from(i).ok().and_then(std::char::from_u32) {
+        Some(c) => ok(Value::Char(c)),
+        None => error(format!("{} is not a unicode code point", i)),
+    })
+}
+
+fn int_of_char(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    match arguments[0] {
+        Value::Char(c) => Ok(Value::Int(c as isize)),
+        ref other => Err(expected("int_of_char", "char", other, token).into()),
+    }
+}
+
+fn string_of_int(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    match arguments[0] {
+        Value::Int(i) => Ok(Value::Str(i.to_string())),
+        ref other => Err(expected("string_of_int", "int", other, token).into()),
+    }
+}
+
+/// `True` or `False`, as they are written in source.
+fn string_of_bool(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    match arguments[0] {
+        Value::Bool(_) => Ok(Value::Str(arguments[0].to_string())),
+        ref other => Err(expected("string_of_bool", "bool", other, token).into()),
+    }
+}
+
+fn string_of_char(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    match arguments[0] {
+        Value::Char(c) => Ok(Value::Str(c.to_string())),
+        ref other => Err(expected("string_of_char", "char", other, token).into()),
+    }
+}
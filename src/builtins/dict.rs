@@ -0,0 +1,142 @@
+//! Dictionaries are immutable. `put`, `delete` and `merge` return a new
+//! dictionary and leave their argument unchanged.
+
+use super::{error, expected, ok, Builtin, Module};
+use crate::dict::{Dict, Key};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+use std::rc::Rc;
+
+pub const MODULE: Module = Module {
+    name: "Dict",
+    functions: &[
+        Builtin {
+            module: "Dict",
+            name: "get",
+            arity: 2,
+            function: get,
+        },
+        Builtin {
+            module: "Dict",
+            name: "put",
+            arity: 3,
+            function: put,
+        },
+        Builtin {
+            module: "Dict",
+            name: "delete",
+            arity: 2,
+            function: delete,
+        },
+        Builtin {
+            module: "Dict",
+            name: "keys",
+            arity: 1,
+            function: keys,
+        },
+        Builtin {
+            module: "Dict",
+            name: "values",
+            arity: 1,
+            function: values,
+        },
+        Builtin {
+            module: "Dict",
+            name: "has_key?",
+            arity: 2,
+            function: has_key,
+        },
+        Builtin {
+            module: "Dict",
+            name: "merge",
+            arity: 2,
+            function: merge,
+        },
+    ],
+};
+
+fn dict<'a>(builtin: &str, value: &'a Value, token: &Token) -> Result<&'a Rc<Dict>, String> {
+    match value {
+        Value::Dict(dict) => Ok(dict),
+        other => Err(expected(builtin, "dict", other, token)),
+    }
+}
+
+/// `{ok: value}` for the value at a key, or `{error: message}` when the
+/// dictionary has no such key. Indexing with `dict[key]` raises instead.
+fn get(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let dict = dict("Dict.get", &arguments[0], token)?;
+    let key = Key::of(&arguments[1], token)?;
+
+    Ok(match dict.get(&key) {
+        Some(value) => ok(value.clone()),
+        None => error(format!("Key {} not found", arguments[1].repr())),
+    })
+}
+
+fn put(_: &mut Interpreter, mut arguments: Vec<Value>, token: &Token) -> Eval {
+    let value = arguments.pop().unwrap();
+    let mut dict = dict("Dict.put", &arguments[0], token)?.clone();
+    let key = Key::of(&arguments[1], token)?;
+
+    Rc::make_mut(&mut dict).insert(key, value);
+
+    Ok(Value::Dict(dict))
+}
+
+fn delete(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let mut dict = dict("Dict.delete", &arguments[0], token)?.clone();
+    let key = Key::of(&arguments[1], token)?;
+
+    if dict.contains_key(&key) {
+        Rc::make_mut(&mut dict).remove(&key);
+    }
+
+    Ok(Value::Dict(dict))
+}
+
+fn keys(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let dict = dict("Dict.keys", &arguments[0], token)?;
+
+    Ok(Value::List(
+        dict.entries()
+            .iter()
+            .map(|(key, _)| key.to_value())
+            .collect(),
+    ))
+}
+
+fn values(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let dict = dict("Dict.values", &arguments[0], token)?;
+
+    Ok(Value::List(
+        dict.entries()
+            .iter()
+            .map(|(_, value)| value.clone())
+            .collect(),
+    ))
+}
+
+fn has_key(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let dict = dict("Dict.has_key?", &arguments[0], token)?;
+    let key = Key::of(&arguments[1], token)?;
+
+    Ok(Value::Bool(dict.contains_key(&key)))
+}
+
+/// Entries of the second dictionary replace those of the first. Keys only in
+/// the second are added after the first's keys, in the second's order.
+fn merge(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let mut merged = dict("Dict.merge", &arguments[0], token)?.clone();
+    let other = dict("Dict.merge", &arguments[1], token)?;
+
+    if !other.is_empty() {
+        let merged = Rc::make_mut(&mut merged);
+        for (key, value) in other.entries() {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+
+    Ok(Value::Dict(merged))
+}
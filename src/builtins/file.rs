@@ -0,0 +1,179 @@
+//! Local filesystem access. Failures such as a missing file return
+//! `{error: message}`. When the interpreter does not allow filesystem access
+//! every `File` and `Dir` function is an error instead; `Path` only works on
+//! strings and is always available.
+
+use super::{error, expected, ok, Builtin, Module};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+
+pub const FILE: Module = Module {
+    name: "File",
+    functions: &[
+        Builtin {
+            module: "File",
+            name: "read",
+            arity: 1,
+            function: read,
+        },
+        Builtin {
+            module: "File",
+            name: "write",
+            arity: 2,
+            function: write,
+        },
+        Builtin {
+            module: "File",
+            name: "append",
+            arity: 2,
+            function: append,
+        },
+        Builtin {
+            module: "File",
+            name: "exists?",
+            arity: 1,
+            function: exists,
+        },
+        Builtin {
+            module: "File",
+            name: "lines",
+            arity: 1,
+            function: lines,
+        },
+    ],
+};
+
+pub const DIR: Module = Module {
+    name: "Dir",
+    functions: &[Builtin {
+        module: "Dir",
+        name: "list",
+        arity: 1,
+        function: list,
+    }],
+};
+
+pub const PATH: Module = Module {
+    name: "Path",
+    functions: &[Builtin {
+        module: "Path",
+        name: "join",
+        arity: 2,
+        function: join,
+    }],
+};
+
+/// The path argument of a filesystem builtin, once access is known to be
+/// allowed.
+fn path<'a>(
+    interpreter: &Interpreter,
+    builtin: &str,
+    value: &'a Value,
+    token: &Token,
+) -> Result<&'a str, String> {
+    if !interpreter.filesystem_allowed() {
+        return Err(format!(
+            "{} is not allowed, filesystem access is disabled {}.{}",
+            builtin, token.row, token.col
+        ));
+    }
+
+    string(builtin, value, token)
+}
+
+fn string<'a>(builtin: &str, value: &'a Value, token: &Token) -> Result<&'a str, String> {
+    match value {
+        Value::Str(s) => Ok(s),
+        other => Err(expected(builtin, "string", other, token)),
+    }
+}
+
+fn result(outcome: io::Result<Value>, path: &str) -> Value {
+    match outcome {
+        Ok(value) => ok(value),
+        Err(failure) => error(format!("{}: {}", path, failure)),
+    }
+}
+
+fn read(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let path = path(interpreter, "File.read", &arguments[0], token)?;
+
+    Ok(result(fs::read_to_string(path).map(Value::Str), path))
+}
+
+/// Creates the file, or replaces its contents when it exists.
+fn write(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let path = path(interpreter, "File.write", &arguments[0], token)?;
+    let contents = string("File.write", &arguments[1], token)?;
+
+    Ok(result(fs::write(path, contents).map(|_| Value::Unit), path))
+}
+
+/// Adds to the end of the file, creating it when it does not exist.
+fn append(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let path = path(interpreter, "File.append", &arguments[0], token)?;
+    let contents = string("File.append", &arguments[1], token)?;
+
+    let outcome = OpenOptions::new()
+        .append(true)
+        .create(true)
+        .open(path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map(|_| Value::Unit);
+
+    Ok(result(outcome, path))
+}
+
+fn exists(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let path = path(interpreter, "File.exists?", &arguments[0], token)?;
+
+    Ok(Value::Bool(Path::new(path).exists()))
+}
+
+/// The lines of a file without their line endings.
+fn lines(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let path = path(interpreter, "File.lines", &arguments[0], token)?;
+
+    let outcome = fs::read_to_string(path).map(|contents| {
+        Value::List(
+            contents
+                .lines()
+                .map(|line| Value::Str(line.to_string()))
+                .collect(),
+        )
+    });
+
+    Ok(result(outcome, path))
+}
+
+/// The names of the entries in a directory, sorted.
+fn list(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let path = path(interpreter, "Dir.list", &arguments[0], token)?;
+
+    let outcome = fs::read_dir(path).and_then(|entries| {
+        let mut names = Vec::new();
+        for entry in entries {
+            names.push(entry?.file_name().to_string_lossy().into_owned());
+        }
+        names.sort();
+
+        Ok(Value::List(names.into_iter().map(Value::Str).collect()))
+    });
+
+    Ok(result(outcome, path))
+}
+
+/// Joins two paths with the platform's separator. A second path that is
+/// absolute replaces the first.
+fn join(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let base = string("Path.join", &arguments[0], token)?;
+    let other = string("Path.join", &arguments[1], token)?;
+
+    Ok(Value::Str(
+        Path::new(base).join(other).to_string_lossy().into_owned(),
+    ))
+}
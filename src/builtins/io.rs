@@ -0,0 +1,118 @@
+//! Output functions in the global scope and the `IO` module for reading
+//! input. Everything goes through the interpreter's `Io`, so output can be
+//! captured.
+
+use super::{error, ok, Builtin, Module};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+use std::io;
+
+pub const GLOBALS: &[Builtin] = &[
+    Builtin {
+        module: "",
+        name: "puts",
+        arity: 1,
+        function: puts,
+    },
+    Builtin {
+        module: "",
+        name: "print",
+        arity: 1,
+        function: print,
+    },
+    Builtin {
+        module: "",
+        name: "eputs",
+        arity: 1,
+        function: eputs,
+    },
+];
+
+pub const MODULE: Module = Module {
+    name: "IO",
+    functions: &[
+        Builtin {
+            module: "IO",
+            name: "read_line",
+            arity: 0,
+            function: read_line,
+        },
+        Builtin {
+            module: "IO",
+            name: "read_all",
+            arity: 0,
+            function: read_all,
+        },
+        Builtin {
+            module: "IO",
+            name: "lines",
+            arity: 0,
+            function: lines,
+        },
+    ],
+};
+
+fn io_error(builtin: &str, error: io::Error, token: &Token) -> String {
+    format!("{} failed: {} {}.{}", builtin, error, token.row, token.col)
+}
+
+/// Writes a value followed by a new line. Strings and characters are written
+/// without quotes.
+fn puts(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let text = format!("{}\n", arguments[0]);
+
+    match interpreter.io().write_out(&text) {
+        Ok(()) => Ok(Value::Unit),
+        Err(error) => Err(io_error("puts", error, token).into()),
+    }
+}
+
+/// Like `puts`, without the new line.
+fn print(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let text = arguments[0].to_string();
+
+    match interpreter.io().write_out(&text) {
+        Ok(()) => Ok(Value::Unit),
+        Err(error) => Err(io_error("print", error, token).into()),
+    }
+}
+
+/// Like `puts`, writing to stderr.
+fn eputs(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let text = format!("{}\n", arguments[0]);
+
+    match interpreter.io().write_err(&text) {
+        Ok(()) => Ok(Value::Unit),
+        Err(error) => Err(io_error("eputs", error, token).into()),
+    }
+}
+
+/// `{ok: line}` without the line ending, or `{error: "end of input"}`.
+fn read_line(interpreter: &mut Interpreter, _: Vec<Value>, token: &Token) -> Eval {
+    match interpreter.io().read_line() {
+        Ok(Some(line)) => Ok(ok(Value::Str(line))),
+        Ok(None) => Ok(error(String::from("end of input"))),
+        Err(error) => Err(io_error("IO.read_line", error, token).into()),
+    }
+}
+
+fn read_all(interpreter: &mut Interpreter, _: Vec<Value>, token: &Token) -> Eval {
+    match interpreter.io().read_all() {
+        Ok(input) => Ok(Value::Str(input)),
+        Err(error) => Err(io_error("IO.read_all", error, token).into()),
+    }
+}
+
+/// The remaining lines of input as a list of strings.
+fn lines(interpreter: &mut Interpreter, _: Vec<Value>, token: &Token) -> Eval {
+    let mut lines = Vec::new();
+
+    loop {
+        match interpreter.io().read_line() {
+            Ok(Some(line)) => lines.push(Value::Str(line)),
+            Ok(None) => return Ok(Value::List(lines.into_iter().collect())),
+            Err(error) => return Err(io_error("IO.lines", error, token).into()),
+        }
+    }
+}
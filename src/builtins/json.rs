@@ -0,0 +1,57 @@
+use super::{error, expected, ok, Builtin, Module};
+use crate::interpreter::{Eval, Interpreter};
+use crate::json;
+use crate::token::Token;
+use crate::value::Value;
+
+pub const MODULE: Module = Module {
+    name: "Json",
+    functions: &[
+        Builtin {
+            module: "Json",
+            name: "decode",
+            arity: 1,
+            function: decode,
+        },
+        Builtin {
+            module: "Json",
+            name: "encode",
+            arity: 1,
+            function: encode,
+        },
+        Builtin {
+            module: "Json",
+            name: "encode_pretty",
+            arity: 1,
+            function: encode_pretty,
+        },
+    ],
+};
+
+/// `{ok: value}`, or `{error: message}` with the line and column of the
+/// first problem in the text.
+fn decode(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let text = match &arguments[0] {
+        Value::Str(text) => text,
+        other => return Err(expected("Json.decode", "string", other, token).into()),
+    };
+
+    Ok(match json::decode(text) {
+        Ok(value) => ok(value),
+        Err(failure) => error(failure.to_string()),
+    })
+}
+
+fn encode(_: &mut Interpreter, arguments: Vec<Value>, _: &Token) -> Eval {
+    Ok(match json::encode(&arguments[0], false) {
+        Ok(text) => ok(Value::Str(text)),
+        Err(message) => error(message),
+    })
+}
+
+fn encode_pretty(_: &mut Interpreter, arguments: Vec<Value>, _: &Token) -> Eval {
+    Ok(match json::encode(&arguments[0], true) {
+        Ok(text) => ok(Value::Str(text)),
+        Err(message) => error(message),
+    })
+}
@@ -0,0 +1,371 @@
+//! Lists are immutable. Functions that build a list share as much of their
+//! arguments as they can: `tail` is O(1) and `append` copies only its first
+//! list. Every function taking a list also takes a range, whose ints are
+//! produced one at a time rather than built into a list first.
+
+use super::{error, expected, ok, Builtin, Module};
+use crate::interpreter::{Control, Eval, Interpreter};
+use crate::list::{self, List};
+use crate::token::Token;
+use crate::value::Value;
+use std::cmp::Ordering;
+use std::convert::TryFrom;
+
+pub const MODULE: Module = Module {
+    name: "List",
+    functions: &[
+        Builtin {
+            module: "List",
+            name: "nth",
+            arity: 2,
+            function: nth,
+        },
+        Builtin {
+            module: "List",
+            name: "length",
+            arity: 1,
+            function: length,
+        },
+        Builtin {
+            module: "List",
+            name: "head",
+            arity: 1,
+            function: head,
+        },
+        Builtin {
+            module: "List",
+            name: "tail",
+            arity: 1,
+            function: tail,
+        },
+        Builtin {
+            module: "List",
+            name: "reverse",
+            arity: 1,
+            function: reverse,
+        },
+        Builtin {
+            module: "List",
+            name: "map",
+            arity: 2,
+            function: map,
+        },
+        Builtin {
+            module: "List",
+            name: "filter",
+            arity: 2,
+            function: filter,
+        },
+        Builtin {
+            module: "List",
+            name: "fold",
+            arity: 3,
+            function: fold,
+        },
+        Builtin {
+            module: "List",
+            name: "append",
+            arity: 2,
+            function: append,
+        },
+        Builtin {
+            module: "List",
+            name: "concat",
+            arity: 1,
+            function: concat,
+        },
+        Builtin {
+            module: "List",
+            name: "sort",
+            arity: 1,
+            function: sort,
+        },
+        Builtin {
+            module: "List",
+            name: "zip",
+            arity: 2,
+            function: zip,
+        },
+        Builtin {
+            module: "List",
+            name: "find",
+            arity: 2,
+            function: find,
+        },
+        Builtin {
+            module: "List",
+            name: "any?",
+            arity: 2,
+            function: any,
+        },
+        Builtin {
+            module: "List",
+            name: "all?",
+            arity: 2,
+            function: all,
+        },
+    ],
+};
+
+/// The elements of a list, or the ints of a range.
+enum Elements<'a> {
+    List(list::Iter<'a>),
+    Range(std::ops::Range<isize>),
+}
+
+impl<'a> Iterator for Elements<'a> {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Value> {
+        match self {
+            Elements::List(iter) => iter.next().cloned(),
+            Elements::Range(range) => range.next().map(Value::Int),
+        }
+    }
+}
+
+fn elements<'a>(builtin: &str, value: &'a Value, token: &Token) -> Result<Elements<'a>, String> {
+    match value {
+        Value::List(list) => Ok(Elements::List(list.iter())),
+        Value::Range(start, end) => Ok(Elements::Range(*start..*end)),
+        other => Err(expected(builtin, "list or range", other, token)),
+    }
+}
+
+/// How many ints a range holds, if that fits in an int.
+fn range_length(start: isize, end: isize) -> Option<isize> {
+    let length = (end as i128 - start as i128).max(0);
+    isize::try_from(length).ok()
+}
+
+/// Calls a predicate passed to `builtin`, which must return a bool.
+fn test(
+    interpreter: &mut Interpreter,
+    builtin: &str,
+    predicate: &Value,
+    element: &Value,
+    token: &Token,
+) -> Result<bool, Control> {
+    match interpreter.call(predicate.clone(), vec![element.clone()], token)? {
+        Value::Bool(b) => Ok(b),
+        other => Err(Control::Error(format!(
+            "{} expected the function to return bool, got {} {}.{}",
+            builtin,
+            other.type_name(),
+            token.row,
+            token.col
+        ))),
+    }
+}
+
+fn nth(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let n = match arguments[1] {
+        Value::Int(n) => n,
+        ref other => return Err(expected("List.nth", "int", other, token).into()),
+    };
+
+    let element = match &arguments[0] {
+        Value::Range(start, end) => {
+            let length = range_length(*start, *end).unwrap_or(isize::MAX);
+            (n >= 0 && n < length).then(|| Value::Int(start + n))
+        }
+        other => {
+            let mut elements = elements("List.nth", other, token)?;
+            usize::try_from(n).ok().and_then(|n| elements.nth(n))
+        }
+    };
+
+    element.ok_or_else(|| {
+        format!(
+            "Index {} out of bounds for list {}.{}",
+            n, token.row, token.col
+        )
+        .into()
+    })
+}
+
+fn length(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    match &arguments[0] {
+        Value::Range(start, end) => match range_length(*start, *end) {
+            Some(length) => Ok(Value::Int(length)),
+            None => Err(format!(
+                "List.length of {}..{} is too large for an int {}.{}",
+                start, end, token.row, token.col
+            )
+            .into()),
+        },
+        other => Ok(Value::Int(
+            elements("List.length", other, token)?.count() as isize
+        )),
+    }
+}
+
+fn head(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    match elements("List.head", &arguments[0], token)?.next() {
+        Some(head) => Ok(head),
+        None => Err(format!(
+            "Cannot take the head of an empty list {}.{}",
+            token.row, token.col
+        )
+        .into()),
+    }
+}
+
+/// The list after the first element, or for a range the range after it.
+fn tail(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let tail = match &arguments[0] {
+        Value::Range(start, end) if start < end => Some(Value::Range(start + 1, *end)),
+        Value::List(list) => list.tail().map(|tail| Value::List(tail.clone())),
+        other => {
+            elements("List.tail", other, token)?;
+            None
+        }
+    };
+
+    tail.ok_or_else(|| {
+        format!(
+            "Cannot take the tail of an empty list {}.{}",
+            token.row, token.col
+        )
+        .into()
+    })
+}
+
+fn reverse(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let elements = elements("List.reverse", &arguments[0], token)?;
+
+    Ok(Value::List(
+        elements.fold(List::new(), |reversed, element| reversed.prepend(element)),
+    ))
+}
+
+fn map(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let elements = elements("List.map", &arguments[0], token)?;
+
+    let mut mapped = Vec::with_capacity(elements.size_hint().0);
+    for element in elements {
+        mapped.push(interpreter.call(arguments[1].clone(), vec![element], token)?);
+    }
+
+    Ok(Value::List(mapped.into_iter().collect()))
+}
+
+fn filter(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let elements = elements("List.filter", &arguments[0], token)?;
+
+    let mut kept = Vec::new();
+    for element in elements {
+        if test(interpreter, "List.filter", &arguments[1], &element, token)? {
+            kept.push(element);
+        }
+    }
+
+    Ok(Value::List(kept.into_iter().collect()))
+}
+
+/// `fold(list, initial, f)` calls `f(accumulator, element)` for each element
+/// from the front.
+fn fold(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let elements = elements("List.fold", &arguments[0], token)?;
+
+    let mut accumulator = arguments[1].clone();
+    for element in elements {
+        accumulator = interpreter.call(arguments[2].clone(), vec![accumulator, element], token)?;
+    }
+
+    Ok(accumulator)
+}
+
+/// The elements of the first list followed by the second list, which is
+/// shared rather than copied.
+fn append(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let first: Vec<Value> = elements("List.append", &arguments[0], token)?.collect();
+    let second = match &arguments[1] {
+        Value::List(list) => list.clone(),
+        other => elements("List.append", other, token)?.collect(),
+    };
+
+    Ok(Value::List(
+        first
+            .into_iter()
+            .rev()
+            .fold(second, |list, element| list.prepend(element)),
+    ))
+}
+
+fn concat(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let mut concatenated = Vec::new();
+    for inner in elements("List.concat", &arguments[0], token)? {
+        concatenated.extend(elements("List.concat", &inner, token)?);
+    }
+
+    Ok(Value::List(concatenated.into_iter().collect()))
+}
+
+/// A stable sort of ints, strings, characters or lists of them.
+fn sort(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let mut elements: Vec<Value> = elements("List.sort", &arguments[0], token)?.collect();
+    let mut incomparable = None;
+
+    elements.sort_by(|a, b| {
+        a.compare(b).unwrap_or_else(|| {
+            incomparable.get_or_insert((a.type_name(), b.type_name()));
+            Ordering::Equal
+        })
+    });
+
+    match incomparable {
+        Some((a, b)) => Err(format!(
+            "List.sort cannot compare {} and {} {}.{}",
+            a, b, token.row, token.col
+        )
+        .into()),
+        None => Ok(Value::List(elements.into_iter().collect())),
+    }
+}
+
+/// Pairs up elements as `[a, b]` lists, stopping at the end of the shorter
+/// list.
+fn zip(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let first = elements("List.zip", &arguments[0], token)?;
+    let second = elements("List.zip", &arguments[1], token)?;
+
+    Ok(Value::List(
+        first
+            .zip(second)
+            .map(|(a, b)| Value::List(List::new().prepend(b).prepend(a)))
+            .collect(),
+    ))
+}
+
+/// `{ok: element}` for the first element the predicate accepts, otherwise
+/// `{error: "not found"}`.
+fn find(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    for element in elements("List.find", &arguments[0], token)? {
+        if test(interpreter, "List.find", &arguments[1], &element, token)? {
+            return Ok(ok(element));
+        }
+    }
+
+    Ok(error(String::from("not found")))
+}
+
+fn any(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    for element in elements("List.any?", &arguments[0], token)? {
+        if test(interpreter, "List.any?", &arguments[1], &element, token)? {
+            return Ok(Value::Bool(true));
+        }
+    }
+
+    Ok(Value::Bool(false))
+}
+
+fn all(interpreter: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    for element in elements("List.all?", &arguments[0], token)? {
+        if !test(interpreter, "List.all?", &arguments[1], &element, token)? {
+            return Ok(Value::Bool(false));
+        }
+    }
+
+    Ok(Value::Bool(true))
+}
@@ -0,0 +1,99 @@
+//! Functions provided by the runtime, grouped into modules such as `Array`
+//! that are defined in the global scope.
+//!
+//! Builtins that can fail for reasons a script should handle return
+//! `{ok: value}` or `{error: message}` so the outcome can be matched on.
+
+mod array;
+mod char;
+mod conversion;
+mod dict;
+mod file;
+mod io;
+mod json;
+mod list;
+mod string;
+
+use crate::dict::{Dict, Key};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+use std::fmt;
+use std::rc::Rc;
+
+pub struct Builtin {
+    /// Empty for builtins defined directly in the global scope.
+    pub module: &'static str,
+    pub name: &'static str,
+    pub arity: usize,
+    pub function: fn(&mut Interpreter, Vec<Value>, &Token) -> Eval,
+}
+
+impl fmt::Debug for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Builtin({})", self)
+    }
+}
+
+impl fmt::Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.module {
+            "" => write!(f, "{}", self.name),
+            module => write!(f, "{}.{}", module, self.name),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Module {
+    pub name: &'static str,
+    pub functions: &'static [Builtin],
+}
+
+impl Module {
+    pub fn get(&'static self, name: &str) -> Option<&'static Builtin> {
+        self.functions.iter().find(|builtin| builtin.name == name)
+    }
+}
+
+/// Builtins defined directly in the global scope.
+pub const GLOBALS: &[&[Builtin]] = &[conversion::GLOBALS, io::GLOBALS];
+
+pub const MODULES: &[&Module] = &[
+    &array::MODULE,
+    &char::MODULE,
+    &dict::MODULE,
+    &file::DIR,
+    &file::FILE,
+    &io::MODULE,
+    &json::MODULE,
+    &list::MODULE,
+    &file::PATH,
+    &string::MODULE,
+];
+
+/// The error for a builtin called with an argument of the wrong type.
+fn expected(builtin: &str, expected: &str, got: &Value, token: &Token) -> String {
+    format!(
+        "{} expected {}, got {} {}.{}",
+        builtin,
+        expected,
+        got.type_name(),
+        token.row,
+        token.col
+    )
+}
+
+/// `{ok: value}`, what builtins that can fail return on success.
+fn ok(value: Value) -> Value {
+    let mut dict = Dict::new();
+    dict.insert(Key::Str(String::from("ok")), value);
+    Value::Dict(Rc::new(dict))
+}
+
+/// `{error: message}`, what builtins that can fail return on failure.
+fn error(message: String) -> Value {
+    let mut dict = Dict::new();
+    dict.insert(Key::Str(String::from("error")), Value::Str(message));
+    Value::Dict(Rc::new(dict))
+}
@@ -0,0 +1,204 @@
+//! Strings are sequences of unicode characters. Lengths and positions count
+//! characters, never bytes.
+
+use super::{expected, Builtin, Module};
+use crate::interpreter::{Eval, Interpreter};
+use crate::token::Token;
+use crate::value::Value;
+
+pub const MODULE: Module = Module {
+    name: "String",
+    functions: &[
+        Builtin {
+            module: "String",
+            name: "length",
+            arity: 1,
+            function: length,
+        },
+        Builtin {
+            module: "String",
+            name: "split",
+            arity: 2,
+            function: split,
+        },
+        Builtin {
+            module: "String",
+            name: "join",
+            arity: 2,
+            function: join,
+        },
+        Builtin {
+            module: "String",
+            name: "trim",
+            arity: 1,
+            function: trim,
+        },
+        Builtin {
+            module: "String",
+            name: "upcase",
+            arity: 1,
+            function: upcase,
+        },
+        Builtin {
+            module: "String",
+            name: "downcase",
+            arity: 1,
+            function: downcase,
+        },
+        Builtin {
+            module: "String",
+            name: "contains?",
+            arity: 2,
+            function: contains,
+        },
+        Builtin {
+            module: "String",
+            name: "starts_with?",
+            arity: 2,
+            function: starts_with,
+        },
+        Builtin {
+            module: "String",
+            name: "ends_with?",
+            arity: 2,
+            function: ends_with,
+        },
+        Builtin {
+            module: "String",
+            name: "replace",
+            arity: 3,
+            function: replace,
+        },
+        Builtin {
+            module: "String",
+            name: "chars",
+            arity: 1,
+            function: chars,
+        },
+        Builtin {
+            module: "String",
+            name: "to_list",
+            arity: 1,
+            function: to_list,
+        },
+    ],
+};
+
+fn string<'a>(builtin: &str, value: &'a Value, token: &Token) -> Result<&'a str, String> {
+    match value {
+        Value::Str(s) => Ok(s),
+        other => Err(expected(builtin, "string", other, token)),
+    }
+}
+
+/// A string argument that is used as a pattern and so cannot be empty.
+fn pattern<'a>(builtin: &str, value: &'a Value, token: &Token) -> Result<&'a str, String> {
+    match string(builtin, value, token)? {
+        "" => Err(format!(
+            "{} expected a non-empty string {}.{}",
+            builtin, token.row, token.col
+        )),
+        s => Ok(s),
+    }
+}
+
+fn length(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.length", &arguments[0], token)?;
+
+    Ok(Value::Int(s.chars().count() as isize))
+}
+
+fn split(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.split", &arguments[0], token)?;
+    let separator = pattern("String.split", &arguments[1], token)?;
+
+    Ok(Value::List(
+        s.split(separator)
+            .map(|part| Value::Str(part.to_string()))
+            .collect(),
+    ))
+}
+
+/// `join(list, separator)`. The list may hold strings and characters.
+fn join(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let list = match &arguments[0] {
+        Value::List(list) => list,
+        other => return Err(expected("String.join", "list", other, token).into()),
+    };
+    let separator = string("String.join", &arguments[1], token)?;
+
+    let mut parts = Vec::with_capacity(list.len());
+    for element in list.iter() {
+        match element {
+            Value::Str(s) => parts.push(s.clone()),
+            Value::Char(c) => parts.push(c.to_string()),
+            other => return Err(expected("String.join", "a list of strings", other, token).into()),
+        }
+    }
+
+    Ok(Value::Str(parts.join(separator)))
+}
+
+fn trim(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.trim", &arguments[0], token)?;
+
+    Ok(Value::Str(s.trim().to_string()))
+}
+
+fn upcase(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.upcase", &arguments[0], token)?;
+
+    Ok(Value::Str(s.to_uppercase()))
+}
+
+fn downcase(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.downcase", &arguments[0], token)?;
+
+    Ok(Value::Str(s.to_lowercase()))
+}
+
+fn contains(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.contains?", &arguments[0], token)?;
+    let part = string("String.contains?", &arguments[1], token)?;
+
+    Ok(Value::Bool(s.contains(part)))
+}
+
+fn starts_with(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.starts_with?", &arguments[0], token)?;
+    let prefix = string("String.starts_with?", &arguments[1], token)?;
+
+    Ok(Value::Bool(s.starts_with(prefix)))
+}
+
+fn ends_with(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.ends_with?", &arguments[0], token)?;
+    let suffix = string("String.ends_with?", &arguments[1], token)?;
+
+    Ok(Value::Bool(s.ends_with(suffix)))
+}
+
+/// `replace(string, from, to)` replaces every occurrence of `from`.
+fn replace(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.replace", &arguments[0], token)?;
+    let from = pattern("String.replace", &arguments[1], token)?;
+    let to = string("String.replace", &arguments[2], token)?;
+
+    Ok(Value::Str(s.replace(from, to)))
+}
+
+/// The characters of a string as a list of chars.
+fn chars(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.chars", &arguments[0], token)?;
+
+    Ok(Value::List(s.chars().map(Value::Char).collect()))
+}
+
+/// The characters of a string as a list of one character strings.
+fn to_list(_: &mut Interpreter, arguments: Vec<Value>, token: &Token) -> Eval {
+    let s = string("String.to_list", &arguments[0], token)?;
+
+    Ok(Value::List(
+        s.chars().map(|c| Value::Str(c.to_string())).collect(),
+    ))
+}
@@ -0,0 +1,438 @@
+//! The command line interface: parses the arguments `slice` was started with
+//! into a `Command` and runs it.
+
+use crate::analysis::analyze;
+use crate::ast::{imports, ImportStatement};
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::editor::{Editor, History, Input};
+use crate::format::format;
+use crate::interpreter::{file_name, Interpreter};
+use crate::lexer::Lexer;
+use crate::lint::{lint, LintConfig};
+use crate::lsp;
+use crate::parser::Parser;
+use crate::repl::Repl;
+use crate::signal;
+use crate::source::SourceMap;
+use crate::token::{Token, TokenType};
+use crate::tree::tree;
+use crate::typecheck::type_check;
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+pub const USAGE: &str = "\
+usage: slice <command> [arguments]
+
+commands:
+    run <file> [args]    run a script, passing it args as Env.args
+    check [--lint <lint>=<level>] <file>
+                         parse and type-check a script and the files it
+                         imports without running them;
+                         --lint sets a lint to allow, warn or deny
+    tokens <file>        print the tokens of a script
+    ast <file>           print the syntax tree of a script
+    fmt [--check] [files]
+                         format scripts in place, or stdin to stdout; with
+                         --check only report the ones that are not formatted
+    repl                 start an interactive session
+    lsp                  start a language server on stdin and stdout
+    help                 print this message
+
+Arguments after -- are passed to the script even if they start with -.";
+
+/// Where the REPL keeps its history, relative to the home directory.
+const HISTORY_FILE: &str = ".slice_history";
+
+/// The program failed: a script error, a parse error or failed check.
+pub const EXIT_FAILURE: i32 = 1;
+/// `slice` itself was called wrong.
+pub const EXIT_USAGE: i32 = 2;
+
+#[derive(PartialEq, Debug)]
+pub enum Command {
+    Run { path: String, args: Vec<String> },
+    Check { path: String, lints: LintConfig },
+    Tokens { path: String },
+    Ast { path: String },
+    Fmt { paths: Vec<String>, check: bool },
+    Repl,
+    Lsp,
+    Help,
+}
+
+pub fn parse_args(args: &[String]) -> Result<Command, String> {
+    let (command, rest) = match args.split_first() {
+        Some((command, rest)) => (command.as_str(), rest),
+        None => return Ok(Command::Repl),
+    };
+
+    match command {
+        "run" => {
+            let (path, rest) = match rest.split_first() {
+                Some((path, rest)) if !path.starts_with('-') => (path.clone(), rest),
+                Some((flag, _)) => return Err(format!("Unknown option {} for run", flag)),
+                None => return Err(String::from("run expects a file")),
+            };
+            let (args, passed) = match rest.iter().position(|arg| arg == "--") {
+                Some(i) => (&rest[..i], &rest[i + 1..]),
+                None => (rest, &[][..]),
+            };
+
+            if let Some(flag) = args.iter().find(|arg| arg.starts_with('-')) {
+                return Err(format!(
+                    "Unknown option {} for run, use -- to pass it to the script",
+                    flag
+                ));
+            }
+
+            Ok(Command::Run {
+                path,
+                args: args.iter().chain(passed).cloned().collect(),
+            })
+        }
+        "check" => {
+            let mut paths = Vec::new();
+            let mut lints = LintConfig::default();
+            let mut args = rest.iter();
+
+            while let Some(arg) = args.next() {
+                match arg.as_str() {
+                    "--lint" => match args.next() {
+                        Some(setting) => lints.parse_setting(setting)?,
+                        None => return Err(String::from("--lint expects lint=level")),
+                    },
+                    flag if flag.starts_with('-') => {
+                        return Err(format!("Unknown option {} for check", flag))
+                    }
+                    path => paths.push(path.to_string()),
+                }
+            }
+
+            match paths.len() {
+                0 => Err(String::from("check expects a file")),
+                1 => Ok(Command::Check {
+                    path: paths.remove(0),
+                    lints,
+                }),
+                _ => Err(String::from("check expects exactly one file")),
+            }
+        }
+        "tokens" | "ast" => {
+            let path = match rest {
+                [path] if !path.starts_with('-') => path.clone(),
+                [] => return Err(format!("{} expects a file", command)),
+                _ => return Err(format!("{} expects exactly one file", command)),
+            };
+
+            Ok(match command {
+                "tokens" => Command::Tokens { path },
+                _ => Command::Ast { path },
+            })
+        }
+        "fmt" => {
+            let mut paths = Vec::new();
+            let mut check = false;
+
+            for arg in rest {
+                match arg.as_str() {
+                    "--check" => check = true,
+                    flag if flag.starts_with('-') => {
+                        return Err(format!("Unknown option {} for fmt", flag))
+                    }
+                    path => paths.push(path.to_string()),
+                }
+            }
+
+            Ok(Command::Fmt { paths, check })
+        }
+        "repl" if rest.is_empty() => Ok(Command::Repl),
+        "repl" => Err(String::from("repl takes no arguments")),
+        "lsp" if rest.is_empty() => Ok(Command::Lsp),
+        "lsp" => Err(String::from("lsp takes no arguments")),
+        "help" | "-h" | "--help" => Ok(Command::Help),
+        _ => Err(format!("Unknown command {}", command)),
+    }
+}
+
+/// Runs `slice` with `args`, not including the program name, and returns the
+/// exit code.
+pub fn main(args: &[String]) -> i32 {
+    let command = match parse_args(args) {
+        Ok(command) => command,
+        Err(error) => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return EXIT_USAGE;
+        }
+    };
+
+    let result = match command {
+        Command::Run { path, args } => run(&path, args),
+        Command::Check { path, lints } => check(&path, &lints, &mut io::stderr()),
+        Command::Tokens { path } => tokens(&path),
+        Command::Ast { path } => ast(&path),
+        Command::Fmt { paths, check } => fmt(&paths, check),
+        Command::Repl => repl(),
+        Command::Lsp => {
+            let stdin = io::stdin();
+            let stdout = io::stdout();
+
+            return match lsp::serve(&mut stdin.lock(), &mut stdout.lock()) {
+                Ok(code) => code,
+                Err(error) => {
+                    eprintln!("error: {}", error);
+                    EXIT_FAILURE
+                }
+            };
+        }
+        Command::Help => {
+            println!("{}", USAGE);
+            Ok(())
+        }
+    };
+
+    match result {
+        Ok(()) => 0,
+        Err(error) => {
+            if !error.is_empty() {
+                eprintln!("error: {}", error);
+            }
+            EXIT_FAILURE
+        }
+    }
+}
+
+fn read(path: &str) -> Result<String, String> {
+    fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))
+}
+
+fn run(path: &str, args: Vec<String>) -> Result<(), String> {
+    let mut interpreter = Interpreter::new();
+    interpreter.set_args(args);
+    interpreter
+        .run_file(path)
+        .map(|_| ())
+        .map_err(|error| interpreter.describe(&error))
+}
+
+/// Checks a file and every file it imports, writing every diagnostic to
+/// `out`, and fails if any of them is an error. The diagnostics are already
+/// written, so the error message is empty.
+pub fn check(path: &str, lints: &LintConfig, out: &mut impl Write) -> Result<(), String> {
+    let source = read(path)?;
+    let mut checked = Checked {
+        lints,
+        sources: SourceMap::new(),
+        files: Vec::new(),
+        loading: Vec::new(),
+        diagnostics: Vec::new(),
+    };
+    checked.file(Path::new(path), source);
+    checked
+        .diagnostics
+        .sort_by_key(|diagnostic| (diagnostic.file, diagnostic.row, diagnostic.col));
+
+    for diagnostic in &checked.diagnostics {
+        writeln!(out, "{}", checked.sources.render(diagnostic)).map_err(|e| e.to_string())?;
+    }
+
+    if checked
+        .diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.severity == Severity::Error)
+    {
+        Err(String::new())
+    } else {
+        Ok(())
+    }
+}
+
+/// The files `check` has gone through and what it found in them.
+struct Checked<'a> {
+    lints: &'a LintConfig,
+    sources: SourceMap,
+    /// Every file checked so far, by canonical path, so each is checked once.
+    files: Vec<PathBuf>,
+    /// The files being checked, each imported by the one before it.
+    loading: Vec<PathBuf>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Checked<'_> {
+    /// Checks the file at `path`, which holds `source`, and then the files
+    /// it imports, which are found relative to it as `run` finds them.
+    fn file(&mut self, path: &Path, source: String) {
+        let file = self.sources.add(path.display().to_string(), source.clone());
+
+        let ast = match Parser::new(Lexer::new(&source).in_file(file)).parse_program() {
+            Ok(ast) => ast,
+            Err(error) => {
+                self.diagnostics.push(Diagnostic::from_error(&error, file));
+                return;
+            }
+        };
+
+        self.diagnostics.extend(type_check(&ast).diagnostics);
+        self.diagnostics.extend(analyze(&ast));
+        self.diagnostics.extend(lint(&ast, self.lints));
+
+        let canonical = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        self.files.push(canonical.clone());
+        self.loading.push(canonical);
+        for import in imports(&ast) {
+            self.import(path, import);
+        }
+        self.loading.pop();
+    }
+
+    fn import(&mut self, from: &Path, import: &ImportStatement) {
+        let path = match from.parent() {
+            Some(directory) => directory.join(import.path()),
+            None => import.path(),
+        };
+        let unreadable = |e: io::Error| format!("Cannot import {}: {}", path.display(), e);
+
+        let canonical = match fs::canonicalize(&path) {
+            Ok(canonical) => canonical,
+            Err(e) => return self.error(unreadable(e), &import.token),
+        };
+        if let Some(start) = self.loading.iter().position(|file| *file == canonical) {
+            let cycle: Vec<String> = self.loading[start..]
+                .iter()
+                .chain(Some(&canonical))
+                .map(|file| file_name(file))
+                .collect();
+
+            return self.error(
+                format!("Import cycle {}", cycle.join(" -> ")),
+                &import.token,
+            );
+        }
+        if self.files.contains(&canonical) {
+            return;
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(source) => self.file(&path, source),
+            Err(e) => self.error(unreadable(e), &import.token),
+        }
+    }
+
+    fn error(&mut self, message: String, token: &Token) {
+        self.diagnostics.push(Diagnostic::error(message, token));
+    }
+}
+
+fn tokens(path: &str) -> Result<(), String> {
+    let source = read(path)?;
+    let mut sources = SourceMap::new();
+    let file = sources.add(path.to_string(), source.clone());
+    let mut lexer = Lexer::new(&source).in_file(file);
+
+    print_out(|out| loop {
+        let token = lexer.next();
+        if token.token_type == TokenType::EOF {
+            return Ok(());
+        }
+        writeln!(out, "{} {}", sources.locate(&token), token)?;
+    })
+}
+
+fn ast(path: &str) -> Result<(), String> {
+    let source = read(path)?;
+    let mut sources = SourceMap::new();
+    let file = sources.add(path.to_string(), source.clone());
+    let ast = Parser::new(Lexer::new(&source).in_file(file))
+        .parse_program()
+        .map_err(|error| sources.place(file, &error))?;
+
+    print_out(|out| out.write_all(tree(&ast).as_bytes()))
+}
+
+/// Writes to a locked stdout. A reader that stops early, as `head` does,
+/// closes the pipe, which ends the output without an error.
+fn print_out(write: impl FnOnce(&mut io::StdoutLock) -> io::Result<()>) -> Result<(), String> {
+    let stdout = io::stdout();
+    let mut out = stdout.lock();
+
+    match write(&mut out).and_then(|()| out.flush()) {
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => Err(e.to_string()),
+        _ => Ok(()),
+    }
+}
+
+/// Formats each file in place, or checks that it is formatted. Every file
+/// is handled even when an earlier one fails.
+fn fmt(paths: &[String], check: bool) -> Result<(), String> {
+    let mut sources = SourceMap::new();
+
+    if paths.is_empty() {
+        let mut source = String::new();
+        io::stdin()
+            .read_to_string(&mut source)
+            .map_err(|e| e.to_string())?;
+        let file = sources.add(String::from("<stdin>"), source.clone());
+        let formatted = format(&source).map_err(|error| sources.place(file, &error))?;
+
+        return match check {
+            true if formatted != source => Err(String::from("stdin is not formatted")),
+            true => Ok(()),
+            false => print_out(|out| out.write_all(formatted.as_bytes())),
+        };
+    }
+
+    let mut failed = false;
+    for path in paths {
+        let result = read(path).and_then(|source| {
+            let file = sources.add(path.to_string(), source.clone());
+            let formatted = format(&source).map_err(|error| sources.place(file, &error))?;
+
+            if formatted == source {
+                Ok(())
+            } else if check {
+                Err(format!("{} is not formatted", path))
+            } else {
+                fs::write(path, formatted).map_err(|e| format!("{}: {}", path, e))
+            }
+        });
+
+        if let Err(error) = result {
+            eprintln!("error: {}", error);
+            failed = true;
+        }
+    }
+
+    if failed {
+        Err(String::new())
+    } else {
+        Ok(())
+    }
+}
+
+fn repl() -> Result<(), String> {
+    let history = env::var_os("HOME").map(|home| PathBuf::from(home).join(HISTORY_FILE));
+    let editor = Rc::new(RefCell::new(Editor::new(History::load(history))));
+    let mut interpreter = Interpreter::with_io(editor.clone());
+    interpreter.interrupt_on(&signal::INTERRUPTED);
+    let mut repl = Repl::new(interpreter);
+
+    loop {
+        let input = editor
+            .borrow_mut()
+            .read_line(repl.prompt(), |line| repl.complete(line))
+            .map_err(|e| e.to_string())?;
+
+        match input {
+            Input::Line(line) => match repl.eval_line(&line) {
+                Ok(Some(output)) => println!("{}", output),
+                Ok(None) => {}
+                Err(error) => eprintln!("error: {}", error),
+            },
+            Input::Interrupted => repl.cancel(),
+            Input::Eof => return Ok(()),
+        }
+    }
+}
@@ -0,0 +1,180 @@
+//! A lossless concrete syntax tree. Unlike the AST it keeps every character
+//! of the source, comments and whitespace included, so printing the tree
+//! gives back the source exactly. It only records the layout of a program,
+//! the blocks between `:` and `end` and the bracketed groups, which is what
+//! tools that rewrite source such as the formatter need.
+
+use crate::lexer::Lexer;
+use crate::token::TokenType;
+use std::fmt;
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct SyntaxToken {
+    pub token_type: TokenType,
+    /// The source text of the token.
+    pub text: String,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum NodeKind {
+    Root,
+    /// The statements after the `:` that opens a block, up to and including
+    /// its `end`.
+    Block,
+    /// A pair of brackets, braces or parentheses and everything between
+    /// them.
+    Group,
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub enum Element {
+    Token(SyntaxToken),
+    Node(Node),
+}
+
+#[derive(PartialEq, Clone, Debug)]
+pub struct Node {
+    pub kind: NodeKind,
+    pub children: Vec<Element>,
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for child in &self.children {
+            match child {
+                Element::Token(token) => write!(f, "{}", token.text)?,
+                Element::Node(node) => write!(f, "{}", node)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What a keyword that starts a block is waiting for before its `:`.
+#[derive(PartialEq, Clone, Copy)]
+enum Opener {
+    Keyword,
+    /// `fn(...)`, which is followed either by `:` and a block or by `->` and
+    /// a single expression.
+    AnonymousFunction,
+}
+
+struct Frame {
+    node: Node,
+    /// Keywords in this node whose `:` has not been seen yet.
+    openers: Vec<Opener>,
+    /// The `:`s of `elif` and `else`, which continue a block rather than
+    /// starting one.
+    continuations: usize,
+}
+
+impl Frame {
+    fn new(kind: NodeKind) -> Self {
+        Frame {
+            node: Node {
+                kind,
+                children: Vec::new(),
+            },
+            openers: Vec::new(),
+            continuations: 0,
+        }
+    }
+}
+
+/// Builds the tree for `source`. Source that does not parse still gives a
+/// tree that prints back to it, with unclosed nodes closed at the end.
+pub fn parse(source: &str) -> Node {
+    let mut lexer = Lexer::with_trivia(source);
+    let mut frames = vec![Frame::new(NodeKind::Root)];
+    let mut previous = TokenType::EOF;
+
+    loop {
+        let start = lexer.offset();
+        let token_type = lexer.next().token_type;
+        if token_type == TokenType::EOF {
+            break;
+        }
+
+        let token = Element::Token(SyntaxToken {
+            token_type: token_type.clone(),
+            text: source[start..lexer.offset()].to_string(),
+        });
+        let frame = frames.last_mut().unwrap();
+
+        match &token_type {
+            TokenType::LParen | TokenType::LBracket | TokenType::LBrace | TokenType::LArray => {
+                if token_type == TokenType::LParen && previous == TokenType::Function {
+                    if let Some(opener) = frame.openers.last_mut() {
+                        *opener = Opener::AnonymousFunction;
+                    }
+                }
+
+                let mut group = Frame::new(NodeKind::Group);
+                group.node.children.push(token);
+                frames.push(group);
+            }
+            TokenType::RParen | TokenType::RBracket | TokenType::RBrace | TokenType::RArray
+                if frame.node.kind == NodeKind::Group =>
+            {
+                frame.node.children.push(token);
+                close(&mut frames);
+            }
+            TokenType::End if frame.node.kind == NodeKind::Block => {
+                frame.node.children.push(token);
+                close(&mut frames);
+            }
+            TokenType::If
+            | TokenType::While
+            | TokenType::For
+            | TokenType::Match
+            | TokenType::Function => {
+                frame.openers.push(Opener::Keyword);
+                frame.node.children.push(token);
+            }
+            TokenType::Elif | TokenType::Else => {
+                frame.continuations += 1;
+                frame.node.children.push(token);
+            }
+            TokenType::Output
+                if previous == TokenType::RParen
+                    && frame.openers.last() == Some(&Opener::AnonymousFunction) =>
+            {
+                frame.openers.pop();
+                frame.node.children.push(token);
+            }
+            TokenType::Colon if frame.continuations > 0 => {
+                frame.continuations -= 1;
+                frame.node.children.push(token);
+            }
+            TokenType::Colon if frame.openers.pop().is_some() => {
+                frame.node.children.push(token);
+                frames.push(Frame::new(NodeKind::Block));
+            }
+            _ => frame.node.children.push(token),
+        }
+
+        if !matches!(
+            token_type,
+            TokenType::Whitespace | TokenType::Comment | TokenType::NewLine
+        ) {
+            previous = token_type;
+        }
+    }
+
+    while frames.len() > 1 {
+        close(&mut frames);
+    }
+    frames.pop().unwrap().node
+}
+
+/// Finishes the innermost node, adding it to its parent.
+fn close(frames: &mut Vec<Frame>) {
+    let node = frames.pop().unwrap().node;
+    frames
+        .last_mut()
+        .unwrap()
+        .node
+        .children
+        .push(Element::Node(node));
+}
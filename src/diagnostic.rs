@@ -0,0 +1,81 @@
+use crate::source::FileId;
+use crate::token::Token;
+use std::fmt;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Severity::Error => write!(f, "error"),
+            Severity::Warning => write!(f, "warning"),
+        }
+    }
+}
+
+/// A problem found by a static pass, positioned at the token it concerns.
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub message: String,
+    pub row: u32,
+    pub col: u32,
+    pub file: FileId,
+}
+
+impl Diagnostic {
+    pub fn warning(message: String, token: &Token) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            message,
+            row: token.row,
+            col: token.col,
+            file: token.file,
+        }
+    }
+
+    /// A diagnostic for an error message ending in the `row.col` it
+    /// happened at in `file`, as the parser and interpreter report them.
+    /// Messages without a position are placed at the start of the file.
+    pub fn from_error(error: &str, file: FileId) -> Self {
+        let (message, row, col) = split_position(error).unwrap_or((error, 1, 1));
+        Diagnostic {
+            severity: Severity::Error,
+            message: message.to_string(),
+            row,
+            col,
+            file,
+        }
+    }
+
+    pub fn error(message: String, token: &Token) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            message,
+            row: token.row,
+            col: token.col,
+            file: token.file,
+        }
+    }
+}
+
+/// The message of an error and the `row.col` it ends in, if it has one.
+pub fn split_position(error: &str) -> Option<(&str, u32, u32)> {
+    let (message, position) = error.rsplit_once(' ')?;
+    let (row, col) = position.split_once('.')?;
+    Some((message, row.parse().ok()?, col.parse().ok()?))
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {} {}.{}",
+            self.severity, self.message, self.row, self.col
+        )
+    }
+}
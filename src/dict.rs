@@ -0,0 +1,118 @@
+//! The runtime representation of dictionaries: a hash map that remembers the
+//! order its keys were first inserted in. Only ints, strings, characters and
+//! bools can be keys.
+
+use crate::token::Token;
+use crate::value::Value;
+use std::collections::HashMap;
+
+#[derive(PartialEq, Eq, Hash, Clone, Debug)]
+pub enum Key {
+    Int(isize),
+    Str(String),
+    Char(char),
+    Bool(bool),
+}
+
+impl Key {
+    pub fn from_value(value: &Value) -> Option<Self> {
+        match value {
+            Value::Int(i) => Some(Key::Int(*i)),
+            Value::Str(s) => Some(Key::Str(s.clone())),
+            Value::Char(c) => Some(Key::Char(*c)),
+            Value::Bool(b) => Some(Key::Bool(*b)),
+            _ => None,
+        }
+    }
+
+    /// Like `from_value`, with an error positioned at `token` for values that
+    /// cannot be hashed.
+    pub fn of(value: &Value, token: &Token) -> Result<Self, String> {
+        Key::from_value(value).ok_or_else(|| {
+            format!(
+                "Cannot use {} as a dict key {}.{}",
+                value.type_name(),
+                token.row,
+                token.col
+            )
+        })
+    }
+
+    pub fn to_value(&self) -> Value {
+        match self {
+            Key::Int(i) => Value::Int(*i),
+            Key::Str(s) => Value::Str(s.clone()),
+            Key::Char(c) => Value::Char(*c),
+            Key::Bool(b) => Value::Bool(*b),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Dict {
+    /// The position of each key in `entries`.
+    indices: HashMap<Key, usize>,
+    entries: Vec<(Key, Value)>,
+}
+
+impl Dict {
+    pub fn new() -> Self {
+        Dict::default()
+    }
+
+    pub fn get(&self, key: &Key) -> Option<&Value> {
+        self.indices.get(key).map(|i| &self.entries[*i].1)
+    }
+
+    pub fn contains_key(&self, key: &Key) -> bool {
+        self.indices.contains_key(key)
+    }
+
+    /// Replacing the value of an existing key keeps its position.
+    pub fn insert(&mut self, key: Key, value: Value) {
+        match self.indices.get(&key) {
+            Some(i) => self.entries[*i].1 = value,
+            None => {
+                self.indices.insert(key.clone(), self.entries.len());
+                self.entries.push((key, value));
+            }
+        }
+    }
+
+    /// Removes a key, shifting the entries after it down so the remaining
+    /// keys keep their order.
+    pub fn remove(&mut self, key: &Key) -> Option<Value> {
+        let i = self.indices.remove(key)?;
+        let (_, value) = self.entries.remove(i);
+
+        for (key, _) in &self.entries[i..] {
+            *self.indices.get_mut(key).unwrap() -= 1;
+        }
+
+        Some(value)
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// The entries in insertion order.
+    pub fn entries(&self) -> &[(Key, Value)] {
+        &self.entries
+    }
+}
+
+impl PartialEq for Dict {
+    /// Dictionaries are equal when they have the same entries, in any order.
+    fn eq(&self, other: &Self) -> bool {
+        self.len() == other.len()
+            && self
+                .entries
+                .iter()
+                .all(|(key, value)| other.get(key) == Some(value))
+    }
+}
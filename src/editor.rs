@@ -0,0 +1,680 @@
+//! Line editing for the REPL: moving and deleting with the arrow and control
+//! keys, history that persists between sessions, and tab completion.
+//!
+//! The terminal is switched out of canonical mode with `stty` for the whole
+//! session, so keys arrive one at a time instead of after enter is pressed,
+//! and restored when the editor is dropped, including while unwinding from a
+//! panic. When stdin is not a terminal lines are read as they are.
+
+use crate::signal;
+use crate::stdio::{Io, StdIo};
+use std::fs::{self, OpenOptions};
+use std::io::{self, IsTerminal, Read, Write};
+use std::mem;
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+use std::sync::atomic::Ordering;
+
+/// The most lines of history kept, in memory and in the history file.
+const MAX_HISTORY: usize = 1000;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Key {
+    Char(char),
+    Enter,
+    Tab,
+    Backspace,
+    Delete,
+    Left,
+    Right,
+    Up,
+    Down,
+    Home,
+    End,
+    /// Ctrl-K
+    KillToEnd,
+    /// Ctrl-U
+    KillToStart,
+    /// Ctrl-C
+    Interrupt,
+    /// Ctrl-D
+    EndOfInput,
+    Unknown,
+}
+
+/// Reads one key press, returning `None` once `input` is closed.
+pub fn read_key(input: &mut impl Read) -> io::Result<Option<Key>> {
+    let byte = match read_byte(input)? {
+        Some(byte) => byte,
+        None => return Ok(None),
+    };
+
+    let key = match byte {
+        b'\r' | b'\n' => Key::Enter,
+        b'\t' => Key::Tab,
+        0x7f | 0x08 => Key::Backspace,
+        0x01 => Key::Home,
+        0x02 => Key::Left,
+        0x03 => Key::Interrupt,
+        0x04 => Key::EndOfInput,
+        0x05 => Key::End,
+        0x06 => Key::Right,
+        0x0b => Key::KillToEnd,
+        0x0e => Key::Down,
+        0x10 => Key::Up,
+        0x15 => Key::KillToStart,
+        0x1b => read_escape(input)?,
+        byte if byte < 0x20 => Key::Unknown,
+        byte => read_char(byte, input)?,
+    };
+
+    Ok(Some(key))
+}
+
+fn read_byte(input: &mut impl Read) -> io::Result<Option<u8>> {
+    let mut byte = [0];
+
+    loop {
+        match input.read(&mut byte) {
+            Ok(0) => return Ok(None),
+            Ok(_) => return Ok(Some(byte[0])),
+            // Ctrl-C is the only signal that interrupts reading on purpose.
+            Err(e)
+                if e.kind() == io::ErrorKind::Interrupted
+                    && !signal::INTERRUPTED.load(Ordering::Relaxed) =>
+            {
+                continue
+            }
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Decodes the escape sequences terminals send for arrow, home, end and
+/// delete keys, such as `ESC [ A` for up or `ESC [ 3 ~` for delete. Modifiers
+/// like the `1;5` of ctrl-right are ignored.
+fn read_escape(input: &mut impl Read) -> io::Result<Key> {
+    match read_byte(input)? {
+        Some(b'[') | Some(b'O') => {}
+        _ => return Ok(Key::Unknown),
+    }
+
+    let mut parameters = String::new();
+    loop {
+        let byte = match read_byte(input)? {
+            Some(byte) => byte,
+            None => return Ok(Key::Unknown),
+        };
+
+        let key = match byte {
+            b'0'..=b'9' | b';' => {
+                parameters.push(byte as char);
+                continue;
+            }
+            b'A' => Key::Up,
+            b'B' => Key::Down,
+            b'C' => Key::Right,
+            b'D' => Key::Left,
+            b'H' => Key::Home,
+            b'F' => Key::End,
+            b'~' => match parameters.split(';').next() {
+                Some("1") | Some("7") => Key::Home,
+                Some("4") | Some("8") => Key::End,
+                Some("3") => Key::Delete,
+                _ => Key::Unknown,
+            },
+            _ => Key::Unknown,
+        };
+
+        return Ok(key);
+    }
+}
+
+fn read_char(first: u8, input: &mut impl Read) -> io::Result<Key> {
+    let len = match first {
+        0xc0..=0xdf => 2,
+        0xe0..=0xef => 3,
+        0xf0..=0xf7 => 4,
+        _ => 1,
+    };
+
+    let mut bytes = vec![first];
+    for _ in 1..len {
+        match read_byte(input)? {
+            Some(byte) => bytes.push(byte),
+            None => return Ok(Key::Unknown),
+        }
+    }
+
+    Ok(String::from_utf8(bytes)
+        .ok()
+        .and_then(|s| s.chars().next())
+        .map_or(Key::Unknown, Key::Char))
+}
+
+/// The line being edited and the cursor's position in it, in characters.
+#[derive(Default, Debug)]
+pub struct Line {
+    chars: Vec<char>,
+    cursor: usize,
+}
+
+impl Line {
+    pub fn new() -> Self {
+        Line::default()
+    }
+
+    pub fn text(&self) -> String {
+        self.chars.iter().collect()
+    }
+
+    pub fn before_cursor(&self) -> String {
+        self.chars[..self.cursor].iter().collect()
+    }
+
+    pub fn cursor(&self) -> usize {
+        self.cursor
+    }
+
+    pub fn len(&self) -> usize {
+        self.chars.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.chars.is_empty()
+    }
+
+    /// Replaces the whole line, leaving the cursor at its end.
+    pub fn set(&mut self, text: &str) {
+        self.chars = text.chars().collect();
+        self.cursor = self.chars.len();
+    }
+
+    pub fn insert(&mut self, c: char) {
+        self.chars.insert(self.cursor, c);
+        self.cursor += 1;
+    }
+
+    /// Replaces the characters from `start` up to the cursor with `text`.
+    pub fn replace_before_cursor(&mut self, start: usize, text: &str) {
+        self.chars.splice(start..self.cursor, text.chars());
+        self.cursor = start + text.chars().count();
+    }
+
+    pub fn backspace(&mut self) {
+        if self.cursor > 0 {
+            self.cursor -= 1;
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn delete(&mut self) {
+        if self.cursor < self.chars.len() {
+            self.chars.remove(self.cursor);
+        }
+    }
+
+    pub fn left(&mut self) {
+        self.cursor = self.cursor.saturating_sub(1);
+    }
+
+    pub fn right(&mut self) {
+        self.cursor = (self.cursor + 1).min(self.chars.len());
+    }
+
+    pub fn home(&mut self) {
+        self.cursor = 0;
+    }
+
+    pub fn end(&mut self) {
+        self.cursor = self.chars.len();
+    }
+
+    pub fn kill_to_end(&mut self) {
+        self.chars.truncate(self.cursor);
+    }
+
+    pub fn kill_to_start(&mut self) {
+        self.chars.drain(..self.cursor);
+        self.cursor = 0;
+    }
+}
+
+/// Previously entered lines, oldest first, saved to a file as they are added
+/// when the history has a path.
+#[derive(Default, Debug)]
+pub struct History {
+    entries: Vec<String>,
+    path: Option<PathBuf>,
+}
+
+impl History {
+    /// Reads the history saved at `path`. A missing or unreadable file starts
+    /// an empty history. A file that has grown past the limit is shortened.
+    pub fn load(path: Option<PathBuf>) -> Self {
+        let mut history = History {
+            entries: Vec::new(),
+            path,
+        };
+
+        let contents = match &history.path {
+            Some(path) => fs::read_to_string(path).unwrap_or_default(),
+            None => String::new(),
+        };
+        history.entries = contents.lines().map(String::from).collect();
+
+        if history.entries.len() > MAX_HISTORY {
+            history.entries.drain(..history.entries.len() - MAX_HISTORY);
+
+            if let Some(path) = &history.path {
+                let mut contents = history.entries.join("\n");
+                contents.push('\n');
+                let _ = fs::write(path, contents);
+            }
+        }
+
+        history
+    }
+
+    pub fn entries(&self) -> &[String] {
+        &self.entries
+    }
+
+    /// Adds a line unless it is blank or repeats the previous line. Failing
+    /// to save it is not an error, the line is still kept for this session.
+    pub fn add(&mut self, line: &str) {
+        if line.trim().is_empty() || self.entries.last().map(String::as_str) == Some(line) {
+            return;
+        }
+
+        self.entries.push(line.to_string());
+        if self.entries.len() > MAX_HISTORY {
+            self.entries.remove(0);
+        }
+
+        if let Some(path) = &self.path {
+            let _ = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{}", line));
+        }
+    }
+}
+
+pub enum Input {
+    Line(String),
+    /// Ctrl-C was pressed.
+    Interrupted,
+    /// Ctrl-D was pressed on an empty line or stdin was closed.
+    Eof,
+}
+
+pub struct Editor {
+    history: History,
+    /// Holds the terminal in raw mode for the whole session, restoring it
+    /// when the editor is dropped. `None` when stdin is not a terminal.
+    raw: Option<RawMode>,
+    display: Display,
+    /// What a running program has written since its last new line, which is
+    /// on screen in front of the input it reads.
+    pending: String,
+}
+
+impl Editor {
+    /// Puts the terminal in raw mode when stdin is one, and has Ctrl-C
+    /// interrupt evaluation instead of ending the session.
+    pub fn new(history: History) -> Self {
+        let raw = match io::stdin().is_terminal() {
+            true => RawMode::enable().ok(),
+            false => None,
+        };
+        if raw.is_some() {
+            signal::catch();
+        }
+
+        Editor {
+            history,
+            raw,
+            display: Display::new(terminal_width()),
+            pending: String::new(),
+        }
+    }
+
+    /// Reads a line after printing `prompt`. `complete` is given the text
+    /// before the cursor when tab is pressed and returns the byte offset of
+    /// the word to complete and what it could be completed to.
+    pub fn read_line(
+        &mut self,
+        prompt: &str,
+        complete: impl Fn(&str) -> (usize, Vec<String>),
+    ) -> io::Result<Input> {
+        let mut stdout = io::stdout();
+        write!(stdout, "{}", prompt)?;
+        stdout.flush()?;
+        self.pending.clear();
+
+        if self.raw.is_none() {
+            return read_plain_line();
+        }
+
+        let input = self.edit(prompt, complete, &mut stdout);
+        if let Ok(Input::Line(line)) = &input {
+            self.history.add(line);
+        }
+        input
+    }
+
+    fn edit(
+        &mut self,
+        prompt: &str,
+        complete: impl Fn(&str) -> (usize, Vec<String>),
+        out: &mut impl Write,
+    ) -> io::Result<Input> {
+        // A Ctrl-C pressed between lines is not meant for this one.
+        signal::take(&signal::INTERRUPTED);
+        if signal::take(&signal::RESIZED) {
+            self.display.width = terminal_width();
+        }
+
+        let stdin = io::stdin();
+        let mut stdin = stdin.lock();
+        let mut line = Line::new();
+        // Where the line is in the history while browsing it with the arrow
+        // keys, and the unfinished line to return to past the newest entry.
+        let mut index = self.history.entries().len();
+        let mut draft = String::new();
+
+        loop {
+            let key = match read_key(&mut stdin) {
+                Ok(Some(key)) => key,
+                Ok(None) => return Ok(Input::Eof),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {
+                    signal::take(&signal::INTERRUPTED);
+                    Key::Interrupt
+                }
+                Err(e) => return Err(e),
+            };
+
+            match key {
+                Key::Char(c) => line.insert(c),
+                Key::Enter => {
+                    self.display.finish(prompt, &line, out)?;
+                    return Ok(Input::Line(line.text()));
+                }
+                Key::Interrupt => {
+                    line.end();
+                    self.display.refresh(prompt, &line, out)?;
+                    writeln!(out, "^C")?;
+                    self.display.row = 0;
+                    return Ok(Input::Interrupted);
+                }
+                Key::EndOfInput if line.is_empty() => {
+                    self.display.finish(prompt, &line, out)?;
+                    return Ok(Input::Eof);
+                }
+                Key::EndOfInput | Key::Delete => line.delete(),
+                Key::Backspace => line.backspace(),
+                Key::Left => line.left(),
+                Key::Right => line.right(),
+                Key::Home => line.home(),
+                Key::End => line.end(),
+                Key::KillToEnd => line.kill_to_end(),
+                Key::KillToStart => line.kill_to_start(),
+                Key::Up if index > 0 => {
+                    if index == self.history.entries().len() {
+                        draft = line.text();
+                    }
+                    index -= 1;
+                    line.set(&self.history.entries()[index]);
+                }
+                Key::Down if index < self.history.entries().len() => {
+                    index += 1;
+                    match self.history.entries().get(index) {
+                        Some(entry) => line.set(entry),
+                        None => line.set(&draft),
+                    }
+                }
+                Key::Tab => self.complete(prompt, &mut line, &complete, out)?,
+                Key::Up | Key::Down | Key::Unknown => {}
+            }
+
+            self.display.refresh(prompt, &line, out)?;
+        }
+    }
+
+    /// Completes a word that has a single candidate, or extends it by what
+    /// all its candidates share. When that adds nothing the candidates are
+    /// listed below the line.
+    fn complete(
+        &mut self,
+        prompt: &str,
+        line: &mut Line,
+        complete: impl Fn(&str) -> (usize, Vec<String>),
+        out: &mut impl Write,
+    ) -> io::Result<()> {
+        let before = line.before_cursor();
+        let (start, candidates) = complete(&before);
+        let word = &before[start..];
+        let start = before[..start].chars().count();
+
+        match candidates.as_slice() {
+            [] => write!(out, "\x07")?,
+            [candidate] => line.replace_before_cursor(start, candidate),
+            _ => {
+                let prefix = common_prefix(&candidates);
+
+                if prefix.len() > word.len() {
+                    line.replace_before_cursor(start, &prefix);
+                } else {
+                    self.display.finish(prompt, line, out)?;
+                    writeln!(out, "{}", candidates.join("  "))?;
+                    write!(out, "{}", prompt)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// A program run in the REPL reads its input through the editor, since the
+/// terminal stays in raw mode while it runs. What it has printed on the
+/// current row is kept in front of the line as it is edited.
+impl Io for Editor {
+    fn write_out(&mut self, text: &str) -> io::Result<()> {
+        match text.rfind('\n') {
+            Some(i) => self.pending = text[i + 1..].to_string(),
+            None => self.pending.push_str(text),
+        }
+        StdIo.write_out(text)
+    }
+
+    fn write_err(&mut self, text: &str) -> io::Result<()> {
+        StdIo.write_err(text)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        if self.raw.is_none() {
+            return StdIo.read_line();
+        }
+
+        let prompt = mem::take(&mut self.pending);
+        match self.edit(&prompt, |_| (0, Vec::new()), &mut io::stdout())? {
+            Input::Line(line) => Ok(Some(line)),
+            Input::Eof => Ok(None),
+            Input::Interrupted => Err(io::Error::new(io::ErrorKind::Interrupted, "interrupted")),
+        }
+    }
+
+    /// Lines up to Ctrl-D, which raw mode leaves to the editor.
+    fn read_all(&mut self) -> io::Result<String> {
+        if self.raw.is_none() {
+            return StdIo.read_all();
+        }
+
+        let mut input = String::new();
+        while let Some(line) = Io::read_line(self)? {
+            input.push_str(&line);
+            input.push('\n');
+        }
+        Ok(input)
+    }
+}
+
+/// Reads a line without editing, for when stdin is not a terminal.
+fn read_plain_line() -> io::Result<Input> {
+    let mut line = String::new();
+
+    match io::stdin().read_line(&mut line)? {
+        0 => {
+            println!();
+            Ok(Input::Eof)
+        }
+        _ => Ok(Input::Line(line)),
+    }
+}
+
+/// Draws the line being edited over as many rows as it wraps onto, keeping
+/// track of the row the cursor is left on so the next redraw starts from
+/// the prompt's row. Every character is taken to be one column wide.
+#[derive(Debug)]
+pub struct Display {
+    width: usize,
+    row: usize,
+}
+
+impl Display {
+    pub fn new(width: usize) -> Self {
+        Display {
+            width: width.max(1),
+            row: 0,
+        }
+    }
+
+    /// Redraws the prompt and line and puts the cursor back where it belongs.
+    pub fn refresh(&mut self, prompt: &str, line: &Line, out: &mut impl Write) -> io::Result<()> {
+        let start = prompt.chars().count();
+        let end = start + line.len();
+        let cursor = start + line.cursor();
+
+        if self.row > 0 {
+            write!(out, "\x1b[{}A", self.row)?;
+        }
+        write!(out, "\r\x1b[J{}{}", prompt, line.text())?;
+
+        // Filling the last row leaves the terminal waiting to wrap, with the
+        // cursor still on that row, until the next character is written.
+        let mut row = end / self.width;
+        if end > 0 && end.is_multiple_of(self.width) {
+            match cursor == end {
+                true => writeln!(out)?,
+                false => row -= 1,
+            }
+        }
+
+        let target = cursor / self.width;
+        if row > target {
+            write!(out, "\x1b[{}A", row - target)?;
+        }
+        write!(out, "\r")?;
+        let column = cursor % self.width;
+        if column > 0 {
+            write!(out, "\x1b[{}C", column)?;
+        }
+        self.row = target;
+
+        out.flush()
+    }
+
+    /// Moves to a new row below the line, for what is written after it.
+    pub fn finish(&mut self, prompt: &str, line: &Line, out: &mut impl Write) -> io::Result<()> {
+        let end = prompt.chars().count() + line.len();
+        let last = match end > 0 && end.is_multiple_of(self.width) {
+            true => end / self.width - 1,
+            false => end / self.width,
+        };
+
+        if last > self.row {
+            write!(out, "\x1b[{}B", last - self.row)?;
+        }
+        // Past the last row when the cursor was moved to the start of an
+        // empty one after filling it.
+        match self.row > last {
+            true => write!(out, "\r")?,
+            false => writeln!(out)?,
+        }
+        self.row = 0;
+
+        out.flush()
+    }
+}
+
+/// The terminal's width in columns, or 80 when it cannot be found.
+fn terminal_width() -> usize {
+    stty(&["size"])
+        .ok()
+        .and_then(|size| size.split_whitespace().nth(1)?.parse().ok())
+        .filter(|width| *width > 0)
+        .unwrap_or(80)
+}
+
+pub fn common_prefix(words: &[String]) -> String {
+    let first = match words.first() {
+        Some(first) => first,
+        None => return String::new(),
+    };
+
+    let mut len = first.len();
+    for word in &words[1..] {
+        len = first
+            .char_indices()
+            .zip(word.chars())
+            .take_while(|((i, a), b)| *i < len && a == b)
+            .map(|((i, a), _)| i + a.len_utf8())
+            .last()
+            .unwrap_or(0);
+    }
+
+    first[..len].to_string()
+}
+
+/// Keeps the terminal sending keys as they are pressed, without echoing them,
+/// until dropped. Ctrl-C still sends SIGINT, so it can interrupt a program
+/// the REPL is running as well as the line being edited.
+struct RawMode {
+    saved: String,
+}
+
+impl RawMode {
+    fn enable() -> io::Result<Self> {
+        let saved = stty(&["-g"])?;
+        stty(&[
+            "-icanon", "-echo", "-iexten", "-ixon", "min", "1", "time", "0",
+        ])?;
+
+        Ok(RawMode {
+            saved: saved.trim().to_string(),
+        })
+    }
+}
+
+impl Drop for RawMode {
+    fn drop(&mut self) {
+        let _ = stty(&[&self.saved]);
+    }
+}
+
+fn stty(args: &[&str]) -> io::Result<String> {
+    let output = Command::new("stty")
+        .args(args)
+        .stdin(Stdio::inherit())
+        .output()?;
+
+    if !output.status.success() {
+        let error = String::from_utf8_lossy(&output.stderr);
+        return Err(io::Error::other(error.trim()));
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
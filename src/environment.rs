@@ -0,0 +1,76 @@
+use crate::value::Value;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+pub type Env = Rc<RefCell<Environment>>;
+
+#[derive(Debug, Default)]
+pub struct Environment {
+    store: HashMap<String, Value>,
+    outer: Option<Env>,
+}
+
+impl Environment {
+    pub fn new() -> Env {
+        Rc::new(RefCell::new(Environment::default()))
+    }
+
+    pub fn enclosed(outer: &Env) -> Env {
+        Rc::new(RefCell::new(Environment {
+            store: HashMap::new(),
+            outer: Some(Rc::clone(outer)),
+        }))
+    }
+
+    /// The outermost scope around `env`, the top level of its file.
+    pub fn global(env: &Env) -> Env {
+        match &env.borrow().outer {
+            Some(outer) => Environment::global(outer),
+            None => Rc::clone(env),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<Value> {
+        match self.store.get(name) {
+            Some(value) => Some(value.clone()),
+            None => self
+                .outer
+                .as_ref()
+                .and_then(|outer| outer.borrow().get(name)),
+        }
+    }
+
+    pub fn get_local(&self, name: &str) -> Option<Value> {
+        self.store.get(name).cloned()
+    }
+
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.store.insert(name.to_string(), value);
+    }
+
+    /// Rebinds an existing variable in the nearest scope that declares it.
+    /// Returns false when no scope declares `name`.
+    pub fn assign(&mut self, name: &str, value: Value) -> bool {
+        if let Some(slot) = self.store.get_mut(name) {
+            *slot = value;
+            return true;
+        }
+
+        match &self.outer {
+            Some(outer) => outer.borrow_mut().assign(name, value),
+            None => false,
+        }
+    }
+
+    /// Every name visible from this scope, including those of outer scopes.
+    pub fn names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self.store.keys().cloned().collect();
+
+        if let Some(outer) = &self.outer {
+            names.extend(outer.borrow().names());
+        }
+
+        names
+    }
+}
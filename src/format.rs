@@ -0,0 +1,203 @@
+//! The source formatter behind `slice fmt`. It keeps the line breaks and
+//! comments of the source and normalises the rest: indentation inside blocks
+//! and multi-line brackets, the spacing between tokens, trailing whitespace
+//! and runs of blank lines.
+
+use crate::cst::{self, Element, Node, NodeKind, SyntaxToken};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::token::TokenType;
+
+const INDENT: &str = "  ";
+
+/// Formats a program, failing if it does not parse.
+pub fn format(source: &str) -> Result<String, String> {
+    Parser::new(Lexer::new(source)).parse_program()?;
+
+    let mut formatter = Formatter::default();
+    formatter.node(&cst::parse(source));
+    formatter.finish_line();
+
+    Ok(formatter.output)
+}
+
+/// The indentation inside an open block or group.
+struct Level {
+    /// The indentation of the line the node starts on, which its closing
+    /// line returns to.
+    outer: usize,
+    inner: usize,
+    /// The line the node starts on.
+    line: usize,
+}
+
+#[derive(Default)]
+struct Formatter {
+    output: String,
+    levels: Vec<Level>,
+    line: usize,
+    /// The indentation of the current line, decided by its first token.
+    indent: Option<usize>,
+    text: String,
+    /// Whether a blank line came after the last line with content.
+    blank: bool,
+    previous: Option<TokenType>,
+    /// Whether `previous` is a `-` that negates rather than subtracts.
+    negation: bool,
+}
+
+impl Formatter {
+    fn node(&mut self, node: &Node) {
+        if node.kind != NodeKind::Root {
+            self.enter();
+        }
+
+        for child in &node.children {
+            match child {
+                Element::Token(token) => self.token(token),
+                Element::Node(node) => self.node(node),
+            }
+        }
+
+        if node.kind != NodeKind::Root {
+            self.levels.pop();
+        }
+    }
+
+    /// Nodes opened on the same line share one level of indentation, so
+    /// `f([` indents its contents once and closes with `])`.
+    fn enter(&mut self) {
+        let indent = self.start_line(false);
+
+        let level = match self.levels.last() {
+            Some(top) if top.line == self.line => Level {
+                outer: top.outer,
+                inner: top.inner,
+                line: self.line,
+            },
+            _ => Level {
+                outer: indent,
+                inner: indent + 1,
+                line: self.line,
+            },
+        };
+
+        self.levels.push(level);
+    }
+
+    /// Decides the indentation of the current line if it has none yet. A
+    /// line starting by closing a node or with `elif` or `else` lines up
+    /// with the line that opened it.
+    fn start_line(&mut self, closing: bool) -> usize {
+        if let Some(indent) = self.indent {
+            return indent;
+        }
+
+        let indent = match self.levels.last() {
+            Some(level) if closing => level.outer,
+            Some(level) => level.inner,
+            None => 0,
+        };
+
+        self.indent = Some(indent);
+        indent
+    }
+
+    fn token(&mut self, token: &SyntaxToken) {
+        match &token.token_type {
+            TokenType::Whitespace => {}
+            TokenType::NewLine => self.finish_line(),
+            TokenType::Comment => {
+                self.start_line(false);
+                if !self.text.is_empty() {
+                    self.text.push(' ');
+                }
+                self.text.push_str(token.text.trim_end());
+            }
+            token_type => {
+                let closing = matches!(
+                    token_type,
+                    TokenType::RParen
+                        | TokenType::RBracket
+                        | TokenType::RBrace
+                        | TokenType::RArray
+                        | TokenType::End
+                        | TokenType::Elif
+                        | TokenType::Else
+                );
+                self.start_line(closing);
+
+                if let Some(previous) = &self.previous {
+                    if !self.text.is_empty() && space_between(previous, self.negation, token_type) {
+                        self.text.push(' ');
+                    }
+                }
+                self.text.push_str(&token.text);
+
+                self.negation = *token_type == TokenType::Minus
+                    && !self.previous.as_ref().is_some_and(ends_operand);
+                self.previous = Some(token_type.clone());
+            }
+        }
+    }
+
+    /// Writes out the current line. Blank lines at the start and runs of
+    /// them are dropped down to one.
+    fn finish_line(&mut self) {
+        match self.indent.take() {
+            Some(indent) if !self.text.is_empty() => {
+                if self.blank && !self.output.is_empty() {
+                    self.output.push('\n');
+                }
+                self.blank = false;
+
+                self.output.push_str(&INDENT.repeat(indent));
+                self.output.push_str(&self.text);
+                self.output.push('\n');
+                self.text.clear();
+            }
+            _ => self.blank = true,
+        }
+
+        self.line += 1;
+    }
+}
+
+/// Whether `token_type` can end an operand, after which `-` subtracts and
+/// `(` or `[` call or index it.
+fn ends_operand(token_type: &TokenType) -> bool {
+    matches!(
+        token_type,
+        TokenType::Identity(_)
+            | TokenType::Int(_)
+            | TokenType::Str(_)
+            | TokenType::Character(_)
+            | TokenType::True
+            | TokenType::False
+            | TokenType::RParen
+            | TokenType::RBracket
+            | TokenType::RBrace
+            | TokenType::RArray
+    )
+}
+
+/// Whether a space separates two tokens on the same line.
+fn space_between(previous: &TokenType, negation: bool, next: &TokenType) -> bool {
+    use TokenType::*;
+
+    match (previous, next) {
+        (_, Comma) | (_, SemiColon) | (_, Colon) => false,
+        (Comma, _) | (SemiColon, _) | (Colon, _) => true,
+        (LParen, _) | (LBracket, _) | (LBrace, _) | (LArray, _) => false,
+        (_, RParen) | (_, RBracket) | (_, RBrace) | (_, RArray) => false,
+        (Dot, _) | (_, Dot) => false,
+        (Range, _) | (_, Range) | (RangeInclusive, _) | (_, RangeInclusive) => false,
+        (Not, _) | (Pin, _) => false,
+        // `- 1` stays apart, joined it would lex as the number -1.
+        (Minus, Int(_)) => true,
+        (Minus, _) if negation => false,
+        (Function, LParen) => false,
+        (previous, LParen) | (previous, LBracket) => !ends_operand(previous),
+        _ => true,
+    }
+}
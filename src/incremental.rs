@@ -0,0 +1,283 @@
+//! Keeps the tokens and top-level statements of a program up to date as it
+//! is edited, for the language server. An edit is lexed again from the start
+//! of its line until the new tokens line up with the old ones at the start
+//! of a later line, and only the top-level statements that read a changed
+//! token are parsed again. The statements after them are kept and moved to
+//! their new rows, so the result is always what parsing the new text from
+//! scratch with `parse_program_with_errors` gives.
+
+use crate::ast::{Ast, Statement};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::source::FileId;
+use crate::token::{Token, TokenType};
+use std::ops::Range;
+
+/// How much of a program an edit lexed and parsed again.
+#[derive(PartialEq, Debug)]
+pub struct Damage {
+    pub tokens: usize,
+    pub statements: usize,
+}
+
+/// A top-level statement, or the error that stopped it from parsing.
+struct Parsed {
+    /// The index of its first token.
+    start: usize,
+    statement: Result<Statement, String>,
+}
+
+pub struct Program {
+    text: String,
+    /// Every token of the text, ending with `EOF`.
+    tokens: Vec<Token>,
+    /// The byte offset each token starts at, counting the spaces and
+    /// comments before it.
+    starts: Vec<usize>,
+    statements: Vec<Parsed>,
+    file: FileId,
+}
+
+impl Program {
+    pub fn new(text: String) -> Self {
+        Program::in_file(text, FileId::default())
+    }
+
+    /// A program whose tokens are marked as coming from `file`.
+    pub fn in_file(text: String, file: FileId) -> Self {
+        let mut program = Program {
+            text,
+            tokens: Vec::new(),
+            starts: Vec::new(),
+            statements: Vec::new(),
+            file,
+        };
+
+        let (tokens, starts) = lex(&program.text, 0, 1, file, |_, _| false);
+        program.tokens = tokens;
+        program.starts = starts;
+        program.statements = parse(&program.tokens, 0, |_| None).0;
+
+        program
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn file(&self) -> FileId {
+        self.file
+    }
+
+    pub fn tokens(&self) -> &[Token] {
+        &self.tokens
+    }
+
+    /// The statements that parsed.
+    pub fn ast(&self) -> Ast {
+        self.statements
+            .iter()
+            .filter_map(|parsed| parsed.statement.as_ref().ok().cloned())
+            .collect()
+    }
+
+    /// The errors of the statements that did not parse, in order.
+    pub fn errors(&self) -> Vec<String> {
+        self.statements
+            .iter()
+            .filter_map(|parsed| parsed.statement.as_ref().err().cloned())
+            .collect()
+    }
+
+    /// Replaces the bytes in `range` with `replacement`. Panics if the range
+    /// does not fall on character boundaries.
+    pub fn edit(&mut self, range: Range<usize>, replacement: &str) -> Damage {
+        self.text.replace_range(range.clone(), replacement);
+        let inserted_end = range.start + replacement.len();
+        let shift = inserted_end as isize - range.end as isize;
+
+        // Lexing starts again at the line the edit begins on, and stops at
+        // the first line past the edit that started a line before it too.
+        let restart = (0..self.tokens.len())
+            .rev()
+            .find(|&i| self.starts[i] <= range.start && self.starts_line(i))
+            .unwrap_or(0);
+
+        let mut resume = None;
+        let (relexed, relexed_starts) = lex(
+            &self.text,
+            self.starts[restart],
+            self.line_of(restart),
+            self.file,
+            |newline, offset| {
+                if offset < inserted_end {
+                    return false;
+                }
+
+                let old_offset = (offset as isize - shift) as usize;
+                match self.starts.binary_search(&old_offset) {
+                    Ok(index) if self.starts_line(index) => {
+                        let rows = newline.row as isize + 1 - self.line_of(index) as isize;
+                        resume = Some((index, rows));
+                        true
+                    }
+                    _ => false,
+                }
+            },
+        );
+
+        // The old tokens from `resume` on follow the relexed ones.
+        let (resume, rows) = resume.unwrap_or((self.tokens.len(), 0));
+        let relexed_end = restart + relexed.len();
+        let moved = relexed_end as isize - resume as isize;
+
+        // A statement reads its own tokens and the first token of the next
+        // one, where it stopped, so it is kept if that is before `restart`.
+        let eof = self.tokens.len() - 1;
+        let mut next_starts = self
+            .statements
+            .iter()
+            .skip(1)
+            .map(|parsed| parsed.start)
+            .chain(Some(eof));
+        let kept = self
+            .statements
+            .iter()
+            .take_while(|_| next_starts.next().is_some_and(|next| next < restart))
+            .count();
+        let from = match kept {
+            0 => 0,
+            _ => self.statements[kept].start,
+        };
+
+        let old_tokens = self.tokens.split_off(resume);
+        let old_starts = self.starts.split_off(resume);
+        self.tokens.truncate(restart);
+        self.starts.truncate(restart);
+        self.tokens.extend(relexed);
+        self.starts.extend(relexed_starts);
+        self.tokens.extend(old_tokens.into_iter().map(|mut token| {
+            token.row = (token.row as isize + rows) as u32;
+            token
+        }));
+        self.starts.extend(
+            old_starts
+                .into_iter()
+                .map(|start| (start as isize + shift) as usize),
+        );
+
+        let mut old = self.statements.split_off(kept);
+        let (parsed, reused) = parse(&self.tokens, from, |start| {
+            if start < relexed_end {
+                return None;
+            }
+
+            let start = (start as isize - moved) as usize;
+            old.iter().position(|parsed| parsed.start == start)
+        });
+        let mut statements = parsed.len();
+        self.statements.extend(parsed);
+
+        if let Some(reused) = reused {
+            for mut parsed in old.drain(reused..) {
+                parsed.start = (parsed.start as isize + moved) as usize;
+
+                match &mut parsed.statement {
+                    Ok(statement) if rows != 0 => statement.tokens_mut(&mut |token| {
+                        token.row = (token.row as isize + rows) as u32;
+                    }),
+                    // Errors name the row they happened on, so they are
+                    // found again rather than moved.
+                    Err(_) if rows != 0 => {
+                        parsed.statement =
+                            Parser::from_tokens(&self.tokens[parsed.start..]).parse_recovering();
+                        statements += 1;
+                    }
+                    _ => {}
+                }
+
+                self.statements.push(parsed);
+            }
+        }
+
+        Damage {
+            tokens: relexed_end - restart,
+            statements,
+        }
+    }
+
+    /// Whether the token at `index` is the first on its line.
+    fn starts_line(&self, index: usize) -> bool {
+        index == 0 || self.tokens[index - 1].token_type == TokenType::NewLine
+    }
+
+    /// The row of the line the token at `index` starts, when it starts one.
+    fn line_of(&self, index: usize) -> u32 {
+        match index {
+            0 => 1,
+            _ => self.tokens[index - 1].row + 1,
+        }
+    }
+}
+
+/// Lexes `text` from `offset`, the start of line `row` of `file`, until the
+/// end or until `stop` returns true for a `NewLine` token and the offset
+/// after it. Returns the tokens, including the `EOF` unless stopped, and the
+/// offsets they start at.
+fn lex(
+    text: &str,
+    offset: usize,
+    row: u32,
+    file: FileId,
+    mut stop: impl FnMut(&Token, usize) -> bool,
+) -> (Vec<Token>, Vec<usize>) {
+    let mut lexer = Lexer::at_line(text, offset, row).in_file(file);
+    let mut tokens = Vec::new();
+    let mut starts = Vec::new();
+
+    loop {
+        let start = lexer.offset();
+        let token = lexer.next();
+        let token_type = token.token_type.clone();
+
+        if token_type == TokenType::NewLine && stop(&token, lexer.offset()) {
+            tokens.push(token);
+            starts.push(start);
+            break;
+        }
+
+        tokens.push(token);
+        starts.push(start);
+        if token_type == TokenType::EOF {
+            break;
+        }
+    }
+
+    (tokens, starts)
+}
+
+/// Parses the top-level statements of `tokens` from index `from` until the
+/// end, or until `reuse` gives the index of an old statement to carry on
+/// with for a statement starting at the given token.
+fn parse(
+    tokens: &[Token],
+    from: usize,
+    reuse: impl Fn(usize) -> Option<usize>,
+) -> (Vec<Parsed>, Option<usize>) {
+    let mut parser = Parser::from_tokens(&tokens[from..]);
+    let mut statements = Vec::new();
+
+    while !parser.at_eof() {
+        let start = from + parser.token_index();
+        if let Some(index) = reuse(start) {
+            return (statements, Some(index));
+        }
+
+        statements.push(Parsed {
+            start,
+            statement: parser.parse_recovering(),
+        });
+    }
+
+    (statements, None)
+}
@@ -0,0 +1,1225 @@
+use crate::ast::{
+    AccessExpr, Ast, CallExpr, DictExpr, Expression, ForStatement, FunctionClause, IfExpr,
+    ImportStatement, IndexAssignStatement, IndexExpr, InfixExpr, LetStatement, ListExpr, MatchExpr,
+    Pattern, PrefixExpr, Statement,
+};
+use crate::builtins::{GLOBALS, MODULES};
+use crate::dict::{Dict, Key};
+use crate::environment::{Env, Environment};
+use crate::lexer::Lexer;
+use crate::list::List;
+use crate::parser::Parser;
+use crate::pattern::match_pattern;
+use crate::signal;
+use crate::source::{FileId, SourceMap};
+use crate::stdio::{Io, StdIo};
+use crate::token::{Token, TokenType};
+use crate::value::{index_bounds, slice_bounds, type_name_of, Function, Namespace, Partial, Value};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::atomic::AtomicBool;
+
+/// How many calls deep a program may go before it fails with a stack
+/// overflow error, rather than overflowing the native stack.
+pub const MAX_CALL_DEPTH: usize = 10_000;
+
+/// The native stack a thread needs to evaluate calls `MAX_CALL_DEPTH` deep,
+/// with room to spare for unoptimized builds.
+pub const STACK_SIZE: usize = 1 << 30;
+
+/// How many of the innermost and of the outermost frames of a long trace
+/// are described, leaving out the ones between.
+const TRACE_ENDS: usize = 10;
+
+/// Non-local exits that unwind through expression evaluation.
+#[derive(Debug)]
+pub enum Control {
+    Error(String),
+    Return(Value),
+    Break,
+    Continue,
+}
+
+impl From<String> for Control {
+    fn from(error: String) -> Self {
+        Control::Error(error)
+    }
+}
+
+pub type Eval = Result<Value, Control>;
+
+/// A function being called, or a file being imported, and where.
+#[derive(Clone, Debug)]
+pub struct Frame {
+    pub name: String,
+    pub file: FileId,
+    pub row: u32,
+    pub col: u32,
+}
+
+impl Frame {
+    fn new(name: String, token: &Token) -> Self {
+        Frame {
+            name,
+            file: token.file,
+            row: token.row,
+            col: token.col,
+        }
+    }
+}
+
+/// Where the last error happened: the file that was running and the frames
+/// that led there, outermost first.
+#[derive(Clone, Debug)]
+pub struct Trace {
+    pub file: FileId,
+    pub frames: Vec<Frame>,
+}
+
+/// Evaluates programs by walking their syntax tree, so each call a program
+/// makes nests several native stack frames. Run it on a thread with a stack
+/// of at least `STACK_SIZE`, as `main` does: `MAX_CALL_DEPTH` only turns deep
+/// recursion into an error before a stack that size overflows, and a smaller
+/// one, such as the 8 MiB of a default main thread, aborts the process well
+/// before the limit is reached.
+pub struct Interpreter {
+    env: Env,
+    io: Box<dyn Io>,
+    filesystem: bool,
+    /// The file being run, which imports are relative to.
+    path: Option<PathBuf>,
+    /// Every file imported so far, by canonical path, so each loads once.
+    modules: HashMap<PathBuf, Value>,
+    /// The files being loaded, each imported by the one before it.
+    loading: Vec<PathBuf>,
+    /// Every file run or imported, which the tokens of its code point to.
+    sources: SourceMap,
+    /// The file the code being evaluated comes from.
+    file: FileId,
+    stack: Vec<Frame>,
+    trace: Option<Trace>,
+    /// Set from outside, such as by a Ctrl-C handler, to stop evaluation.
+    interrupt: Option<&'static AtomicBool>,
+}
+
+/// A scope holding the builtin modules and global functions.
+fn global_env() -> Env {
+    let env = Environment::new();
+
+    for module in MODULES {
+        env.borrow_mut().define(module.name, Value::Module(module));
+    }
+    for builtin in GLOBALS.iter().flat_map(|globals| globals.iter()) {
+        env.borrow_mut()
+            .define(builtin.name, Value::Builtin(builtin));
+    }
+
+    env
+}
+
+impl Default for Interpreter {
+    fn default() -> Self {
+        Interpreter::new()
+    }
+}
+
+impl Interpreter {
+    pub fn new() -> Self {
+        Interpreter::with_io(StdIo)
+    }
+
+    /// An interpreter whose scripts read and write through `io` instead of
+    /// the standard streams.
+    pub fn with_io(io: impl Io + 'static) -> Self {
+        let mut interpreter = Interpreter {
+            env: global_env(),
+            io: Box::new(io),
+            filesystem: true,
+            path: None,
+            modules: HashMap::new(),
+            loading: Vec::new(),
+            sources: SourceMap::new(),
+            file: FileId::default(),
+            stack: Vec::new(),
+            trace: None,
+            interrupt: None,
+        };
+        interpreter.set_args(Vec::new());
+        interpreter
+    }
+
+    /// Sets the command line arguments scripts see as `Env.args`.
+    pub fn set_args(&mut self, args: Vec<String>) {
+        let mut env = Dict::new();
+        env.insert(
+            Key::Str(String::from("args")),
+            Value::List(args.into_iter().map(Value::Str).collect()),
+        );
+
+        self.env
+            .borrow_mut()
+            .define("Env", Value::Dict(Rc::new(env)));
+    }
+
+    /// Forgets every variable and function defined so far, keeping the
+    /// script arguments.
+    pub fn reset(&mut self) {
+        let args = self.env.borrow().get("Env");
+        self.env = global_env();
+
+        if let Some(args) = args {
+            self.env.borrow_mut().define("Env", args);
+        }
+    }
+
+    /// The value a global variable is bound to.
+    pub fn lookup(&self, name: &str) -> Option<Value> {
+        self.env.borrow().get(name)
+    }
+
+    /// The names of every global variable.
+    pub fn names(&self) -> Vec<String> {
+        self.env.borrow().names()
+    }
+
+    pub fn io(&mut self) -> &mut dyn Io {
+        self.io.as_mut()
+    }
+
+    /// Whether the `File` and `Dir` modules may touch the filesystem, and
+    /// `import` read other files. Allowed by default; disable it before
+    /// running untrusted scripts.
+    pub fn allow_filesystem(&mut self, allowed: bool) {
+        self.filesystem = allowed;
+    }
+
+    /// Stops evaluation with an error at the next call or loop iteration
+    /// once `flag` is set, clearing it. The REPL passes the flag Ctrl-C sets.
+    pub fn interrupt_on(&mut self, flag: &'static AtomicBool) {
+        self.interrupt = Some(flag);
+    }
+
+    fn check_interrupt(&self, token: &Token) -> Result<(), Control> {
+        match self.interrupt {
+            Some(flag) if signal::take(flag) => Err(Control::Error(format!(
+                "Interrupted {}.{}",
+                token.row, token.col
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    pub fn filesystem_allowed(&self) -> bool {
+        self.filesystem
+    }
+
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    /// Adds the code in `text` to the files errors are placed in, replacing
+    /// the text of the file with the same name if there is one.
+    pub fn set_source(&mut self, name: &str, text: String) -> FileId {
+        self.sources.set(name, text)
+    }
+
+    /// Where the error returned by the last run happened.
+    pub fn trace(&self) -> Option<&Trace> {
+        self.trace.as_ref()
+    }
+
+    /// An error returned by the last run, placed in the file it happened in
+    /// and followed by the calls that led to it, innermost first. Code that
+    /// was not given a file is placed at just its row and column.
+    pub fn describe(&self, error: &str) -> String {
+        let trace = match &self.trace {
+            Some(trace) => trace,
+            None => return error.to_string(),
+        };
+
+        let mut described = self.sources.place(trace.file, error);
+
+        let frames = trace.frames.len();
+        for (depth, frame) in trace.frames.iter().enumerate().rev() {
+            if frames > 2 * TRACE_ENDS && depth < frames - TRACE_ENDS && depth >= TRACE_ENDS {
+                if depth == frames - TRACE_ENDS - 1 {
+                    let left_out = frames - 2 * TRACE_ENDS;
+                    described.push_str(&format!("\n    ... {} more calls", left_out));
+                }
+                continue;
+            }
+
+            described.push_str(&format!(
+                "\n    at {} ({})",
+                frame.name,
+                self.sources.location(frame.file, frame.row, frame.col)
+            ));
+        }
+
+        described
+    }
+
+    /// Runs a program, returning the value of its last statement.
+    pub fn run(&mut self, ast: &Ast) -> Result<Value, String> {
+        self.trace = None;
+
+        let env = Rc::clone(&self.env);
+        self.run_in(ast, &env)
+    }
+
+    /// Runs a program lexed from `file`, which its errors are placed in.
+    pub fn run_from(&mut self, ast: &Ast, file: FileId) -> Result<Value, String> {
+        let caller = mem::replace(&mut self.file, file);
+        let result = self.run(ast);
+        self.file = caller;
+        result
+    }
+
+    /// Runs the program in a file, resolving its imports relative to it.
+    pub fn run_file(&mut self, path: &str) -> Result<Value, String> {
+        self.trace = None;
+
+        let source = fs::read_to_string(path).map_err(|e| format!("{}: {}", path, e))?;
+        let file = self.sources.add(path.to_string(), source.clone());
+        let ast = Parser::new(Lexer::new(&source).in_file(file))
+            .parse_program()
+            .map_err(|e| self.parse_error(e, file))?;
+
+        let canonical = fs::canonicalize(path).map_err(|e| format!("{}: {}", path, e))?;
+        let previous = self.path.replace(PathBuf::from(path));
+        let caller = mem::replace(&mut self.file, file);
+        self.loading.push(canonical);
+
+        let result = self.run(&ast);
+
+        self.loading.pop();
+        self.file = caller;
+        self.path = previous;
+        result
+    }
+
+    fn run_in(&mut self, ast: &Ast, env: &Env) -> Result<Value, String> {
+        let result = self.eval_block(ast, env);
+        self.trace_error(&result);
+
+        match result {
+            Ok(value) | Err(Control::Return(value)) => Ok(value),
+            Err(Control::Error(error)) => Err(error),
+            Err(Control::Break) | Err(Control::Continue) => {
+                Err(String::from("break or continue outside of a loop"))
+            }
+        }
+    }
+
+    /// Notes where an error happened, on the way out of the innermost frame
+    /// it passes through.
+    fn trace_error<T>(&mut self, result: &Result<T, Control>) {
+        if let (Err(Control::Error(_)), None) = (result, &self.trace) {
+            self.trace = Some(Trace {
+                file: self.file,
+                frames: self.stack.clone(),
+            });
+        }
+    }
+
+    fn parse_error(&mut self, error: String, file: FileId) -> String {
+        self.trace = Some(Trace {
+            file,
+            frames: self.stack.clone(),
+        });
+        error
+    }
+
+    fn eval_block(&mut self, block: &[Statement], env: &Env) -> Eval {
+        let mut result = Value::Unit;
+
+        for statement in block {
+            result = self.eval_statement(statement, env)?;
+        }
+
+        Ok(result)
+    }
+
+    fn eval_statement(&mut self, statement: &Statement, env: &Env) -> Eval {
+        match statement {
+            Statement::Let(ls) => {
+                let value = self.eval_expression(&ls.expression, env)?;
+
+                if !match_pattern(&ls.pattern, &value, env)? {
+                    let token = ls.pattern.token();
+                    return Err(Control::Error(format!(
+                        "No match for {} in let {}.{}",
+                        value, token.row, token.col
+                    )));
+                }
+                bind_recursive(ls, &value);
+
+                Ok(Value::Unit)
+            }
+            Statement::Assign(assign) => {
+                let value = self.eval_expression(&assign.expression, env)?;
+                self.assign(&assign.name, value, env)?;
+
+                Ok(Value::Unit)
+            }
+            Statement::IndexAssign(assign) => self.eval_index_assign(assign, env),
+            Statement::Function(clause) => {
+                self.define_function(clause, env);
+
+                Ok(Value::Unit)
+            }
+            Statement::While(while_statement) => {
+                while self.eval_condition(&while_statement.condition, env)? {
+                    self.check_interrupt(&while_statement.token)?;
+                    match self.eval_block(&while_statement.body, &Environment::enclosed(env)) {
+                        Ok(_) | Err(Control::Continue) => {}
+                        Err(Control::Break) => break,
+                        Err(other) => return Err(other),
+                    }
+                }
+
+                Ok(Value::Unit)
+            }
+            Statement::For(for_statement) => self.eval_for(for_statement, env),
+            Statement::Break(_) => Err(Control::Break),
+            Statement::Continue(_) => Err(Control::Continue),
+            Statement::Return(expression) => {
+                let value = self.eval_expression(expression, env)?;
+
+                Err(Control::Return(value))
+            }
+            Statement::Expr(expression) => self.eval_expression(expression, env),
+            Statement::Import(import) => {
+                let module = self.import(import)?;
+                env.borrow_mut().define(&import.name(), module);
+
+                Ok(Value::Unit)
+            }
+            Statement::End => Err(Control::Error(String::from("Unexpected 'end'"))),
+        }
+    }
+
+    /// Loads the file an import names, running it in a scope of its own the
+    /// first time. A file that imports one of the files importing it is an
+    /// error, as it would see them half loaded.
+    fn import(&mut self, import: &ImportStatement) -> Result<Value, String> {
+        let token = &import.token;
+        let name = import.name();
+
+        if !self.filesystem {
+            return Err(format!(
+                "Cannot import {}, the filesystem is not allowed {}.{}",
+                name, token.row, token.col
+            ));
+        }
+        if !name.starts_with(|c: char| c.is_alphabetic()) || !name.chars().all(Lexer::is_letter) {
+            return Err(format!(
+                "Cannot name a module after {} {}.{}",
+                import.module, token.row, token.col
+            ));
+        }
+
+        let path = match self.path.as_ref().and_then(|file| file.parent()) {
+            Some(directory) => directory.join(import.path()),
+            None => import.path(),
+        };
+        let canonical = fs::canonicalize(&path).map_err(|e| {
+            format!(
+                "Cannot import {}: {} {}.{}",
+                path.display(),
+                e,
+                token.row,
+                token.col
+            )
+        })?;
+
+        if let Some(module) = self.modules.get(&canonical) {
+            return Ok(module.clone());
+        }
+        if let Some(start) = self.loading.iter().position(|file| *file == canonical) {
+            let cycle: Vec<String> = self.loading[start..]
+                .iter()
+                .chain(Some(&canonical))
+                .map(|file| file_name(file))
+                .collect();
+
+            return Err(format!(
+                "Import cycle {} {}.{}",
+                cycle.join(" -> "),
+                token.row,
+                token.col
+            ));
+        }
+
+        let source = fs::read_to_string(&path).map_err(|e| {
+            format!(
+                "Cannot import {}: {} {}.{}",
+                path.display(),
+                e,
+                token.row,
+                token.col
+            )
+        })?;
+        let file = self.sources.add(path.display().to_string(), source.clone());
+
+        self.stack
+            .push(Frame::new(format!("import {}", name), token));
+        let ast = Parser::new(Lexer::new(&source).in_file(file))
+            .parse_program()
+            .map_err(|e| self.parse_error(e, file));
+
+        let env = global_env();
+        if let Some(args) = self.env.borrow().get("Env") {
+            env.borrow_mut().define("Env", args);
+        }
+
+        let previous = self.path.replace(path);
+        let caller = mem::replace(&mut self.file, file);
+        self.loading.push(canonical.clone());
+        let result = ast.and_then(|ast| self.run_in(&ast, &env).map(|_| ast));
+        self.loading.pop();
+        self.file = caller;
+        self.path = previous;
+        self.stack.pop();
+
+        let ast = result?;
+
+        let mut exports = Vec::new();
+        for statement in &ast {
+            match statement {
+                Statement::Function(clause) if clause.public => {
+                    if let TokenType::Identity(name) = &clause.name.token_type {
+                        exports.push(name.clone());
+                    }
+                }
+                Statement::Let(ls) if ls.public => ls.pattern.bound_names(&mut exports),
+                _ => {}
+            }
+        }
+
+        let module = Value::Namespace(Rc::new(Namespace { name, env, exports }));
+        self.modules.insert(canonical, module.clone());
+
+        Ok(module)
+    }
+
+    fn eval_for(&mut self, for_statement: &ForStatement, env: &Env) -> Eval {
+        let iterable = self.eval_expression(&for_statement.iterable, env)?;
+
+        let elements = match iterable.iter() {
+            Some(elements) => elements,
+            None => {
+                let token = &for_statement.token;
+                return Err(Control::Error(format!(
+                    "Cannot iterate over {} {}.{}",
+                    iterable.type_name(),
+                    token.row,
+                    token.col
+                )));
+            }
+        };
+
+        for element in elements {
+            self.check_interrupt(&for_statement.token)?;
+            let iteration_env = Environment::enclosed(env);
+
+            if !match_pattern(&for_statement.pattern, &element, &iteration_env)? {
+                let token = for_statement.pattern.token();
+                return Err(Control::Error(format!(
+                    "No match for {} in for {}.{}",
+                    element.repr(),
+                    token.row,
+                    token.col
+                )));
+            }
+
+            match self.eval_block(&for_statement.body, &iteration_env) {
+                Ok(_) | Err(Control::Continue) => {}
+                Err(Control::Break) => break,
+                Err(other) => return Err(other),
+            }
+        }
+
+        Ok(Value::Unit)
+    }
+
+    fn assign(&mut self, name: &Token, value: Value, env: &Env) -> Result<(), String> {
+        let identifier = identifier_of(name)?;
+
+        let current = env.borrow().get(identifier).ok_or_else(|| {
+            format!(
+                "Undefined variable {} {}.{}",
+                identifier, name.row, name.col
+            )
+        })?;
+
+        if current.type_name() != value.type_name() {
+            return Err(format!(
+                "Cannot assign {} to {} of type {} {}.{}",
+                value.type_name(),
+                identifier,
+                current.type_name(),
+                name.row,
+                name.col
+            ));
+        }
+
+        env.borrow_mut().assign(identifier, value);
+
+        Ok(())
+    }
+
+    /// Adds a clause to the function of the same name in the current scope,
+    /// or starts a new function when there is none.
+    fn define_function(&mut self, clause: &FunctionClause, env: &Env) {
+        let name = match &clause.name.token_type {
+            TokenType::Identity(name) => name.clone(),
+            _ => return,
+        };
+
+        let mut clauses = match env.borrow().get_local(&name) {
+            Some(Value::Function(function)) => function.clauses.clone(),
+            _ => Vec::new(),
+        };
+        clauses.push(Rc::new(clause.clone()));
+
+        let function = Function {
+            name: name.clone(),
+            clauses,
+            env: Rc::clone(env),
+        };
+
+        env.borrow_mut()
+            .define(&name, Value::Function(Rc::new(function)));
+    }
+
+    fn eval_expression(&mut self, expression: &Expression, env: &Env) -> Eval {
+        match expression {
+            Expression::Value(token) => self.eval_value(token, env),
+            Expression::Prefix(prefix) => self.eval_prefix(prefix, env),
+            Expression::Infix(infix) => self.eval_infix(infix, env),
+            Expression::Call(call) => self.eval_call(call, env),
+            Expression::If(if_expr) => self.eval_if(if_expr, env),
+            Expression::Match(match_expr) => self.eval_match(match_expr, env),
+            Expression::List(list) => self.eval_list(list, env),
+            Expression::Array(array) => {
+                let mut elements = Vec::with_capacity(array.elements.len());
+                for element in &array.elements {
+                    elements.push(self.eval_expression(element, env)?);
+                }
+
+                Ok(Value::Array(Rc::new(RefCell::new(elements))))
+            }
+            Expression::Dict(dict) => self.eval_dict(dict, env),
+            Expression::Index(index) => self.eval_index(index, env),
+            Expression::Access(access) => self.eval_access(access, env),
+            Expression::Function(clause) => Ok(self.make_closure(expression, clause, env)),
+        }
+    }
+
+    /// Anonymous functions capture the values of the names they reference
+    /// when they are created. Names not visible at that point are looked up
+    /// in the global scope of their file when the function is called.
+    fn make_closure(
+        &self,
+        expression: &Expression,
+        clause: &Rc<FunctionClause>,
+        env: &Env,
+    ) -> Value {
+        let captured = Environment::enclosed(&Environment::global(env));
+
+        let mut names = Vec::new();
+        expression.referenced_names(&mut names);
+
+        for name in names {
+            if let Some(value) = env.borrow().get(&name) {
+                captured.borrow_mut().define(&name, value);
+            }
+        }
+
+        Value::Function(Rc::new(Function {
+            name: String::from("fn"),
+            clauses: vec![Rc::clone(clause)],
+            env: captured,
+        }))
+    }
+
+    fn eval_index(&mut self, index: &IndexExpr, env: &Env) -> Eval {
+        let left = self.eval_expression(&index.left, env)?;
+        let position = self.eval_expression(&index.index, env)?;
+        let token = &index.token;
+
+        let result = match (&left, &position) {
+            (Value::Str(s), Value::Int(i)) => {
+                let len = s.chars().count();
+                index_bounds(*i, len).and_then(|i| s.chars().nth(i).map(Value::Char))
+            }
+            (Value::Str(s), Value::Range(start, end)) => {
+                let len = s.chars().count();
+                slice_bounds(*start, *end, len).map(|(start, end)| {
+                    Value::Str(s.chars().skip(start).take(end - start).collect())
+                })
+            }
+            (Value::Dict(dict), key) => {
+                let key = Key::of(key, token)?;
+                match dict.get(&key) {
+                    Some(value) => Some(value.clone()),
+                    None => {
+                        return Err(Control::Error(format!(
+                            "Key {} not found {}.{}",
+                            position.repr(),
+                            token.row,
+                            token.col
+                        )))
+                    }
+                }
+            }
+            (Value::Array(array), Value::Int(i)) => {
+                let array = array.borrow();
+                index_bounds(*i, array.len()).map(|i| array[i].clone())
+            }
+            (Value::Array(array), Value::Range(start, end)) => {
+                let array = array.borrow();
+                slice_bounds(*start, *end, array.len()).map(|(start, end)| {
+                    Value::Array(Rc::new(RefCell::new(array[start..end].to_vec())))
+                })
+            }
+            _ => {
+                return Err(Control::Error(format!(
+                    "Cannot index {} with {} {}.{}",
+                    left.type_name(),
+                    position.type_name(),
+                    token.row,
+                    token.col
+                )));
+            }
+        };
+
+        result.ok_or_else(|| {
+            Control::Error(format!(
+                "Index {} out of bounds for {} {}.{}",
+                position,
+                left.type_name(),
+                token.row,
+                token.col
+            ))
+        })
+    }
+
+    /// Elements are evaluated left to right, then the tail, then the elements
+    /// are prepended to the tail without copying it.
+    fn eval_list(&mut self, list: &ListExpr, env: &Env) -> Eval {
+        let mut elements = Vec::with_capacity(list.elements.len());
+        for element in &list.elements {
+            elements.push(self.eval_expression(element, env)?);
+        }
+
+        let tail = match &list.tail {
+            Some(tail) => match self.eval_expression(tail, env)? {
+                Value::List(tail) => tail,
+                other => {
+                    return Err(Control::Error(format!(
+                        "Cannot prepend to {} {}.{}",
+                        other.type_name(),
+                        list.token.row,
+                        list.token.col
+                    )))
+                }
+            },
+            None => List::new(),
+        };
+
+        Ok(Value::List(
+            elements
+                .into_iter()
+                .rev()
+                .fold(tail, |list, element| list.prepend(element)),
+        ))
+    }
+
+    /// Writes into an array in place. The array is evaluated first, then the
+    /// index, then the new value.
+    fn eval_index_assign(&mut self, assign: &IndexAssignStatement, env: &Env) -> Eval {
+        let target = self.eval_expression(&assign.target.left, env)?;
+        let position = self.eval_expression(&assign.target.index, env)?;
+        let value = self.eval_expression(&assign.expression, env)?;
+        let token = &assign.target.token;
+
+        let (array, i) = match (&target, &position) {
+            (Value::Array(array), Value::Int(i)) => (array, *i),
+            _ => {
+                return Err(Control::Error(format!(
+                    "Cannot assign to {} index {} {}.{}",
+                    target.type_name(),
+                    position.type_name(),
+                    token.row,
+                    token.col
+                )))
+            }
+        };
+
+        let mut array = array.borrow_mut();
+        match index_bounds(i, array.len()) {
+            Some(i) => array[i] = value,
+            None => {
+                return Err(Control::Error(format!(
+                    "Index {} out of bounds for array {}.{}",
+                    i, token.row, token.col
+                )))
+            }
+        }
+
+        Ok(Value::Unit)
+    }
+
+    fn eval_access(&mut self, access: &AccessExpr, env: &Env) -> Eval {
+        let left = self.eval_expression(&access.left, env)?;
+        let name = identifier_of(&access.name)?;
+        let token = &access.name;
+
+        match &left {
+            Value::Module(module) => module.get(name).map(Value::Builtin).ok_or_else(|| {
+                Control::Error(format!(
+                    "{} has no function {} {}.{}",
+                    module.name, name, token.row, token.col
+                ))
+            }),
+            Value::Namespace(namespace) => namespace.get(name).ok_or_else(|| {
+                let reason = match namespace.env.borrow().get_local(name) {
+                    Some(_) => "is private to",
+                    None => "is not defined in",
+                };
+
+                Control::Error(format!(
+                    "{} {} {} {}.{}",
+                    name, reason, namespace.name, token.row, token.col
+                ))
+            }),
+            Value::Dict(_) => left
+                .dict_get(&Value::Str(name.to_string()))
+                .cloned()
+                .ok_or_else(|| {
+                    Control::Error(format!(
+                        "Key {} not found {}.{}",
+                        Value::Str(name.to_string()).repr(),
+                        token.row,
+                        token.col
+                    ))
+                }),
+            other => Err(Control::Error(format!(
+                "Cannot access {} on {} {}.{}",
+                name,
+                other.type_name(),
+                token.row,
+                token.col
+            ))),
+        }
+    }
+
+    /// When a key appears more than once the last value wins, at the position
+    /// of the key's first appearance.
+    fn eval_dict(&mut self, dict_expr: &DictExpr, env: &Env) -> Eval {
+        let mut dict = Dict::new();
+
+        for (key_expr, value) in &dict_expr.entries {
+            let key = self.eval_expression(key_expr, env)?;
+            let key = Key::of(&key, key_expr.token())?;
+            let value = self.eval_expression(value, env)?;
+
+            dict.insert(key, value);
+        }
+
+        Ok(Value::Dict(Rc::new(dict)))
+    }
+
+    fn eval_value(&mut self, token: &Token, env: &Env) -> Eval {
+        if let Some(value) = Value::from_literal(token) {
+            return Ok(value);
+        }
+
+        let identifier = identifier_of(token)?;
+
+        env.borrow().get(identifier).ok_or_else(|| {
+            Control::Error(format!(
+                "Undefined variable {} {}.{}",
+                identifier, token.row, token.col
+            ))
+        })
+    }
+
+    fn eval_prefix(&mut self, prefix: &PrefixExpr, env: &Env) -> Eval {
+        let right = self.eval_expression(&prefix.right, env)?;
+        let operator = &prefix.operator;
+
+        match (&operator.token_type, right) {
+            (TokenType::Not, Value::Bool(b)) => Ok(Value::Bool(!b)),
+            (TokenType::Minus, Value::Int(i)) => match i.checked_neg() {
+                Some(negated) => Ok(Value::Int(negated)),
+                None => Err(Control::Error(format!(
+                    "Integer overflow {}.{}",
+                    operator.row, operator.col
+                ))),
+            },
+            (_, right) => Err(Control::Error(format!(
+                "Unsupported operand {} for {} {}.{}",
+                right.type_name(),
+                operator,
+                operator.row,
+                operator.col
+            ))),
+        }
+    }
+
+    fn eval_infix(&mut self, infix: &InfixExpr, env: &Env) -> Eval {
+        let operator = &infix.operator;
+
+        if let TokenType::And | TokenType::Or = operator.token_type {
+            let left = self.eval_condition(&infix.left, env)?;
+
+            return match (&operator.token_type, left) {
+                (TokenType::And, false) => Ok(Value::Bool(false)),
+                (TokenType::Or, true) => Ok(Value::Bool(true)),
+                _ => Ok(Value::Bool(self.eval_condition(&infix.right, env)?)),
+            };
+        }
+
+        let left = self.eval_expression(&infix.left, env)?;
+        let right = self.eval_expression(&infix.right, env)?;
+
+        let result = match (&operator.token_type, &left, &right) {
+            (TokenType::Equal, _, _) => Value::Bool(left == right),
+            (TokenType::NotEqual, _, _) => Value::Bool(left != right),
+            (TokenType::Xor, Value::Bool(a), Value::Bool(b)) => Value::Bool(a ^ b),
+            (
+                TokenType::Plus
+                | TokenType::Minus
+                | TokenType::Multiply
+                | TokenType::Divide
+                | TokenType::Modulo,
+                Value::Int(a),
+                Value::Int(b),
+            ) => Value::Int(arithmetic(operator, *a, *b)?),
+            (TokenType::Plus, Value::Str(a), Value::Str(b)) => Value::Str(format!("{}{}", a, b)),
+            (TokenType::Range, Value::Int(a), Value::Int(b)) => Value::Range(*a, *b),
+            (TokenType::RangeInclusive, Value::Int(a), Value::Int(b)) => match b.checked_add(1) {
+                Some(end) => Value::Range(*a, end),
+                None => {
+                    return Err(Control::Error(format!(
+                        "Range end {} is too large {}.{}",
+                        b, operator.row, operator.col
+                    )));
+                }
+            },
+            (TokenType::GreaterThan, Value::Int(a), Value::Int(b)) => Value::Bool(a > b),
+            (TokenType::LessThan, Value::Int(a), Value::Int(b)) => Value::Bool(a < b),
+            (TokenType::GreaterEqual, Value::Int(a), Value::Int(b)) => Value::Bool(a >= b),
+            (TokenType::LessEqual, Value::Int(a), Value::Int(b)) => Value::Bool(a <= b),
+            (TokenType::GreaterThan, Value::Str(a), Value::Str(b)) => Value::Bool(a > b),
+            (TokenType::LessThan, Value::Str(a), Value::Str(b)) => Value::Bool(a < b),
+            (TokenType::GreaterEqual, Value::Str(a), Value::Str(b)) => Value::Bool(a >= b),
+            (TokenType::LessEqual, Value::Str(a), Value::Str(b)) => Value::Bool(a <= b),
+            (TokenType::GreaterThan, Value::Char(a), Value::Char(b)) => Value::Bool(a > b),
+            (TokenType::LessThan, Value::Char(a), Value::Char(b)) => Value::Bool(a < b),
+            (TokenType::GreaterEqual, Value::Char(a), Value::Char(b)) => Value::Bool(a >= b),
+            (TokenType::LessEqual, Value::Char(a), Value::Char(b)) => Value::Bool(a <= b),
+            _ => {
+                return Err(Control::Error(format!(
+                    "Unsupported operands {} and {} for {} {}.{}",
+                    left.type_name(),
+                    right.type_name(),
+                    operator,
+                    operator.row,
+                    operator.col
+                )));
+            }
+        };
+
+        Ok(result)
+    }
+
+    fn eval_condition(&mut self, expression: &Expression, env: &Env) -> Result<bool, Control> {
+        match self.eval_expression(expression, env)? {
+            Value::Bool(b) => Ok(b),
+            other => {
+                let token = expression.token();
+                Err(Control::Error(format!(
+                    "Expected bool, got {} {}.{}",
+                    other.type_name(),
+                    token.row,
+                    token.col
+                )))
+            }
+        }
+    }
+
+    fn eval_if(&mut self, if_expr: &IfExpr, env: &Env) -> Eval {
+        for (condition, block) in &if_expr.branches {
+            if self.eval_condition(condition, env)? {
+                return self.eval_block(block, &Environment::enclosed(env));
+            }
+        }
+
+        match &if_expr.alternative {
+            Some(block) => self.eval_block(block, &Environment::enclosed(env)),
+            None => Ok(Value::Unit),
+        }
+    }
+
+    /// Arms are tried in order with the same pattern engine as `let` and
+    /// function heads. Falling off the end is a runtime error.
+    fn eval_match(&mut self, match_expr: &MatchExpr, env: &Env) -> Eval {
+        let subject = self.eval_expression(&match_expr.subject, env)?;
+
+        for arm in &match_expr.arms {
+            let arm_env = Environment::enclosed(env);
+
+            if !match_pattern(&arm.pattern, &subject, &arm_env)? {
+                continue;
+            }
+
+            if let Some(guard) = &arm.guard {
+                if !self.eval_condition(guard, &arm_env)? {
+                    continue;
+                }
+            }
+
+            return self.eval_expression(&arm.body, &arm_env);
+        }
+
+        let token = &match_expr.token;
+        Err(Control::Error(format!(
+            "No match for {} {}.{}",
+            subject.repr(),
+            token.row,
+            token.col
+        )))
+    }
+
+    fn eval_call(&mut self, call: &CallExpr, env: &Env) -> Eval {
+        let function = self.eval_expression(&call.function, env)?;
+
+        let mut arguments = Vec::with_capacity(call.arguments.len());
+        for argument in &call.arguments {
+            arguments.push(self.eval_expression(argument, env)?);
+        }
+
+        self.call(function, arguments, call.function.token())
+    }
+
+    pub fn call(&mut self, function: Value, arguments: Vec<Value>, token: &Token) -> Eval {
+        match function {
+            Value::Function(_) | Value::Builtin(_) => self.apply(function, arguments, token),
+            Value::Partial(partial) => {
+                let mut combined = partial.arguments.clone();
+                combined.extend(arguments);
+                self.apply(partial.function.clone(), combined, token)
+            }
+            other => Err(Control::Error(format!(
+                "{} is not a function {}.{}",
+                other.type_name(),
+                token.row,
+                token.col
+            ))),
+        }
+    }
+
+    /// Calls are curried only when no clause takes exactly as many arguments
+    /// as were given. Then, if at least one argument was given and some clause
+    /// takes more, the result is a partial application waiting for the rest.
+    /// Any other call is an arity error, so overloads by arity are never
+    /// shadowed by currying.
+    fn apply(&mut self, function: Value, arguments: Vec<Value>, token: &Token) -> Eval {
+        let (name, arities) = match &function {
+            Value::Function(function) if function.takes(arguments.len()) => {
+                return self.call_function(function, arguments, token);
+            }
+            Value::Builtin(builtin) if builtin.arity == arguments.len() => {
+                return (builtin.function)(self, arguments, token);
+            }
+            Value::Function(function) => (function.name.clone(), function.arities()),
+            Value::Builtin(builtin) => (builtin.to_string(), vec![builtin.arity]),
+            _ => unreachable!(),
+        };
+
+        if !arguments.is_empty() && arities.iter().any(|arity| *arity > arguments.len()) {
+            return Ok(Value::Partial(Rc::new(Partial {
+                function,
+                arguments,
+            })));
+        }
+
+        let arities: Vec<String> = arities.iter().map(usize::to_string).collect();
+
+        Err(Control::Error(format!(
+            "{} takes {} arguments, got {} {}.{}",
+            name,
+            arities.join(" or "),
+            arguments.len(),
+            token.row,
+            token.col
+        )))
+    }
+
+    /// Tries each clause in source order. A clause is chosen when its arity
+    /// matches, every parameter pattern matches, and its guard (evaluated with
+    /// the pattern bindings in scope) is True.
+    fn call_function(&mut self, function: &Function, arguments: Vec<Value>, token: &Token) -> Eval {
+        self.check_interrupt(token)?;
+        if self.stack.len() >= MAX_CALL_DEPTH {
+            return Err(Control::Error(format!(
+                "Stack overflow calling {}, more than {} calls deep {}.{}",
+                function.name, MAX_CALL_DEPTH, token.row, token.col
+            )));
+        }
+
+        let file = function
+            .clauses
+            .first()
+            .map_or(self.file, |clause| clause.name.file);
+        let caller = mem::replace(&mut self.file, file);
+        self.stack.push(Frame::new(function.name.clone(), token));
+
+        let result = self.call_clauses(function, &arguments);
+        self.trace_error(&result);
+
+        self.stack.pop();
+        self.file = caller;
+
+        if let Some(value) = result? {
+            return Ok(value);
+        }
+
+        let argument_types: Vec<&str> = arguments.iter().map(Value::type_name).collect();
+
+        Err(Control::Error(format!(
+            "No clause of {} matches ({}) {}.{}",
+            function.name,
+            argument_types.join(", "),
+            token.row,
+            token.col
+        )))
+    }
+
+    /// The result of the first clause that takes the arguments, or `None`
+    /// when none does.
+    fn call_clauses(
+        &mut self,
+        function: &Function,
+        arguments: &[Value],
+    ) -> Result<Option<Value>, Control> {
+        for clause in &function.clauses {
+            if clause.parameters.len() != arguments.len() {
+                continue;
+            }
+
+            let env = Environment::enclosed(&function.env);
+
+            let mut matched = true;
+            for (parameter, argument) in clause.parameters.iter().zip(arguments) {
+                if !match_pattern(parameter, argument, &env)? {
+                    matched = false;
+                    break;
+                }
+            }
+
+            if !matched {
+                continue;
+            }
+
+            if let Some(guard) = &clause.guard {
+                if !self.eval_condition(guard, &env)? {
+                    continue;
+                }
+            }
+
+            let result = match self.eval_block(&clause.body, &env) {
+                Ok(value) | Err(Control::Return(value)) => value,
+                Err(error) => return Err(error),
+            };
+
+            if let Some(return_type) = &clause.return_type {
+                if !result.is_type(return_type) {
+                    return Err(Control::Error(format!(
+                        "{} returned {}, expected {} {}.{}",
+                        function.name,
+                        result.type_name(),
+                        type_name_of(return_type),
+                        clause.name.row,
+                        clause.name.col
+                    )));
+                }
+            }
+
+            return Ok(Some(result));
+        }
+
+        Ok(None)
+    }
+}
+
+/// Applies an arithmetic operator to two ints, failing rather than wrapping
+/// when the result does not fit.
+fn arithmetic(operator: &Token, a: isize, b: isize) -> Result<isize, String> {
+    if b == 0 {
+        if let TokenType::Divide | TokenType::Modulo = operator.token_type {
+            return Err(format!(
+                "Division by zero {}.{}",
+                operator.row, operator.col
+            ));
+        }
+    }
+
+    let result = match operator.token_type {
+        TokenType::Plus => a.checked_add(b),
+        TokenType::Minus => a.checked_sub(b),
+        TokenType::Multiply => a.checked_mul(b),
+        TokenType::Divide => a.checked_div(b),
+        _ => a.checked_rem(b),
+    };
+
+    result.ok_or_else(|| format!("Integer overflow {}.{}", operator.row, operator.col))
+}
+
+pub fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or(String::new(), |name| name.to_string_lossy().into_owned())
+}
+
+/// Lets an anonymous function bound by `let g = fn ...` call itself as `g`,
+/// which it could not capture as it did not exist yet when it was created.
+fn bind_recursive(ls: &LetStatement, value: &Value) {
+    let (name, function) = match (&ls.pattern, &ls.expression, value) {
+        (
+            Pattern::Binding(
+                Token {
+                    token_type: TokenType::Identity(name),
+                    ..
+                },
+                _,
+            ),
+            Expression::Function(_),
+            Value::Function(function),
+        ) => (name, function),
+        _ => return,
+    };
+
+    let mut names = Vec::new();
+    ls.expression.referenced_names(&mut names);
+    if names.contains(name) {
+        function.env.borrow_mut().define(name, value.clone());
+    }
+}
+
+fn identifier_of(token: &Token) -> Result<&str, String> {
+    match &token.token_type {
+        TokenType::Identity(name) => Ok(name),
+        _ => Err(format!(
+            "Unexpected token expected Identity, got {} {}.{}",
+            token, token.row, token.col
+        )),
+    }
+}
@@ -0,0 +1,491 @@
+//! Converts between JSON text and runtime values. Objects become dicts with
+//! their keys in document order, arrays become lists, numbers become ints
+//! when they are whole and fit and floats otherwise, and `null` becomes `()`.
+
+use crate::dict::{Dict, Key};
+use crate::value::{array_key, visit, Value};
+use std::fmt;
+use std::rc::Rc;
+
+/// Objects and arrays nested deeper than this are rejected rather than
+/// risking the stack.
+const MAX_DEPTH: usize = 512;
+
+#[derive(Debug, PartialEq)]
+pub struct JsonError {
+    pub message: String,
+    pub line: usize,
+    pub col: usize,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} at line {}, column {}",
+            self.message, self.line, self.col
+        )
+    }
+}
+
+pub fn decode(text: &str) -> Result<Value, JsonError> {
+    let mut decoder = Decoder {
+        chars: text.chars().collect(),
+        position: 0,
+        line: 1,
+        col: 1,
+        depth: 0,
+    };
+
+    decoder.skip_whitespace();
+    let value = decoder.value()?;
+    decoder.skip_whitespace();
+
+    match decoder.peek() {
+        None => Ok(value),
+        Some(c) => Err(decoder.error(format!("Unexpected {:?} after the value", c))),
+    }
+}
+
+struct Decoder {
+    chars: Vec<char>,
+    position: usize,
+    line: usize,
+    col: usize,
+    depth: usize,
+}
+
+impl Decoder {
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.position += 1;
+
+        if c == '\n' {
+            self.line += 1;
+            self.col = 1;
+        } else {
+            self.col += 1;
+        }
+
+        Some(c)
+    }
+
+    /// An error at the current position.
+    fn error(&self, message: String) -> JsonError {
+        JsonError {
+            message,
+            line: self.line,
+            col: self.col,
+        }
+    }
+
+    fn unexpected(&self, expected: &str) -> JsonError {
+        match self.peek() {
+            Some(c) => self.error(format!("Expected {}, got {:?}", expected, c)),
+            None => self.error(format!("Expected {}, got end of input", expected)),
+        }
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(' ') | Some('\t') | Some('\n') | Some('\r') = self.peek() {
+            self.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), JsonError> {
+        if self.peek() == Some(expected) {
+            self.next();
+            Ok(())
+        } else {
+            Err(self.unexpected(&format!("{:?}", expected)))
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, JsonError> {
+        match self.peek() {
+            Some('{') => self.nested(Decoder::object),
+            Some('[') => self.nested(Decoder::array),
+            Some('"') => Ok(Value::Str(self.string()?)),
+            Some('-') | Some('0'..='9') => self.number(),
+            Some('t') => self.literal("true", Value::Bool(true)),
+            Some('f') => self.literal("false", Value::Bool(false)),
+            Some('n') => self.literal("null", Value::Unit),
+            _ => Err(self.unexpected("a value")),
+        }
+    }
+
+    fn nested(
+        &mut self,
+        parse: fn(&mut Decoder) -> Result<Value, JsonError>,
+    ) -> Result<Value, JsonError> {
+        if self.depth == MAX_DEPTH {
+            return Err(self.error(format!("Nested more than {} levels deep", MAX_DEPTH)));
+        }
+
+        self.depth += 1;
+        let value = parse(self);
+        self.depth -= 1;
+
+        value
+    }
+
+    fn literal(&mut self, word: &str, value: Value) -> Result<Value, JsonError> {
+        let start = self.error(String::new());
+
+        for expected in word.chars() {
+            if self.peek() != Some(expected) {
+                return Err(JsonError {
+                    message: format!("Expected {}", word),
+                    ..start
+                });
+            }
+            self.next();
+        }
+
+        Ok(value)
+    }
+
+    /// Duplicate keys keep the position of their first appearance and the
+    /// value of their last.
+    fn object(&mut self) -> Result<Value, JsonError> {
+        self.expect('{')?;
+        self.skip_whitespace();
+
+        let mut dict = Dict::new();
+
+        if self.peek() == Some('}') {
+            self.next();
+            return Ok(Value::Dict(Rc::new(dict)));
+        }
+
+        loop {
+            if self.peek() != Some('"') {
+                return Err(self.unexpected("a string key"));
+            }
+            let key = self.string()?;
+
+            self.skip_whitespace();
+            self.expect(':')?;
+            self.skip_whitespace();
+
+            let value = self.value()?;
+            dict.insert(Key::Str(key), value);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                    self.skip_whitespace();
+                }
+                Some('}') => {
+                    self.next();
+                    return Ok(Value::Dict(Rc::new(dict)));
+                }
+                _ => return Err(self.unexpected("',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self) -> Result<Value, JsonError> {
+        self.expect('[')?;
+        self.skip_whitespace();
+
+        let mut elements = Vec::new();
+
+        if self.peek() == Some(']') {
+            self.next();
+            return Ok(Value::List(elements.into_iter().collect()));
+        }
+
+        loop {
+            elements.push(self.value()?);
+
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => {
+                    self.next();
+                    self.skip_whitespace();
+                }
+                Some(']') => {
+                    self.next();
+                    return Ok(Value::List(elements.into_iter().collect()));
+                }
+                _ => return Err(self.unexpected("',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, JsonError> {
+        self.expect('"')?;
+
+        let mut s = String::new();
+
+        loop {
+            match self.peek() {
+                None => return Err(self.error(String::from("Unterminated string"))),
+                Some('"') => {
+                    self.next();
+                    return Ok(s);
+                }
+                Some('\\') => {
+                    self.next();
+                    s.push(self.escape()?);
+                }
+                Some(c) if (c as u32) < 0x20 => {
+                    return Err(self.error(format!("Unescaped control character {:?}", c)));
+                }
+                Some(c) => {
+                    self.next();
+                    s.push(c);
+                }
+            }
+        }
+    }
+
+    /// The character after a backslash.
+    fn escape(&mut self) -> Result<char, JsonError> {
+        let c = match self.peek() {
+            Some('"') => '"',
+            Some('\\') => '\\',
+            Some('/') => '/',
+            Some('b') => '\u{8}',
+            Some('f') => '\u{c}',
+            Some('n') => '\n',
+            Some('r') => '\r',
+            Some('t') => '\t',
+            Some('u') => {
+                self.next();
+                return self.unicode_escape();
+            }
+            _ => return Err(self.unexpected("an escape character")),
+        };
+
+        self.next();
+        Ok(c)
+    }
+
+    /// `\uXXXX`, where characters outside the basic multilingual plane are
+    /// written as a surrogate pair of two escapes.
+    fn unicode_escape(&mut self) -> Result<char, JsonError> {
+        let high = self.hex()?;
+
+        if !(0xD800..0xDC00).contains(&high) {
+            return std::char::from_u32(high)
+                .ok_or_else(|| self.error(String::from("Invalid unicode escape")));
+        }
+
+        self.expect('\\')?;
+        self.expect('u')?;
+        let low = self.hex()?;
+
+        if !(0xDC00..0xE000).contains(&low) {
+            return Err(self.error(String::from("Invalid unicode surrogate pair")));
+        }
+
+        let code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+        std::char::from_u32(code).ok_or_else(|| self.error(String::from("Invalid unicode escape")))
+    }
+
+    fn hex(&mut self) -> Result<u32, JsonError> {
+        let mut code = 0;
+
+        for _ in 0..4 {
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    self.next();
+                    code = code * 16 + digit;
+                }
+                None => return Err(self.unexpected("a hex digit")),
+            }
+        }
+
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<Value, JsonError> {
+        let start = self.position;
+        let mut whole = true;
+
+        if self.peek() == Some('-') {
+            self.next();
+        }
+
+        match self.peek() {
+            Some('0') => {
+                self.next();
+            }
+            Some('1'..='9') => self.digits(),
+            _ => return Err(self.unexpected("a digit")),
+        }
+
+        if self.peek() == Some('.') {
+            whole = false;
+            self.next();
+            self.required_digits()?;
+        }
+
+        if let Some('e') | Some('E') = self.peek() {
+            whole = false;
+            self.next();
+            if let Some('+') | Some('-') = self.peek() {
+                self.next();
+            }
+            self.required_digits()?;
+        }
+
+        let text: String = self.chars[start..self.position].iter().collect();
+
+        if whole {
+            if let Ok(i) = text.parse::<isize>() {
+                return Ok(Value::Int(i));
+            }
+        }
+
+        match text.parse::<f64>() {
+            Ok(f) if f.is_finite() => Ok(Value::Float(f)),
+            _ => Err(self.error(format!("Number {} is out of range", text))),
+        }
+    }
+
+    fn digits(&mut self) {
+        while let Some('0'..='9') = self.peek() {
+            self.next();
+        }
+    }
+
+    fn required_digits(&mut self) -> Result<(), JsonError> {
+        match self.peek() {
+            Some('0'..='9') => {
+                self.digits();
+                Ok(())
+            }
+            _ => Err(self.unexpected("a digit")),
+        }
+    }
+}
+
+/// Writes a value as JSON, on one line or, when `pretty`, indented by two
+/// spaces. Only dicts with string keys, lists, arrays, ints, finite floats,
+/// strings, characters, bools and `()` can be encoded.
+pub fn encode(value: &Value, pretty: bool) -> Result<String, String> {
+    let mut out = String::new();
+    write_value(value, pretty, 0, &mut out)?;
+    Ok(out)
+}
+
+fn write_value(value: &Value, pretty: bool, depth: usize, out: &mut String) -> Result<(), String> {
+    match value {
+        Value::Int(i) => out.push_str(&i.to_string()),
+        Value::Float(f) if f.is_finite() => out.push_str(&format!("{:?}", f)),
+        Value::Str(s) => write_string(s, out),
+        Value::Char(c) => write_string(&c.to_string(), out),
+        Value::Bool(b) => out.push_str(if *b { "true" } else { "false" }),
+        Value::Unit => out.push_str("null"),
+        Value::List(list) => write_sequence('[', ']', list.iter(), pretty, depth, out)?,
+        Value::Array(array) => visit(array_key(array, None), || {
+            let array = array.borrow();
+            write_sequence('[', ']', array.iter(), pretty, depth, out)
+        })
+        .unwrap_or_else(|| {
+            Err(String::from(
+                "Cannot encode an array holding itself as JSON",
+            ))
+        })?,
+        Value::Dict(dict) => {
+            out.push('{');
+
+            for (i, (key, value)) in dict.entries().iter().enumerate() {
+                if i > 0 {
+                    out.push(',');
+                }
+                newline(pretty, depth + 1, out);
+
+                match key {
+                    Key::Str(key) => write_string(key, out),
+                    other => {
+                        return Err(format!(
+                            "Cannot encode {} dict key {} as JSON",
+                            other.to_value().type_name(),
+                            other.to_value().repr()
+                        ))
+                    }
+                }
+
+                out.push(':');
+                if pretty {
+                    out.push(' ');
+                }
+                write_value(value, pretty, depth + 1, out)?;
+            }
+
+            if !dict.is_empty() {
+                newline(pretty, depth, out);
+            }
+            out.push('}');
+        }
+        other => return Err(format!("Cannot encode {} as JSON", other.type_name())),
+    }
+
+    Ok(())
+}
+
+fn write_sequence<'a>(
+    open: char,
+    close: char,
+    elements: impl Iterator<Item = &'a Value>,
+    pretty: bool,
+    depth: usize,
+    out: &mut String,
+) -> Result<(), String> {
+    out.push(open);
+
+    let mut empty = true;
+    for (i, element) in elements.enumerate() {
+        if i > 0 {
+            out.push(',');
+        }
+        newline(pretty, depth + 1, out);
+        write_value(element, pretty, depth + 1, out)?;
+        empty = false;
+    }
+
+    if !empty {
+        newline(pretty, depth, out);
+    }
+    out.push(close);
+
+    Ok(())
+}
+
+fn newline(pretty: bool, depth: usize, out: &mut String) {
+    if pretty {
+        out.push('\n');
+        for _ in 0..depth {
+            out.push_str("  ");
+        }
+    }
+}
+
+fn write_string(s: &str, out: &mut String) {
+    out.push('"');
+
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{8}' => out.push_str("\\b"),
+            '\u{c}' => out.push_str("\\f"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+
+    out.push('"');
+}
@@ -1,23 +1,143 @@
+use crate::source::FileId;
 use crate::token::{Token, TokenType};
-use std::iter::Peekable;
-use std::str::Chars;
+
+/// The words that lex as keywords rather than identifiers.
+pub const KEYWORDS: &[(&str, TokenType)] = &[
+    ("let", TokenType::Let),
+    ("fn", TokenType::Function),
+    ("end", TokenType::End),
+    ("string", TokenType::StringType),
+    ("bool", TokenType::BoolType),
+    ("int", TokenType::IntType),
+    ("char", TokenType::CharType),
+    ("if", TokenType::If),
+    ("else", TokenType::Else),
+    ("elif", TokenType::Elif),
+    ("and", TokenType::And),
+    ("or", TokenType::Or),
+    ("xor", TokenType::Xor),
+    ("when", TokenType::When),
+    ("match", TokenType::Match),
+    ("while", TokenType::While),
+    ("for", TokenType::For),
+    ("in", TokenType::In),
+    ("break", TokenType::Break),
+    ("continue", TokenType::Continue),
+    ("True", TokenType::True),
+    ("False", TokenType::False),
+    ("return", TokenType::Return),
+    ("import", TokenType::Import),
+    ("pub", TokenType::Pub),
+];
+
+/// The characters of the input, tracking the byte offset of the next one.
+struct Source<'a> {
+    input: &'a str,
+    offset: usize,
+    peeked: Option<char>,
+}
+
+impl<'a> Source<'a> {
+    fn new(input: &'a str, offset: usize) -> Self {
+        Source {
+            input,
+            offset,
+            peeked: input[offset..].chars().next(),
+        }
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let c = self.peeked?;
+        self.offset += c.len_utf8();
+        self.peeked = self.input[self.offset..].chars().next();
+        Some(c)
+    }
+
+    fn peek(&self) -> Option<&char> {
+        self.peeked.as_ref()
+    }
+}
 
 pub struct Lexer<'a> {
-    position: Peekable<Chars<'a>>,
+    position: Source<'a>,
     row: u32,
     col: u32,
+    after_operand: bool,
+    /// Whether whitespace and comments are returned as tokens rather than
+    /// skipped.
+    trivia: bool,
+    file: FileId,
 }
 
 impl<'a> Lexer<'a> {
     pub fn new(input: &'a str) -> Self {
         Lexer {
-            position: input.chars().peekable(),
+            position: Source::new(input, 0),
             row: 1,
             col: 0,
+            after_operand: false,
+            trivia: false,
+            file: FileId::default(),
+        }
+    }
+
+    /// A lexer that also returns runs of spaces and tabs as `Whitespace`
+    /// tokens and comments as `Comment` tokens, so that together the tokens
+    /// cover every character of the input.
+    pub fn with_trivia(input: &'a str) -> Self {
+        Lexer {
+            trivia: true,
+            ..Lexer::new(input)
         }
     }
 
+    /// A lexer that starts `offset` bytes into the input, at the beginning
+    /// of line `row`. Started just after a `NewLine` token it gives the same
+    /// tokens as lexing the whole input would from there.
+    pub fn at_line(input: &'a str, offset: usize, row: u32) -> Self {
+        Lexer {
+            position: Source::new(input, offset),
+            row,
+            ..Lexer::new(input)
+        }
+    }
+
+    /// Marks every token as coming from `file`.
+    pub fn in_file(self, file: FileId) -> Self {
+        Lexer { file, ..self }
+    }
+
+    /// The byte offset of the first character not lexed yet.
+    pub fn offset(&self) -> usize {
+        self.position.offset
+    }
+
     pub fn next(&mut self) -> Token {
+        let mut token = self.next_token();
+        token.file = self.file;
+
+        if let TokenType::Whitespace | TokenType::Comment = token.token_type {
+            return token;
+        }
+
+        self.after_operand = matches!(
+            token.token_type,
+            TokenType::Identity(_)
+                | TokenType::Int(_)
+                | TokenType::Str(_)
+                | TokenType::Character(_)
+                | TokenType::True
+                | TokenType::False
+                | TokenType::RParen
+                | TokenType::RBracket
+                | TokenType::RBrace
+                | TokenType::RArray
+        );
+
+        token
+    }
+
+    fn next_token(&mut self) -> Token {
         let character = self.position.next();
 
         self.col += 1;
@@ -32,23 +152,14 @@ impl<'a> Lexer<'a> {
                 let start_col = self.col;
                 self.col += (word.len() - 1) as u32;
 
-                match word.as_str() {
-                    "let" => Token::new(TokenType::Let, self.row, start_col),
-                    "fn" => Token::new(TokenType::Function, self.row, start_col),
-                    "end" => Token::new(TokenType::End, self.row, start_col),
-                    "string" => Token::new(TokenType::StringType, self.row, start_col),
-                    "bool" => Token::new(TokenType::BoolType, self.row, start_col),
-                    "int" => Token::new(TokenType::IntType, self.row, start_col),
-                    "if" => Token::new(TokenType::If, self.row, start_col),
-                    "else" => Token::new(TokenType::Else, self.row, start_col),
-                    "elif" => Token::new(TokenType::Elif, self.row, start_col),
-                    "and" => Token::new(TokenType::And, self.row, start_col),
-                    "or" => Token::new(TokenType::Or, self.row, start_col),
-                    "True" => Token::new(TokenType::True, self.row, start_col),
-                    "False" => Token::new(TokenType::False, self.row, start_col),
-                    "return" => Token::new(TokenType::Return, self.row, start_col),
-                    _ => Token::new(TokenType::Identity(word.clone()), self.row, start_col),
-                }
+                let token_type = KEYWORDS
+                    .iter()
+                    .find(|(keyword, _)| *keyword == word)
+                    .map_or(TokenType::Identity(word), |(_, token_type)| {
+                        token_type.clone()
+                    });
+
+                Token::new(token_type, self.row, start_col)
             } else if c.is_ascii_digit() {
                 let mut int = String::new();
                 int.push(c);
@@ -57,19 +168,17 @@ impl<'a> Lexer<'a> {
 
                 self.col += (int.len() - 1) as u32;
 
-                Token::new(
-                    TokenType::Int(int.parse::<isize>().unwrap()),
-                    self.row,
-                    self.col,
-                )
+                Token::new(Lexer::int(&int), self.row, self.col)
             } else {
                 match c {
                     '=' => self.get_assign_or_equal(),
                     '+' => Token::new(TokenType::Plus, self.row, self.col),
                     '-' => self.minus_or_pipe_or_negative(),
                     '!' => self.get_not_or_not_equal(),
-                    '>' => Token::new(TokenType::GreaterThan, self.row, self.col),
-                    '<' => Token::new(TokenType::LessThan, self.row, self.col),
+                    '>' => self.get_greater_or_greater_equal(),
+                    '<' => self.get_less_or_less_equal(),
+                    '*' => Token::new(TokenType::Multiply, self.row, self.col),
+                    '%' => Token::new(TokenType::Modulo, self.row, self.col),
                     '/' => self.get_slash_or_array(),
                     '(' => Token::new(TokenType::LParen, self.row, self.col),
                     ')' => Token::new(TokenType::RParen, self.row, self.col),
@@ -78,10 +187,13 @@ impl<'a> Lexer<'a> {
                     '{' => Token::new(TokenType::LBrace, self.row, self.col),
                     '}' => Token::new(TokenType::RBrace, self.row, self.col),
                     ':' => Token::new(TokenType::Colon, self.row, self.col),
+                    '.' => self.get_dot_or_range(),
+                    '|' => Token::new(TokenType::Bar, self.row, self.col),
                     ';' => Token::new(TokenType::SemiColon, self.row, self.col),
                     ',' => Token::new(TokenType::Comma, self.row, self.col),
                     '"' => self.get_string(),
                     '^' => Token::new(TokenType::Pin, self.row, self.col),
+                    '#' if self.trivia => self.comment(),
                     '#' => self.token_after_comment(),
                     '\'' => self.get_character(self.col),
                     '\n' => {
@@ -93,6 +205,7 @@ impl<'a> Lexer<'a> {
 
                         Token::new(TokenType::NewLine, row, col)
                     }
+                    '\t' | ' ' if self.trivia => self.whitespace(),
                     '\t' => self.next(),
                     ' ' => self.next(),
                     _ => Token::new(TokenType::Illegal, self.row, self.col),
@@ -108,12 +221,51 @@ impl<'a> Lexer<'a> {
 
         if c_peek.is_some() && c_peek == Some(&'=') {
             self.position.next();
-            return Token::new(TokenType::NotEqual, self.row, self.col);
+            self.col += 1;
+            return Token::new(TokenType::NotEqual, self.row, self.col - 1);
         }
 
         Token::new(TokenType::Not, self.row, self.col)
     }
 
+    fn get_dot_or_range(&mut self) -> Token {
+        if self.position.peek() != Some(&'.') {
+            return Token::new(TokenType::Dot, self.row, self.col);
+        }
+
+        let start_col = self.col;
+        self.position.next();
+        self.col += 1;
+
+        if self.position.peek() == Some(&'=') {
+            self.position.next();
+            self.col += 1;
+            return Token::new(TokenType::RangeInclusive, self.row, start_col);
+        }
+
+        Token::new(TokenType::Range, self.row, start_col)
+    }
+
+    fn get_greater_or_greater_equal(&mut self) -> Token {
+        if self.position.peek() == Some(&'=') {
+            self.position.next();
+            self.col += 1;
+            return Token::new(TokenType::GreaterEqual, self.row, self.col - 1);
+        }
+
+        Token::new(TokenType::GreaterThan, self.row, self.col)
+    }
+
+    fn get_less_or_less_equal(&mut self) -> Token {
+        if self.position.peek() == Some(&'=') {
+            self.position.next();
+            self.col += 1;
+            return Token::new(TokenType::LessEqual, self.row, self.col - 1);
+        }
+
+        Token::new(TokenType::LessThan, self.row, self.col)
+    }
+
     fn get_rest_of_word(&mut self, word: &mut String) {
         let mut c_peek = self.position.peek();
 
@@ -132,7 +284,7 @@ impl<'a> Lexer<'a> {
         }
     }
 
-    fn is_letter(c: char) -> bool {
+    pub fn is_letter(c: char) -> bool {
         if c.is_ascii_alphabetic() || c == '_' || c == '?' {
             return true;
         }
@@ -140,11 +292,20 @@ impl<'a> Lexer<'a> {
         false
     }
 
+    /// An int literal, or `Illegal` when it is too large for an int.
+    fn int(digits: &str) -> TokenType {
+        match digits.parse::<isize>() {
+            Ok(int) => TokenType::Int(int),
+            Err(_) => TokenType::Illegal,
+        }
+    }
+
     fn minus_or_pipe_or_negative(&mut self) -> Token {
         if self.position.peek() == Some(&'>') {
-            self.next();
-            return Token::new(TokenType::Output, self.row, self.col);
-        } else if self.position.peek().is_some() {
+            self.position.next();
+            self.col += 1;
+            return Token::new(TokenType::Output, self.row, self.col - 1);
+        } else if self.position.peek().is_some() && !self.after_operand {
             let next_c = self.position.peek().unwrap();
             if next_c.is_ascii_digit() {
                 let mut negative_number = String::from("-");
@@ -155,11 +316,7 @@ impl<'a> Lexer<'a> {
 
                 self.col += (negative_number.len() - 1) as u32;
 
-                return Token::new(
-                    TokenType::Int(negative_number.parse::<isize>().unwrap()),
-                    self.row,
-                    start_col,
-                );
+                return Token::new(Lexer::int(&negative_number), self.row, start_col);
             }
         }
 
@@ -169,28 +326,40 @@ impl<'a> Lexer<'a> {
     fn get_assign_or_equal(&mut self) -> Token {
         if self.position.peek() == Some(&'=') {
             self.position.next();
-            return Token::new(TokenType::Equal, self.row, self.col);
+            self.col += 1;
+            return Token::new(TokenType::Equal, self.row, self.col - 1);
         }
 
         Token::new(TokenType::Assign, self.row, self.col)
     }
 
     fn get_string(&mut self) -> Token {
+        let (row, col) = (self.row, self.col);
         let mut string = String::from("");
 
-        let mut character = self.position.next();
+        loop {
+            match self.string_char() {
+                Some('"') => break,
+                Some(c) => string.push(c),
+                None => return Token::new(TokenType::Illegal, row, col),
+            }
+        }
 
-        while character.is_some() && character != Some('"') {
-            string.push(character.unwrap());
+        Token::new(TokenType::Str(string), row, col)
+    }
 
-            character = self.position.next();
+    /// Consumes a character of a string literal, which may span lines.
+    fn string_char(&mut self) -> Option<char> {
+        let c = self.position.next()?;
 
-            if character.is_none() {
-                return Token::new(TokenType::Illegal, self.row, self.col);
-            }
+        if c == '\n' {
+            self.row += 1;
+            self.col = 0;
+        } else {
+            self.col += 1;
         }
 
-        Token::new(TokenType::Str(string), self.row, self.col)
+        Some(c)
     }
 
     fn get_character(&mut self, col: u32) -> Token {
@@ -220,7 +389,7 @@ impl<'a> Lexer<'a> {
             return Token::new(TokenType::Character('\''), self.row, col);
         }
 
-        if character.len() > 1 || character.is_empty() {
+        if character.chars().count() != 1 {
             return Token::new(TokenType::Illegal, self.row, col);
         }
 
@@ -236,6 +405,7 @@ impl<'a> Lexer<'a> {
 
         if c_peek.is_some() && c_peek == Some(&'/') {
             self.position.next();
+            self.col += 1;
             return Token::new(TokenType::LArray, self.row, self.col - 1);
         }
 
@@ -247,6 +417,7 @@ impl<'a> Lexer<'a> {
 
         if c_peek.is_some() && c_peek == Some(&']') {
             self.position.next();
+            self.col += 1;
             return Token::new(TokenType::RArray, self.row, self.col - 1);
         }
 
@@ -269,4 +440,26 @@ impl<'a> Lexer<'a> {
             token
         }
     }
+
+    fn comment(&mut self) -> Token {
+        let col = self.col;
+
+        while self.position.peek().is_some_and(|c| *c != '\n') {
+            self.position.next();
+            self.col += 1;
+        }
+
+        Token::new(TokenType::Comment, self.row, col)
+    }
+
+    fn whitespace(&mut self) -> Token {
+        let col = self.col;
+
+        while let Some(' ') | Some('\t') = self.position.peek() {
+            self.position.next();
+            self.col += 1;
+        }
+
+        Token::new(TokenType::Whitespace, self.row, col)
+    }
 }
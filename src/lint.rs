@@ -0,0 +1,219 @@
+//! Style lints. Each lint can be allowed, reported as a warning, or denied,
+//! which reports it as an error.
+
+use crate::ast::{Expression, FunctionClause, Statement};
+use crate::diagnostic::Diagnostic;
+use crate::token::{Token, TokenType};
+use std::str::FromStr;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum Level {
+    Allow,
+    Warn,
+    Deny,
+}
+
+impl FromStr for Level {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "allow" => Ok(Level::Allow),
+            "warn" => Ok(Level::Warn),
+            "deny" => Ok(Level::Deny),
+            _ => Err(format!(
+                "Unknown lint level {}, expected allow, warn or deny",
+                s
+            )),
+        }
+    }
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub struct LintConfig {
+    /// Functions named `name?` must return `bool`, and functions returning
+    /// `bool` must be named `name?`.
+    pub predicate_names: Level,
+}
+
+impl Default for LintConfig {
+    fn default() -> Self {
+        LintConfig {
+            predicate_names: Level::Warn,
+        }
+    }
+}
+
+impl LintConfig {
+    /// Sets the level of the lint called `name`, as in `predicate-names`.
+    pub fn set(&mut self, name: &str, level: Level) -> Result<(), String> {
+        match name {
+            "predicate-names" => self.predicate_names = level,
+            _ => return Err(format!("Unknown lint {}", name)),
+        }
+        Ok(())
+    }
+
+    /// Sets a lint from a `name=level` setting, as in `predicate-names=deny`.
+    pub fn parse_setting(&mut self, setting: &str) -> Result<(), String> {
+        match setting.split_once('=') {
+            Some((name, level)) => self.set(name, level.parse()?),
+            None => Err(format!("Expected lint=level, got {}", setting)),
+        }
+    }
+}
+
+pub fn lint(ast: &[Statement], config: &LintConfig) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+
+    if config.predicate_names != Level::Allow {
+        let mut reported = Vec::new();
+        predicate_names(ast, config.predicate_names, &mut reported, &mut diagnostics);
+    }
+
+    diagnostics
+}
+
+fn report(level: Level, message: String, token: &Token) -> Diagnostic {
+    match level {
+        Level::Deny => Diagnostic::error(message, token),
+        _ => Diagnostic::warning(message, token),
+    }
+}
+
+/// Reports each offending function once, at its first offending clause.
+fn predicate_names(
+    block: &[Statement],
+    level: Level,
+    reported: &mut Vec<String>,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for statement in block {
+        let clause = match statement {
+            Statement::Function(clause) => clause,
+            _ => continue,
+        };
+
+        predicate_names(&clause.body, level, reported, diagnostics);
+
+        let name = match &clause.name.token_type {
+            TokenType::Identity(name) => name,
+            _ => continue,
+        };
+
+        if reported.contains(name) {
+            continue;
+        }
+
+        let message = match (name.ends_with('?'), returns_bool(clause)) {
+            (true, Some(false)) => format!("Predicate {} should return bool", name),
+            (false, Some(true)) => format!(
+                "Function {} returns bool and should be named {}?",
+                name, name
+            ),
+            _ => continue,
+        };
+
+        reported.push(name.clone());
+        diagnostics.push(report(level, message, &clause.name));
+    }
+}
+
+/// Whether a clause returns bool, from its annotation when it has one and
+/// otherwise from the expressions it can return. `None` when unknown.
+fn returns_bool(clause: &FunctionClause) -> Option<bool> {
+    if let Some(return_type) = &clause.return_type {
+        return Some(return_type.token_type == TokenType::BoolType);
+    }
+
+    let mut results = Vec::new();
+    collect_results(&clause.body, &mut results);
+    collect_returns(&clause.body, &mut results);
+
+    let kinds: Vec<Option<bool>> = results.into_iter().map(is_bool).collect();
+
+    if kinds.contains(&Some(false)) {
+        Some(false)
+    } else if !kinds.is_empty() && kinds.iter().all(|kind| *kind == Some(true)) {
+        Some(true)
+    } else {
+        None
+    }
+}
+
+/// The expressions a block evaluates to, looking through trailing `if`s and
+/// `match`es.
+fn collect_results<'a>(block: &'a [Statement], results: &mut Vec<&'a Expression>) {
+    let expression = match block.last() {
+        Some(Statement::Expr(expression)) => expression,
+        _ => return,
+    };
+
+    match expression {
+        Expression::If(if_expr) => {
+            for (_, block) in &if_expr.branches {
+                collect_results(block, results);
+            }
+            if let Some(block) = &if_expr.alternative {
+                collect_results(block, results);
+            }
+        }
+        Expression::Match(match_expr) => {
+            for arm in &match_expr.arms {
+                results.push(&arm.body);
+            }
+        }
+        expression => results.push(expression),
+    }
+}
+
+fn collect_returns<'a>(block: &'a [Statement], results: &mut Vec<&'a Expression>) {
+    for statement in block {
+        match statement {
+            Statement::Return(expression) => results.push(expression),
+            Statement::Expr(Expression::If(if_expr)) => {
+                for (_, block) in &if_expr.branches {
+                    collect_returns(block, results);
+                }
+                if let Some(block) = &if_expr.alternative {
+                    collect_returns(block, results);
+                }
+            }
+            Statement::While(while_statement) => collect_returns(&while_statement.body, results),
+            Statement::For(for_statement) => collect_returns(&for_statement.body, results),
+            _ => {}
+        }
+    }
+}
+
+fn is_bool(expression: &Expression) -> Option<bool> {
+    match expression {
+        Expression::Value(token) => match &token.token_type {
+            TokenType::True | TokenType::False => Some(true),
+            TokenType::Int(_) | TokenType::Str(_) | TokenType::Character(_) => Some(false),
+            _ => None,
+        },
+        Expression::Prefix(prefix) => Some(prefix.operator.token_type == TokenType::Not),
+        Expression::Infix(infix) => match infix.operator.token_type {
+            TokenType::Equal
+            | TokenType::NotEqual
+            | TokenType::GreaterThan
+            | TokenType::LessThan
+            | TokenType::GreaterEqual
+            | TokenType::LessEqual
+            | TokenType::And
+            | TokenType::Or
+            | TokenType::Xor => Some(true),
+            _ => Some(false),
+        },
+        Expression::Call(call) => match call.function.as_ref() {
+            Expression::Value(Token {
+                token_type: TokenType::Identity(name),
+                ..
+            }) if name.ends_with('?') => Some(true),
+            _ => None,
+        },
+        Expression::List(_) | Expression::Array(_) | Expression::Dict(_) => Some(false),
+        _ => None,
+    }
+}
@@ -0,0 +1,160 @@
+//! The runtime representation of lists: a persistent singly linked list.
+//! Lists are never modified. Prepending builds one new node in front of an
+//! existing list and taking the tail returns the list after the first node,
+//! so both are O(1) and every list built this way shares its nodes.
+
+use crate::value::Value;
+use std::fmt;
+use std::iter::FromIterator;
+use std::rc::Rc;
+
+#[derive(Clone, Default)]
+pub struct List {
+    head: Option<Rc<Node>>,
+}
+
+struct Node {
+    value: Value,
+    next: List,
+    /// The length of the list starting at this node.
+    len: usize,
+}
+
+impl List {
+    pub fn new() -> Self {
+        List { head: None }
+    }
+
+    /// A new list with `value` in front of `self`.
+    pub fn prepend(&self, value: Value) -> Self {
+        List {
+            head: Some(Rc::new(Node {
+                value,
+                next: self.clone(),
+                len: self.len() + 1,
+            })),
+        }
+    }
+
+    pub fn head(&self) -> Option<&Value> {
+        self.head.as_ref().map(|node| &node.value)
+    }
+
+    pub fn tail(&self) -> Option<&List> {
+        self.head.as_ref().map(|node| &node.next)
+    }
+
+    /// The list after its first `n` elements, in O(n).
+    pub fn skip(&self, n: usize) -> Option<&List> {
+        let mut list = self;
+        for _ in 0..n {
+            list = list.tail()?;
+        }
+        Some(list)
+    }
+
+    pub fn nth(&self, n: usize) -> Option<&Value> {
+        self.skip(n)?.head()
+    }
+
+    pub fn len(&self) -> usize {
+        self.head.as_ref().map_or(0, |node| node.len)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.head.is_none()
+    }
+
+    pub fn iter(&self) -> Iter<'_> {
+        Iter { list: self }
+    }
+
+    /// Whether both lists are the same nodes, not just equal elements.
+    pub fn ptr_eq(&self, other: &List) -> bool {
+        match (&self.head, &other.head) {
+            (Some(a), Some(b)) => Rc::ptr_eq(a, b),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+}
+
+impl Drop for List {
+    /// Frees the nodes this list owns alone one at a time. The default drop
+    /// recurses once per node and overflows the stack on long lists.
+    fn drop(&mut self) {
+        let mut head = self.head.take();
+
+        while let Some(node) = head {
+            match Rc::try_unwrap(node) {
+                Ok(mut node) => head = node.next.head.take(),
+                Err(_) => break,
+            }
+        }
+    }
+}
+
+impl FromIterator<Value> for List {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Self {
+        let values: Vec<Value> = iter.into_iter().collect();
+
+        values
+            .into_iter()
+            .rev()
+            .fold(List::new(), |list, value| list.prepend(value))
+    }
+}
+
+impl PartialEq for List {
+    fn eq(&self, other: &Self) -> bool {
+        self.ptr_eq(other) || (self.len() == other.len() && self.iter().eq(other.iter()))
+    }
+}
+
+impl fmt::Debug for List {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+pub struct Iter<'a> {
+    list: &'a List,
+}
+
+impl<'a> Iterator for Iter<'a> {
+    type Item = &'a Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.list.head.as_ref()?;
+        self.list = &node.next;
+        Some(&node.value)
+    }
+}
+
+/// Iterates a list by value, holding on to the rest of the list rather than
+/// borrowing it.
+pub struct IntoIter {
+    list: List,
+}
+
+impl Iterator for IntoIter {
+    type Item = Value;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (value, next) = {
+            let node = self.list.head.as_ref()?;
+            (node.value.clone(), node.next.clone())
+        };
+        self.list = next;
+        Some(value)
+    }
+}
+
+impl IntoIterator for List {
+    type Item = Value;
+    type IntoIter = IntoIter;
+
+    fn into_iter(self) -> IntoIter {
+        IntoIter { list: self }
+    }
+}
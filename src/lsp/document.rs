@@ -0,0 +1,591 @@
+//! What the server knows about one open document. Its tokens and statements
+//! are updated around each change, and the rest is worked out again from
+//! them.
+//!
+//! Positions here are the 1-based rows and columns of tokens. The server
+//! converts them to the 0-based lines and UTF-16 characters of the protocol.
+
+use super::scope::resolve;
+
+use crate::analysis::analyze;
+use crate::ast::{Ast, Expression, FunctionClause, Pattern, Statement};
+use crate::builtins::{Builtin, GLOBALS, MODULES};
+use crate::diagnostic::Diagnostic;
+use crate::incremental::Program;
+use crate::lexer::{Lexer, KEYWORDS};
+use crate::lint::{lint, LintConfig};
+use crate::source::FileId;
+use crate::token::{Token, TokenType};
+use crate::typecheck::{type_check, Types};
+use std::ops::Range;
+use std::path::PathBuf;
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum DefinitionKind {
+    Function,
+    Variable,
+}
+
+/// A function clause, or a variable bound by `let`, `for`, a parameter or a
+/// match arm.
+#[derive(Clone, Debug)]
+pub struct Definition {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub row: u32,
+    pub col: u32,
+    pub file: FileId,
+    /// The header of a function clause, or the type of a variable when the
+    /// type check knows it.
+    pub detail: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Symbol {
+    pub name: String,
+    pub kind: DefinitionKind,
+    pub detail: Option<String>,
+    pub row: u32,
+    pub col: u32,
+    /// Functions defined inside a function and its variables.
+    pub children: Vec<Symbol>,
+}
+
+#[derive(PartialEq, Clone, Copy, Debug)]
+pub enum CompletionKind {
+    Keyword,
+    Function,
+    Variable,
+    Module,
+}
+
+pub struct Document {
+    program: Program,
+    ast: Ast,
+    pub diagnostics: Vec<Diagnostic>,
+    definitions: Vec<Definition>,
+    types: Types,
+    lints: LintConfig,
+}
+
+impl Document {
+    pub fn new(text: String, file: FileId, lints: LintConfig) -> Self {
+        Document::analyze(Program::in_file(text, file), lints)
+    }
+
+    /// Replaces the bytes in `range` with `replacement`, lexing and parsing
+    /// again only around the change.
+    pub fn edit(self, range: Range<usize>, replacement: &str) -> Self {
+        let mut program = self.program;
+        program.edit(range, replacement);
+
+        Document::analyze(program, self.lints)
+    }
+
+    fn analyze(program: Program, lints: LintConfig) -> Self {
+        let ast = program.ast();
+        let file = program.file();
+
+        let mut diagnostics: Vec<Diagnostic> = program
+            .errors()
+            .iter()
+            .map(|error| Diagnostic::from_error(error, file))
+            .collect();
+        let mut types = type_check(&ast);
+        diagnostics.append(&mut types.diagnostics);
+        diagnostics.extend(analyze(&ast));
+        diagnostics.extend(lint(&ast, &lints));
+
+        let mut document = Document {
+            program,
+            ast,
+            diagnostics,
+            definitions: Vec::new(),
+            types,
+            lints,
+        };
+        let mut definitions = Vec::new();
+        document.block_definitions(&document.ast, &mut definitions);
+        document.definitions = definitions;
+
+        document
+    }
+
+    pub fn text(&self) -> &str {
+        self.program.text()
+    }
+
+    pub fn file(&self) -> FileId {
+        self.program.file()
+    }
+
+    pub fn lints(&self) -> LintConfig {
+        self.lints
+    }
+
+    /// The byte offset of `row`.`col`, where `col` may be just past the end
+    /// of the line. Positions past the end of the text give its length.
+    pub fn offset(&self, row: u32, col: u32) -> usize {
+        let text = self.text();
+        let line_start = text
+            .split_inclusive('\n')
+            .take(row as usize - 1)
+            .map(str::len)
+            .sum::<usize>();
+        let line = self.line(row);
+        let within = line
+            .char_indices()
+            .nth(col as usize - 1)
+            .map_or(line.len(), |(offset, _)| offset);
+
+        (line_start + within).min(text.len())
+    }
+
+    /// The line at `row`, without its line break.
+    pub fn line(&self, row: u32) -> &str {
+        self.text().lines().nth(row as usize - 1).unwrap_or("")
+    }
+
+    /// How many characters the token starting at `row`.`col` spans: a whole
+    /// word for names and keywords and a single character otherwise.
+    pub fn width_at(&self, row: u32, col: u32) -> u32 {
+        let rest = self.line(row).chars().skip(col as usize - 1);
+        let word = rest.take_while(|c| Lexer::is_letter(*c)).count();
+
+        word.max(1) as u32
+    }
+
+    /// The index of the name touching `row`.`col`, including a cursor just
+    /// after its last character.
+    fn name_at(&self, row: u32, col: u32) -> Option<usize> {
+        self.program
+            .tokens()
+            .iter()
+            .position(|token| match &token.token_type {
+                TokenType::Identity(name) => {
+                    token.row == row
+                        && token.col <= col
+                        && col <= token.col + name.chars().count() as u32
+                }
+                _ => false,
+            })
+    }
+
+    fn name(&self, index: usize) -> &str {
+        match &self.program.tokens()[index].token_type {
+            TokenType::Identity(name) => name,
+            _ => "",
+        }
+    }
+
+    /// The module a name is accessed from, as in `List.map`.
+    fn module_of(&self, index: usize) -> Option<&str> {
+        if index < 2 || self.program.tokens()[index - 1].token_type != TokenType::Dot {
+            return None;
+        }
+
+        match &self.program.tokens()[index - 2].token_type {
+            TokenType::Identity(module) => Some(module),
+            _ => None,
+        }
+    }
+
+    /// Where the name at `row`.`col` is defined, found through the scopes
+    /// around it. A function is defined by all of its clauses. A name that
+    /// no scope binds before it is used, such as a global a function reads
+    /// before it is defined, goes to the first definition of the name.
+    pub fn definition(&self, row: u32, col: u32) -> Vec<&Definition> {
+        let index = match self.name_at(row, col) {
+            Some(index) if self.module_of(index).is_none() => index,
+            _ => return Vec::new(),
+        };
+        let name = self.name(index);
+        let token = &self.program.tokens()[index];
+
+        let bindings = resolve(&self.ast, token.row, token.col);
+        if !bindings.is_empty() {
+            return self
+                .definitions
+                .iter()
+                .filter(|d| d.name == name && bindings.contains(&(d.row, d.col)))
+                .collect();
+        }
+
+        let functions: Vec<&Definition> = self
+            .definitions
+            .iter()
+            .filter(|d| d.kind == DefinitionKind::Function && d.name == name)
+            .collect();
+        if !functions.is_empty() {
+            return functions;
+        }
+
+        self.definitions
+            .iter()
+            .filter(|d| d.kind == DefinitionKind::Variable && d.name == name)
+            .min_by_key(|d| (d.row, d.col))
+            .into_iter()
+            .collect()
+    }
+
+    /// The path of the file imported under the module name a member at
+    /// `row`.`col` is accessed from, as in `Helpers.double`, and the
+    /// member's name. The path is relative to the document.
+    pub fn imported(&self, row: u32, col: u32) -> Option<(PathBuf, String)> {
+        let index = self.name_at(row, col)?;
+        let module = self.module_of(index)?;
+
+        self.ast.iter().find_map(|statement| match statement {
+            Statement::Import(import) if import.name() == module => {
+                Some((import.path(), self.name(index).to_string()))
+            }
+            _ => None,
+        })
+    }
+
+    /// Where the document defines `name` for other files to import: its
+    /// top-level `pub` function clauses and variables.
+    pub fn exports(&self, name: &str) -> Vec<Definition> {
+        let mut definitions = Vec::new();
+
+        for statement in &self.ast {
+            match statement {
+                Statement::Function(clause) if clause.public => {
+                    definitions.push(self.clause_definition(clause))
+                }
+                Statement::Let(ls) if ls.public => self.bind(&ls.pattern, &mut definitions),
+                _ => {}
+            }
+        }
+
+        definitions.retain(|d| d.name == name);
+        definitions
+    }
+
+    /// A description of the name at `row`.`col` and the token it starts at.
+    pub fn hover(&self, row: u32, col: u32) -> Option<(Vec<String>, &Token)> {
+        let index = self.name_at(row, col)?;
+        let name = self.name(index);
+        let token = &self.program.tokens()[index];
+
+        if let Some(module) = self.module_of(index) {
+            let module = MODULES.iter().find(|m| m.name == module)?;
+            return Some((vec![describe_builtin(module.get(name)?)], token));
+        }
+
+        if let Some(module) = MODULES.iter().find(|m| m.name == name) {
+            return Some((vec![format!("module {}", module.name)], token));
+        }
+
+        let definitions = self.definition(row, col);
+        if definitions.is_empty() {
+            let builtin = GLOBALS
+                .iter()
+                .flat_map(|globals| globals.iter())
+                .find(|builtin| builtin.name == name)?;
+            return Some((vec![describe_builtin(builtin)], token));
+        }
+
+        let lines = definitions
+            .iter()
+            .map(|d| match (&d.kind, &d.detail) {
+                (DefinitionKind::Function, Some(header)) => header.clone(),
+                (DefinitionKind::Variable, Some(kind)) => format!("{}: {}", d.name, kind),
+                _ => d.name.clone(),
+            })
+            .collect();
+
+        Some((lines, token))
+    }
+
+    /// The words that could complete the one ending at `row`.`col`, sorted.
+    pub fn completions(&self, row: u32, col: u32) -> Vec<(String, CompletionKind)> {
+        let before: String = self.line(row).chars().take(col as usize - 1).collect();
+        let word: String = before
+            .chars()
+            .rev()
+            .take_while(|c| Lexer::is_letter(*c) || *c == '.')
+            .collect::<Vec<char>>()
+            .into_iter()
+            .rev()
+            .collect();
+
+        let mut candidates: Vec<(String, CompletionKind)> = match word.rsplit_once('.') {
+            Some((module, prefix)) => MODULES
+                .iter()
+                .filter(|m| m.name == module)
+                .flat_map(|m| m.functions.iter())
+                .filter(|builtin| builtin.name.starts_with(prefix))
+                .map(|builtin| (builtin.name.to_string(), CompletionKind::Function))
+                .collect(),
+            None => {
+                let keywords = KEYWORDS
+                    .iter()
+                    .map(|(keyword, _)| (keyword.to_string(), CompletionKind::Keyword));
+                let modules = MODULES
+                    .iter()
+                    .map(|module| (module.name.to_string(), CompletionKind::Module));
+                let globals = GLOBALS
+                    .iter()
+                    .flat_map(|globals| globals.iter())
+                    .map(|builtin| (builtin.name.to_string(), CompletionKind::Function));
+                let definitions = self.definitions.iter().map(|d| {
+                    let kind = match d.kind {
+                        DefinitionKind::Function => CompletionKind::Function,
+                        DefinitionKind::Variable => CompletionKind::Variable,
+                    };
+                    (d.name.clone(), kind)
+                });
+
+                keywords
+                    .chain(modules)
+                    .chain(globals)
+                    .chain(definitions)
+                    .filter(|(name, _)| name.starts_with(&word))
+                    .collect()
+            }
+        };
+
+        candidates.sort_by(|a, b| a.0.cmp(&b.0));
+        candidates.dedup_by(|a, b| a.0 == b.0);
+        candidates
+    }
+
+    /// The functions and variables of the document, nested inside the
+    /// functions that define them.
+    pub fn symbols(&self) -> Vec<Symbol> {
+        self.block_symbols(&self.ast)
+    }
+
+    fn block_symbols(&self, block: &[Statement]) -> Vec<Symbol> {
+        let mut symbols = Vec::new();
+
+        for statement in block {
+            match statement {
+                Statement::Function(clause) => symbols.push(Symbol {
+                    name: self.name_of(&clause.name),
+                    kind: DefinitionKind::Function,
+                    detail: Some(self.header(clause)),
+                    row: clause.name.row,
+                    col: clause.name.col,
+                    children: self.block_symbols(&clause.body),
+                }),
+                Statement::Let(ls) => {
+                    let mut definitions = Vec::new();
+                    self.bind(&ls.pattern, &mut definitions);
+
+                    symbols.extend(definitions.into_iter().map(|d| Symbol {
+                        name: d.name,
+                        kind: d.kind,
+                        detail: d.detail,
+                        row: d.row,
+                        col: d.col,
+                        children: Vec::new(),
+                    }));
+                }
+                Statement::While(while_statement) => {
+                    symbols.extend(self.block_symbols(&while_statement.body))
+                }
+                Statement::For(for_statement) => {
+                    symbols.extend(self.block_symbols(&for_statement.body))
+                }
+                _ => {}
+            }
+        }
+
+        symbols
+    }
+
+    fn name_of(&self, token: &Token) -> String {
+        match &token.token_type {
+            TokenType::Identity(name) => name.clone(),
+            _ => String::from("fn"),
+        }
+    }
+
+    /// The source of a clause up to the `:` that starts its body, such as
+    /// `fn add(a: int, b: int) -> int`.
+    fn header(&self, clause: &FunctionClause) -> String {
+        let line = self.line(clause.name.row).trim();
+        let mut lexer = Lexer::new(line);
+        let mut depth = 0;
+
+        loop {
+            let start = lexer.offset();
+            match lexer.next().token_type {
+                TokenType::LParen | TokenType::LBracket | TokenType::LBrace => depth += 1,
+                TokenType::RParen | TokenType::RBracket | TokenType::RBrace => depth -= 1,
+                TokenType::Colon if depth == 0 => return line[..start].trim_end().to_string(),
+                TokenType::EOF => return line.to_string(),
+                _ => {}
+            }
+        }
+    }
+
+    fn block_definitions(&self, block: &[Statement], out: &mut Vec<Definition>) {
+        for statement in block {
+            match statement {
+                Statement::Let(ls) => {
+                    self.expression_definitions(&ls.expression, out);
+                    self.bind(&ls.pattern, out);
+                }
+                Statement::Assign(assign) => self.expression_definitions(&assign.expression, out),
+                Statement::IndexAssign(assign) => {
+                    self.expression_definitions(&assign.target.left, out);
+                    self.expression_definitions(&assign.target.index, out);
+                    self.expression_definitions(&assign.expression, out);
+                }
+                Statement::Function(clause) => {
+                    out.push(self.clause_definition(clause));
+                    self.clause_definitions(clause, out);
+                }
+                Statement::While(while_statement) => {
+                    self.expression_definitions(&while_statement.condition, out);
+                    self.block_definitions(&while_statement.body, out);
+                }
+                Statement::For(for_statement) => {
+                    self.expression_definitions(&for_statement.iterable, out);
+                    self.bind(&for_statement.pattern, out);
+                    self.block_definitions(&for_statement.body, out);
+                }
+                Statement::Return(expression) | Statement::Expr(expression) => {
+                    self.expression_definitions(expression, out)
+                }
+                Statement::Import(import) => out.push(Definition {
+                    name: import.name(),
+                    kind: DefinitionKind::Variable,
+                    row: import.module.row,
+                    col: import.module.col,
+                    file: import.module.file,
+                    detail: Some(String::from("module")),
+                }),
+                Statement::Break(_) | Statement::Continue(_) | Statement::End => {}
+            }
+        }
+    }
+
+    fn clause_definition(&self, clause: &FunctionClause) -> Definition {
+        Definition {
+            name: self.name_of(&clause.name),
+            kind: DefinitionKind::Function,
+            row: clause.name.row,
+            col: clause.name.col,
+            file: clause.name.file,
+            detail: Some(self.header(clause)),
+        }
+    }
+
+    fn clause_definitions(&self, clause: &FunctionClause, out: &mut Vec<Definition>) {
+        for parameter in &clause.parameters {
+            self.bind(parameter, out);
+        }
+        if let Some(guard) = &clause.guard {
+            self.expression_definitions(guard, out);
+        }
+        self.block_definitions(&clause.body, out);
+    }
+
+    fn expression_definitions(&self, expression: &Expression, out: &mut Vec<Definition>) {
+        match expression {
+            Expression::Value(_) => {}
+            Expression::Prefix(prefix) => self.expression_definitions(&prefix.right, out),
+            Expression::Infix(infix) => {
+                self.expression_definitions(&infix.left, out);
+                self.expression_definitions(&infix.right, out);
+            }
+            Expression::Call(call) => {
+                self.expression_definitions(&call.function, out);
+                for argument in &call.arguments {
+                    self.expression_definitions(argument, out);
+                }
+            }
+            Expression::If(if_expr) => {
+                for (condition, block) in &if_expr.branches {
+                    self.expression_definitions(condition, out);
+                    self.block_definitions(block, out);
+                }
+                if let Some(block) = &if_expr.alternative {
+                    self.block_definitions(block, out);
+                }
+            }
+            Expression::Match(match_expr) => {
+                self.expression_definitions(&match_expr.subject, out);
+                for arm in &match_expr.arms {
+                    self.bind(&arm.pattern, out);
+                    if let Some(guard) = &arm.guard {
+                        self.expression_definitions(guard, out);
+                    }
+                    self.expression_definitions(&arm.body, out);
+                }
+            }
+            Expression::List(list) => {
+                for element in &list.elements {
+                    self.expression_definitions(element, out);
+                }
+                if let Some(tail) = &list.tail {
+                    self.expression_definitions(tail, out);
+                }
+            }
+            Expression::Array(array) => {
+                for element in &array.elements {
+                    self.expression_definitions(element, out);
+                }
+            }
+            Expression::Dict(dict) => {
+                for (key, value) in &dict.entries {
+                    self.expression_definitions(key, out);
+                    self.expression_definitions(value, out);
+                }
+            }
+            Expression::Index(index) => {
+                self.expression_definitions(&index.left, out);
+                self.expression_definitions(&index.index, out);
+            }
+            Expression::Access(access) => self.expression_definitions(&access.left, out),
+            Expression::Function(clause) => self.clause_definitions(clause, out),
+        }
+    }
+
+    /// Adds the variables a pattern binds, with the type the type check
+    /// found for each, if it is known.
+    fn bind(&self, pattern: &Pattern, out: &mut Vec<Definition>) {
+        match pattern {
+            Pattern::Binding(token, _) => {
+                let detail = self
+                    .types
+                    .binding(token.row, token.col)
+                    .map(|kind| kind.to_string());
+
+                out.push(Definition {
+                    name: self.name_of(token),
+                    kind: DefinitionKind::Variable,
+                    row: token.row,
+                    col: token.col,
+                    file: token.file,
+                    detail,
+                });
+            }
+            Pattern::List(list) => {
+                for element in &list.elements {
+                    self.bind(element, out);
+                }
+                if let Some(tail) = &list.tail {
+                    self.bind(tail, out);
+                }
+            }
+            Pattern::Dict(dict) => {
+                for (_, pattern) in &dict.entries {
+                    self.bind(pattern, out);
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Literal(_) | Pattern::Pin(_) => {}
+        }
+    }
+}
+
+fn describe_builtin(builtin: &Builtin) -> String {
+    match builtin.arity {
+        1 => format!("{}, a builtin taking 1 argument", builtin),
+        arity => format!("{}, a builtin taking {} arguments", builtin, arity),
+    }
+}
@@ -0,0 +1,585 @@
+//! A Language Server Protocol server, run by `slice lsp`, that talks JSON-RPC
+//! over stdin and stdout. It offers diagnostics, hover, go to definition,
+//! document symbols and completion. Documents are synced by the ranges that
+//! change, and only the statements around a change are parsed again. Every
+//! document, and every file its imports are followed to, is kept in a
+//! `SourceMap` under its URI, which locations are given in.
+//!
+//! Lint levels are read from the `lint` object of the `initializationOptions`
+//! the client starts the server with, as in `{"predicate-names": "deny"}`.
+//!
+//! The protocol counts characters in UTF-16 code units, while documents
+//! count them in chars, so positions are converted on the way in and out.
+
+mod document;
+mod scope;
+mod transport;
+
+use document::{CompletionKind, Definition, DefinitionKind, Document, Symbol};
+use transport::{read_message, write_message};
+
+use crate::diagnostic::Severity;
+use crate::dict::{Dict, Key};
+use crate::json;
+use crate::lint::LintConfig;
+use crate::source::SourceMap;
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fs;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+const PARSE_ERROR: isize = -32700;
+const INVALID_REQUEST: isize = -32600;
+const METHOD_NOT_FOUND: isize = -32601;
+const INVALID_PARAMS: isize = -32602;
+const SERVER_NOT_INITIALIZED: isize = -32002;
+
+/// Serves messages from `input` until the client sends `exit`, returning
+/// the exit code: 0 when `shutdown` was requested first, 1 otherwise.
+pub fn serve(input: &mut impl BufRead, output: &mut impl Write) -> Result<i32, String> {
+    let mut server = Server::default();
+
+    while let Some(body) = read_message(input)? {
+        for message in server.handle(&body) {
+            write_message(output, &message).map_err(|e| e.to_string())?;
+        }
+
+        if server.exited {
+            return Ok(if server.shut_down { 0 } else { 1 });
+        }
+    }
+
+    Ok(1)
+}
+
+#[derive(Default)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    sources: SourceMap,
+    lints: LintConfig,
+    initialized: bool,
+    shut_down: bool,
+    exited: bool,
+}
+
+/// An error response's code and message.
+type Failure = (isize, String);
+
+impl Server {
+    /// Handles one message, returning the messages to send back: the
+    /// response to a request, and diagnostics for changed documents.
+    pub fn handle(&mut self, body: &str) -> Vec<Value> {
+        let message = match json::decode(body) {
+            Ok(message) => message,
+            Err(error) => return vec![failure(Value::Unit, (PARSE_ERROR, error.to_string()))],
+        };
+
+        let method = match get(&message, &["method"]) {
+            Some(Value::Str(method)) => method.as_str(),
+            // A response to a request of ours, and we send none.
+            _ if get(&message, &["id"]).is_none() => return Vec::new(),
+            _ => {
+                let id = get(&message, &["id"]).cloned().unwrap_or(Value::Unit);
+                return vec![failure(
+                    id,
+                    (INVALID_REQUEST, String::from("Missing method")),
+                )];
+            }
+        };
+        let params = get(&message, &["params"]).cloned().unwrap_or(Value::Unit);
+
+        match get(&message, &["id"]) {
+            Some(id) => {
+                let response = match self.request(method, &params) {
+                    Ok(result) => object(vec![
+                        ("jsonrpc", string("2.0")),
+                        ("id", id.clone()),
+                        ("result", result),
+                    ]),
+                    Err(error) => failure(id.clone(), error),
+                };
+                vec![response]
+            }
+            None => self.notification(method, &params),
+        }
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, Failure> {
+        if !self.initialized && method != "initialize" {
+            return Err((
+                SERVER_NOT_INITIALIZED,
+                String::from("The server is not initialized"),
+            ));
+        }
+        if self.shut_down {
+            return Err((INVALID_REQUEST, String::from("The server is shutting down")));
+        }
+
+        match method {
+            "initialize" => {
+                if let Some(Value::Dict(settings)) = get(params, &["initializationOptions", "lint"])
+                {
+                    for (name, level) in settings.entries() {
+                        let set = match (name, level) {
+                            (Key::Str(name), Value::Str(level)) => {
+                                level.parse().and_then(|level| self.lints.set(name, level))
+                            }
+                            _ => Err(format!("Expected a lint level, got {}", level)),
+                        };
+                        set.map_err(|error| (INVALID_PARAMS, error))?;
+                    }
+                }
+                self.initialized = true;
+                Ok(capabilities())
+            }
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(Value::Unit)
+            }
+            "textDocument/hover" => {
+                let (_, document, row, col) = self.position(params)?;
+                Ok(hover(document, row, col).unwrap_or(Value::Unit))
+            }
+            "textDocument/definition" => {
+                let (uri, document, row, col) = self.position(params)?;
+                let definitions = match document.imported(row, col) {
+                    Some((path, member)) => self.exports(uri, &path, &member),
+                    None => document.definition(row, col).into_iter().cloned().collect(),
+                };
+
+                let locations: Vec<Value> = definitions
+                    .iter()
+                    .map(|d| {
+                        let line = self.sources.line(d.file, d.row).unwrap_or("");
+                        object(vec![
+                            ("uri", string(self.sources.name(d.file).unwrap_or(uri))),
+                            ("range", name_range(line, d.row, d.col, &d.name)),
+                        ])
+                    })
+                    .collect();
+
+                Ok(list(locations))
+            }
+            "textDocument/documentSymbol" => {
+                let (_, document) = self.document(params)?;
+                let symbols = document.symbols();
+                Ok(list(symbols.iter().map(|s| symbol(document, s)).collect()))
+            }
+            "textDocument/completion" => {
+                let (_, document, row, col) = self.position(params)?;
+                let items = document
+                    .completions(row, col)
+                    .into_iter()
+                    .map(|(label, kind)| {
+                        let kind = match kind {
+                            CompletionKind::Function => 3,
+                            CompletionKind::Variable => 6,
+                            CompletionKind::Module => 9,
+                            CompletionKind::Keyword => 14,
+                        };
+                        object(vec![("label", string(&label)), ("kind", int(kind))])
+                    })
+                    .collect();
+
+                Ok(list(items))
+            }
+            _ => Err((METHOD_NOT_FOUND, format!("Unknown method {}", method))),
+        }
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        match method {
+            "exit" => {
+                self.exited = true;
+                Vec::new()
+            }
+            "textDocument/didOpen" => {
+                let uri = get_str(params, &["textDocument", "uri"]);
+                let text = get_str(params, &["textDocument", "text"]);
+
+                match (uri, text) {
+                    (Some(uri), Some(text)) => self.open(uri, text),
+                    _ => Vec::new(),
+                }
+            }
+            "textDocument/didChange" => {
+                let uri = match get_str(params, &["textDocument", "uri"]) {
+                    Some(uri) => uri,
+                    None => return Vec::new(),
+                };
+                let changes = match get(params, &["contentChanges"]) {
+                    Some(Value::List(changes)) => changes,
+                    _ => return Vec::new(),
+                };
+
+                let mut document = match self.documents.remove(uri) {
+                    Some(document) => document,
+                    None => return Vec::new(),
+                };
+                for change in changes.iter() {
+                    document = apply(document, change);
+                }
+                self.sources.set(uri, document.text().to_string());
+
+                let published = diagnostics(uri, Some(&document));
+                self.documents.insert(uri.to_string(), document);
+                vec![published]
+            }
+            "textDocument/didClose" => match get_str(params, &["textDocument", "uri"]) {
+                Some(uri) => {
+                    self.documents.remove(uri);
+                    vec![diagnostics(uri, None)]
+                }
+                None => Vec::new(),
+            },
+            _ => Vec::new(),
+        }
+    }
+
+    fn open(&mut self, uri: &str, text: &str) -> Vec<Value> {
+        let file = self.sources.set(uri, text.to_string());
+        let document = Document::new(text.to_string(), file, self.lints);
+        let published = diagnostics(uri, Some(&document));
+
+        self.documents.insert(uri.to_string(), document);
+        vec![published]
+    }
+
+    /// The definitions of `name` exported by the file at `path`, relative
+    /// to the document at `uri`. The file is read from disk unless it is
+    /// open.
+    fn exports(&mut self, uri: &str, path: &Path, name: &str) -> Vec<Definition> {
+        let file = match uri_path(uri) {
+            Some(file) => file,
+            None => return Vec::new(),
+        };
+        let path = file.parent().unwrap_or(&file).join(path);
+        let module = path_uri(&path);
+
+        // The client may encode the URI of an open document differently.
+        let open = self
+            .documents
+            .iter()
+            .find(|(uri, _)| uri_path(uri).as_ref() == Some(&path));
+        if let Some((_, document)) = open {
+            return document.exports(name);
+        }
+
+        match fs::read_to_string(&path) {
+            Ok(text) => {
+                let file = self.sources.set(&module, text.clone());
+                Document::new(text, file, self.lints).exports(name)
+            }
+            Err(_) => Vec::new(),
+        }
+    }
+
+    fn document<'a>(&self, params: &'a Value) -> Result<(&'a str, &Document), Failure> {
+        let uri = get_str(params, &["textDocument", "uri"])
+            .ok_or_else(|| (INVALID_PARAMS, String::from("Missing textDocument.uri")))?;
+        let document = self
+            .documents
+            .get(uri)
+            .ok_or_else(|| (INVALID_PARAMS, format!("Unknown document {}", uri)))?;
+
+        Ok((uri, document))
+    }
+
+    /// The document and the 1-based row and column of a request's position.
+    fn position<'a>(&self, params: &'a Value) -> Result<(&'a str, &Document, u32, u32), Failure> {
+        let (uri, document) = self.document(params)?;
+
+        match (
+            get(params, &["position", "line"]),
+            get(params, &["position", "character"]),
+        ) {
+            (Some(Value::Int(line)), Some(Value::Int(character)))
+                if *line >= 0 && *character >= 0 =>
+            {
+                let row = *line as u32 + 1;
+                let col = column(document.line(row), *character as usize);
+                Ok((uri, document, row, col))
+            }
+            _ => Err((INVALID_PARAMS, String::from("Missing position"))),
+        }
+    }
+}
+
+fn capabilities() -> Value {
+    object(vec![
+        (
+            "capabilities",
+            object(vec![
+                ("textDocumentSync", int(2)),
+                ("hoverProvider", Value::Bool(true)),
+                ("definitionProvider", Value::Bool(true)),
+                ("documentSymbolProvider", Value::Bool(true)),
+                (
+                    "completionProvider",
+                    object(vec![("triggerCharacters", list(vec![string(".")]))]),
+                ),
+            ]),
+        ),
+        (
+            "serverInfo",
+            object(vec![
+                ("name", string("slice")),
+                ("version", string(env!("CARGO_PKG_VERSION"))),
+            ]),
+        ),
+    ])
+}
+
+/// Applies one of the `contentChanges` of `textDocument/didChange`, which
+/// replaces either a range or, without one, the whole text.
+fn apply(document: Document, change: &Value) -> Document {
+    let text = match get_str(change, &["text"]) {
+        Some(text) => text,
+        None => return document,
+    };
+
+    let offset = |end: &str| match (
+        get(change, &["range", end, "line"]),
+        get(change, &["range", end, "character"]),
+    ) {
+        (Some(Value::Int(line)), Some(Value::Int(character))) if *line >= 0 && *character >= 0 => {
+            let row = *line as u32 + 1;
+            Some(document.offset(row, column(document.line(row), *character as usize)))
+        }
+        _ => None,
+    };
+
+    if get(change, &["range"]).is_none() {
+        return Document::new(text.to_string(), document.file(), document.lints());
+    }
+
+    match (offset("start"), offset("end")) {
+        (Some(start), Some(end)) if start <= end => document.edit(start..end, text),
+        _ => document,
+    }
+}
+
+/// The `textDocument/publishDiagnostics` notification for a document, with
+/// no diagnostics for a closed one.
+fn diagnostics(uri: &str, document: Option<&Document>) -> Value {
+    let diagnostics = document.map_or(Vec::new(), |document| {
+        document
+            .diagnostics
+            .iter()
+            .map(|diagnostic| {
+                let width = document.width_at(diagnostic.row, diagnostic.col);
+                let severity = match diagnostic.severity {
+                    Severity::Error => 1,
+                    Severity::Warning => 2,
+                };
+
+                let line = document.line(diagnostic.row);
+
+                object(vec![
+                    ("range", range(line, diagnostic.row, diagnostic.col, width)),
+                    ("severity", int(severity)),
+                    ("source", string("slice")),
+                    ("message", string(&diagnostic.message)),
+                ])
+            })
+            .collect()
+    });
+
+    object(vec![
+        ("jsonrpc", string("2.0")),
+        ("method", string("textDocument/publishDiagnostics")),
+        (
+            "params",
+            object(vec![
+                ("uri", string(uri)),
+                ("diagnostics", list(diagnostics)),
+            ]),
+        ),
+    ])
+}
+
+fn hover(document: &Document, row: u32, col: u32) -> Option<Value> {
+    let (lines, token) = document.hover(row, col)?;
+    let name = match &token.token_type {
+        crate::token::TokenType::Identity(name) => name,
+        _ => return None,
+    };
+
+    Some(object(vec![
+        (
+            "contents",
+            object(vec![
+                ("kind", string("markdown")),
+                (
+                    "value",
+                    string(&format!("```slice\n{}\n```", lines.join("\n"))),
+                ),
+            ]),
+        ),
+        (
+            "range",
+            name_range(document.line(token.row), token.row, token.col, name),
+        ),
+    ]))
+}
+
+fn symbol(document: &Document, symbol: &Symbol) -> Value {
+    let kind = match symbol.kind {
+        DefinitionKind::Function => 12,
+        DefinitionKind::Variable => 13,
+    };
+    let line = document.line(symbol.row);
+    let range = name_range(line, symbol.row, symbol.col, &symbol.name);
+
+    let mut entries = vec![("name", string(&symbol.name)), ("kind", int(kind))];
+    if let Some(detail) = &symbol.detail {
+        entries.push(("detail", string(detail)));
+    }
+    entries.push(("range", range.clone()));
+    entries.push(("selectionRange", range));
+    entries.push((
+        "children",
+        list(
+            symbol
+                .children
+                .iter()
+                .map(|child| self::symbol(document, child))
+                .collect(),
+        ),
+    ));
+
+    object(entries)
+}
+
+/// The range of `width` chars from `row`.`col`, in the UTF-16 code units
+/// of `line`, the text of that row.
+fn range(line: &str, row: u32, col: u32, width: u32) -> Value {
+    let position = |col: u32| {
+        object(vec![
+            ("line", int(row as isize - 1)),
+            ("character", int(character(line, col) as isize)),
+        ])
+    };
+
+    object(vec![
+        ("start", position(col)),
+        ("end", position(col + width)),
+    ])
+}
+
+fn name_range(line: &str, row: u32, col: u32, name: &str) -> Value {
+    range(line, row, col, name.chars().count() as u32)
+}
+
+/// The 1-based column of the char `character` UTF-16 code units into
+/// `line`. Past the end of the line each unit counts as a column.
+fn column(line: &str, character: usize) -> u32 {
+    let mut units = 0;
+    for (index, c) in line.chars().enumerate() {
+        if units >= character {
+            return index as u32 + 1;
+        }
+        units += c.len_utf16();
+    }
+
+    (line.chars().count() + character.saturating_sub(units)) as u32 + 1
+}
+
+/// How many UTF-16 code units of `line` come before the 1-based column
+/// `col`.
+fn character(line: &str, col: u32) -> usize {
+    let index = col as usize - 1;
+    let within: usize = line.chars().take(index).map(char::len_utf16).sum();
+
+    within + index.saturating_sub(line.chars().count())
+}
+
+/// The path of a `file://` URI, with its percent-encoded bytes decoded.
+fn uri_path(uri: &str) -> Option<PathBuf> {
+    let rest = uri.strip_prefix("file://")?;
+    // Skip a host, as in `file://localhost/path`.
+    let encoded = &rest[rest.find('/')?..];
+
+    let mut bytes = Vec::with_capacity(encoded.len());
+    let mut iter = encoded.bytes();
+    while let Some(byte) = iter.next() {
+        if byte != b'%' {
+            bytes.push(byte);
+            continue;
+        }
+
+        let hex = [iter.next()?, iter.next()?];
+        if !hex.iter().all(u8::is_ascii_hexdigit) {
+            return None;
+        }
+        bytes.push(u8::from_str_radix(std::str::from_utf8(&hex).ok()?, 16).ok()?);
+    }
+
+    String::from_utf8(bytes).ok().map(PathBuf::from)
+}
+
+/// The `file://` URI of a path, percent-encoding every byte but `/` and
+/// the unreserved characters.
+fn path_uri(path: &Path) -> String {
+    let mut uri = String::from("file://");
+
+    for byte in path.to_string_lossy().bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'/' => {
+                uri.push(byte as char)
+            }
+            _ => uri.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+
+    uri
+}
+
+fn failure(id: Value, (code, message): Failure) -> Value {
+    object(vec![
+        ("jsonrpc", string("2.0")),
+        ("id", id),
+        (
+            "error",
+            object(vec![("code", int(code)), ("message", string(&message))]),
+        ),
+    ])
+}
+
+pub fn object(entries: Vec<(&str, Value)>) -> Value {
+    let mut dict = Dict::new();
+    for (key, value) in entries {
+        dict.insert(Key::Str(key.to_string()), value);
+    }
+
+    Value::Dict(Rc::new(dict))
+}
+
+fn list(values: Vec<Value>) -> Value {
+    Value::List(values.into_iter().collect())
+}
+
+fn string(s: &str) -> Value {
+    Value::Str(s.to_string())
+}
+
+fn int(i: isize) -> Value {
+    Value::Int(i)
+}
+
+/// The value at `path` inside nested objects.
+pub fn get<'a>(value: &'a Value, path: &[&str]) -> Option<&'a Value> {
+    path.iter().try_fold(value, |value, name| match value {
+        Value::Dict(dict) => dict.get(&Key::Str(name.to_string())),
+        _ => None,
+    })
+}
+
+fn get_str<'a>(value: &'a Value, path: &[&str]) -> Option<&'a str> {
+    match get(value, path)? {
+        Value::Str(s) => Some(s),
+        _ => None,
+    }
+}
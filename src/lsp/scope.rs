@@ -0,0 +1,255 @@
+//! Finds what a name refers to by the scopes around it: the parameters and
+//! bindings of the function, loop or match arm it is in, then the blocks
+//! around those out to the top level. Within a block, functions are visible
+//! throughout and variables from where they are bound.
+
+use crate::ast::{Expression, FunctionClause, Pattern, Statement};
+use crate::token::{Token, TokenType};
+
+/// A name bound in a scope, at the position of the token that binds it.
+struct Binding {
+    name: String,
+    function: bool,
+    row: u32,
+    col: u32,
+}
+
+/// The positions of the bindings the name at `row`.`col` refers to: every
+/// clause of a function defined in the nearest scope that binds the name, or
+/// the last binding of a variable before it. Empty when no scope around the
+/// name binds it before it is used.
+pub fn resolve(ast: &[Statement], row: u32, col: u32) -> Vec<(u32, u32)> {
+    let mut resolver = Resolver {
+        target: (row, col),
+        scopes: vec![Vec::new()],
+        found: None,
+    };
+    resolver.statements(ast);
+
+    resolver.found.unwrap_or_default()
+}
+
+struct Resolver {
+    target: (u32, u32),
+    scopes: Vec<Vec<Binding>>,
+    found: Option<Vec<(u32, u32)>>,
+}
+
+impl Resolver {
+    fn scoped(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(Vec::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    fn define(&mut self, name: String, function: bool, token: &Token) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.push(Binding {
+                name,
+                function,
+                row: token.row,
+                col: token.col,
+            });
+        }
+    }
+
+    /// Resolves `token` if it is the name being looked for.
+    fn refer(&mut self, token: &Token) {
+        let name = match &token.token_type {
+            TokenType::Identity(name) if (token.row, token.col) == self.target => name,
+            _ => return,
+        };
+
+        for scope in self.scopes.iter().rev() {
+            let bindings: Vec<&Binding> = scope.iter().filter(|b| b.name == *name).collect();
+            let last = match bindings.last() {
+                Some(last) => last,
+                None => continue,
+            };
+
+            let found = match last.function {
+                true => bindings.iter().filter(|b| b.function).collect(),
+                false => vec![last],
+            };
+            self.found = Some(found.iter().map(|b| (b.row, b.col)).collect());
+            return;
+        }
+
+        self.found = Some(Vec::new());
+    }
+
+    fn block(&mut self, block: &[Statement]) {
+        self.scoped(|resolver| resolver.statements(block));
+    }
+
+    /// Walks the statements of a block in the current scope, defining its
+    /// functions first.
+    fn statements(&mut self, block: &[Statement]) {
+        for statement in block {
+            if let Statement::Function(clause) = statement {
+                if let TokenType::Identity(name) = &clause.name.token_type {
+                    self.define(name.clone(), true, &clause.name);
+                }
+            }
+        }
+
+        for statement in block {
+            if self.found.is_some() {
+                return;
+            }
+            self.statement(statement);
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) {
+        match statement {
+            Statement::Let(ls) => {
+                // A function bound by `let` can call itself by its name.
+                let recursive = matches!(
+                    (&ls.pattern, &ls.expression),
+                    (Pattern::Binding(_, _), Expression::Function(_))
+                );
+                if recursive {
+                    self.bind(&ls.pattern);
+                }
+                self.expression(&ls.expression);
+                if !recursive {
+                    self.bind(&ls.pattern);
+                }
+            }
+            Statement::Assign(assign) => {
+                self.expression(&assign.expression);
+                self.refer(&assign.name);
+            }
+            Statement::IndexAssign(assign) => {
+                self.expression(&assign.target.left);
+                self.expression(&assign.target.index);
+                self.expression(&assign.expression);
+            }
+            Statement::Function(clause) => {
+                self.refer(&clause.name);
+                self.clause(clause);
+            }
+            Statement::While(while_statement) => {
+                self.expression(&while_statement.condition);
+                self.block(&while_statement.body);
+            }
+            Statement::For(for_statement) => {
+                self.expression(&for_statement.iterable);
+                self.scoped(|resolver| {
+                    resolver.bind(&for_statement.pattern);
+                    resolver.statements(&for_statement.body);
+                });
+            }
+            Statement::Return(expression) | Statement::Expr(expression) => {
+                self.expression(expression)
+            }
+            Statement::Import(import) => {
+                self.define(import.name(), false, &import.module);
+                self.refer(&import.module);
+            }
+            Statement::Break(_) | Statement::Continue(_) | Statement::End => {}
+        }
+    }
+
+    fn clause(&mut self, clause: &FunctionClause) {
+        self.scoped(|resolver| {
+            for parameter in &clause.parameters {
+                resolver.bind(parameter);
+            }
+            if let Some(guard) = &clause.guard {
+                resolver.expression(guard);
+            }
+            resolver.block(&clause.body);
+        });
+    }
+
+    /// Defines the names a pattern binds. A name is its own definition, and
+    /// a pinned name refers to one bound before.
+    fn bind(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Binding(token, _) => {
+                if let TokenType::Identity(name) = &token.token_type {
+                    self.define(name.clone(), false, token);
+                    self.refer(token);
+                }
+            }
+            Pattern::Pin(token) => self.refer(token),
+            Pattern::List(list) => {
+                for element in list.elements.iter().chain(list.tail.as_deref()) {
+                    self.bind(element);
+                }
+            }
+            Pattern::Dict(dict) => {
+                for (_, pattern) in &dict.entries {
+                    self.bind(pattern);
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Literal(_) => {}
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) {
+        if self.found.is_some() {
+            return;
+        }
+
+        match expression {
+            Expression::Value(token) => self.refer(token),
+            Expression::Prefix(prefix) => self.expression(&prefix.right),
+            Expression::Infix(infix) => {
+                self.expression(&infix.left);
+                self.expression(&infix.right);
+            }
+            Expression::Call(call) => {
+                self.expression(&call.function);
+                for argument in &call.arguments {
+                    self.expression(argument);
+                }
+            }
+            Expression::If(if_expr) => {
+                for (condition, block) in &if_expr.branches {
+                    self.expression(condition);
+                    self.block(block);
+                }
+                if let Some(block) = &if_expr.alternative {
+                    self.block(block);
+                }
+            }
+            Expression::Match(match_expr) => {
+                self.expression(&match_expr.subject);
+                for arm in &match_expr.arms {
+                    self.scoped(|resolver| {
+                        resolver.bind(&arm.pattern);
+                        if let Some(guard) = &arm.guard {
+                            resolver.expression(guard);
+                        }
+                        resolver.expression(&arm.body);
+                    });
+                }
+            }
+            Expression::List(list) => {
+                for element in list.elements.iter().chain(list.tail.as_deref()) {
+                    self.expression(element);
+                }
+            }
+            Expression::Array(array) => {
+                for element in &array.elements {
+                    self.expression(element);
+                }
+            }
+            Expression::Dict(dict) => {
+                for (key, value) in &dict.entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+            }
+            Expression::Index(index) => {
+                self.expression(&index.left);
+                self.expression(&index.index);
+            }
+            Expression::Access(access) => self.expression(&access.left),
+            Expression::Function(clause) => self.clause(clause),
+        }
+    }
+}
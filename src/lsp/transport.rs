@@ -0,0 +1,48 @@
+//! The base protocol: each message is a `Content-Length` header, a blank
+//! line, and that many bytes of JSON.
+
+use crate::json;
+use crate::value::Value;
+use std::io::{self, BufRead, Write};
+
+/// Reads the body of the next message, or `None` once the input is closed.
+pub fn read_message(input: &mut impl BufRead) -> Result<Option<String>, String> {
+    let mut length = None;
+
+    loop {
+        let mut header = String::new();
+        if input.read_line(&mut header).map_err(|e| e.to_string())? == 0 {
+            return Ok(None);
+        }
+
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let value = value.trim();
+                length = Some(
+                    value
+                        .parse::<usize>()
+                        .map_err(|_| format!("Invalid Content-Length {}", value))?,
+                );
+            }
+        }
+    }
+
+    let length = length.ok_or_else(|| String::from("Message without a Content-Length"))?;
+    let mut body = vec![0; length];
+    input.read_exact(&mut body).map_err(|e| e.to_string())?;
+
+    String::from_utf8(body).map(Some).map_err(|e| e.to_string())
+}
+
+pub fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body =
+        json::encode(message, false).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
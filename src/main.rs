@@ -1,35 +1,48 @@
 #![allow(dead_code)]
+mod analysis;
 mod ast;
+mod builtins;
+mod cli;
+mod cst;
+mod diagnostic;
+mod dict;
+mod editor;
+mod environment;
+mod format;
+mod incremental;
+mod interpreter;
+mod json;
 mod lexer;
+mod lint;
+mod list;
+mod lsp;
 mod parser;
+mod pattern;
+mod repl;
+mod signal;
+mod source;
+mod stdio;
 mod token;
+mod tree;
+mod typecheck;
+mod value;
 
 #[cfg(test)]
 mod tests;
 
-use crate::lexer::Lexer;
-use crate::token::TokenType;
-use std::io;
+use std::env;
+use std::process;
+use std::thread;
 
 fn main() {
-    loop {
-        print!(">> ");
-        io::Write::flush(&mut io::stdout()).expect("flush failed!");
+    let args: Vec<String> = env::args().skip(1).collect();
 
-        let mut input = String::new();
-        match io::stdin().read_line(&mut input) {
-            Ok(_) => {
-                let mut lexer = Lexer::new(input.as_str());
-                let mut token = lexer.next();
+    // Scripts recurse deeply, so they run on a thread with a stack large
+    // enough to reach the interpreter's call depth limit.
+    let main = thread::Builder::new()
+        .stack_size(interpreter::STACK_SIZE)
+        .spawn(move || cli::main(&args))
+        .expect("cannot start the main thread");
 
-                while token.token_type != TokenType::EOF {
-                    print!("{} ", token);
-                    token = lexer.next();
-                }
-
-                println!();
-            }
-            Err(e) => println!("Error: {}", e),
-        }
-    }
+    process::exit(main.join().unwrap_or(cli::EXIT_FAILURE));
 }
@@ -1,100 +1,542 @@
-use crate::ast::{Ast, Expression, Statement};
+use crate::ast::{
+    AccessExpr, ArrayExpr, AssignStatement, Ast, Block, CallExpr, DictExpr, DictPattern,
+    Expression, ForStatement, FunctionClause, IfExpr, ImportStatement, IndexAssignStatement,
+    IndexExpr, InfixExpr, ListExpr, ListPattern, MatchArm, MatchExpr, Pattern, PrefixExpr,
+    Statement, WhileStatement,
+};
 use crate::lexer::Lexer;
 use crate::token::{Token, TokenType};
 use std::mem;
+use std::rc::Rc;
+
+#[derive(PartialEq, PartialOrd, Clone, Copy, Debug)]
+enum Precedence {
+    Lowest,
+    Pipe,
+    Or,
+    And,
+    Equals,
+    LessGreater,
+    Range,
+    Sum,
+    Product,
+    Prefix,
+    Call,
+}
+
+impl Precedence {
+    fn of(token_type: &TokenType) -> Self {
+        match token_type {
+            TokenType::Output => Precedence::Pipe,
+            TokenType::Or | TokenType::Xor => Precedence::Or,
+            TokenType::And => Precedence::And,
+            TokenType::Equal | TokenType::NotEqual => Precedence::Equals,
+            TokenType::GreaterThan
+            | TokenType::LessThan
+            | TokenType::GreaterEqual
+            | TokenType::LessEqual => Precedence::LessGreater,
+            TokenType::Range | TokenType::RangeInclusive => Precedence::Range,
+            TokenType::Plus | TokenType::Minus => Precedence::Sum,
+            TokenType::Multiply | TokenType::Divide | TokenType::Modulo => Precedence::Product,
+            TokenType::LParen | TokenType::LBracket | TokenType::Dot => Precedence::Call,
+            _ => Precedence::Lowest,
+        }
+    }
+}
+
+/// Where the parser's tokens come from.
+enum Tokens<'a> {
+    Lexer(Lexer<'a>),
+    /// Tokens lexed already, ending with `EOF`, and the index of the next
+    /// one.
+    Slice(&'a [Token], usize),
+}
+
+impl<'a> Tokens<'a> {
+    fn next(&mut self) -> Token {
+        match self {
+            Tokens::Lexer(lexer) => lexer.next(),
+            Tokens::Slice(tokens, next) => {
+                *next += 1;
+
+                match tokens.get(*next - 1) {
+                    Some(token) => token.clone(),
+                    // Past the end the lexer keeps returning `EOF`, a column
+                    // further along each time.
+                    None => {
+                        let eof = tokens.last().unwrap();
+                        let col = eof.col + (*next - tokens.len()) as u32;
+                        Token::new(TokenType::EOF, eof.row, col)
+                    }
+                }
+            }
+        }
+    }
+}
 
 pub struct Parser<'a> {
-    lexer: Lexer<'a>,
+    tokens: Tokens<'a>,
     current_token: Token,
     peek_token: Token,
+    /// How many tokens have been read, the peek token included.
+    read: usize,
+    loop_depth: usize,
 }
 
 impl<'a> Parser<'a> {
-    pub fn new(mut lexer: Lexer<'a>) -> Self {
-        let current_token = lexer.next();
-        let peek_token = lexer.next();
+    pub fn new(lexer: Lexer<'a>) -> Self {
+        Parser::with_tokens(Tokens::Lexer(lexer))
+    }
+
+    /// A parser over tokens lexed already, which must end with `EOF`. It
+    /// starts at the first statement, after any separators.
+    pub fn from_tokens(tokens: &'a [Token]) -> Self {
+        let mut parser = Parser::with_tokens(Tokens::Slice(tokens, 0));
+        parser.skip_separators();
+        parser
+    }
+
+    fn with_tokens(mut tokens: Tokens<'a>) -> Self {
+        let current_token = tokens.next();
+        let peek_token = tokens.next();
 
         Parser {
-            lexer,
+            tokens,
             current_token,
             peek_token,
+            read: 2,
+            loop_depth: 0,
         }
     }
 
+    /// The index of the current token among all the tokens read.
+    pub fn token_index(&self) -> usize {
+        self.read - 2
+    }
+
+    /// Whether parsing stopped at the end of the input, so a failed parse
+    /// might succeed once more input follows.
+    pub fn at_eof(&self) -> bool {
+        self.current_token.token_type == TokenType::EOF
+    }
+
     fn advance_tokens(&mut self) {
         std::mem::swap(&mut self.peek_token, &mut self.current_token);
-        self.peek_token = self.lexer.next();
+        self.peek_token = self.tokens.next();
+        self.read += 1;
     }
 
     pub fn parse_program(&mut self) -> Result<Ast, String> {
         let mut ast = Ast::new();
+        self.skip_separators();
+
         while self.current_token.token_type != TokenType::EOF {
-            ast.push(self.parse_statement()?);
+            ast.push(self.parse_top_level_statement()?);
             self.advance_tokens();
+            self.expect_separator(&[])?;
+            self.skip_separators();
         }
 
         Ok(ast)
     }
 
+    /// Parses a whole program, recovering from errors by skipping to the
+    /// next line, so that one mistake still leaves the rest of the program
+    /// parsed. Returns every error found.
+    pub fn parse_program_with_errors(&mut self) -> (Ast, Vec<String>) {
+        let mut ast = Ast::new();
+        let mut errors = Vec::new();
+        self.skip_separators();
+
+        while self.current_token.token_type != TokenType::EOF {
+            match self.parse_recovering() {
+                Ok(statement) => ast.push(statement),
+                Err(error) => errors.push(error),
+            }
+        }
+
+        (ast, errors)
+    }
+
+    /// Parses the top-level statement at the current token and the
+    /// separators after it, leaving the next statement as the current token.
+    /// After an error it skips to the next line instead.
+    pub fn parse_recovering(&mut self) -> Result<Statement, String> {
+        let statement = self.parse_top_level_statement().and_then(|statement| {
+            self.advance_tokens();
+            self.expect_separator(&[])?;
+            Ok(statement)
+        });
+
+        if statement.is_err() {
+            self.loop_depth = 0;
+
+            while !matches!(
+                self.current_token.token_type,
+                TokenType::NewLine | TokenType::EOF
+            ) {
+                self.advance_tokens();
+            }
+        }
+
+        self.skip_separators();
+        statement
+    }
+
+    /// Parses the statements following a `:` up to (but not past) one of the
+    /// `terminators`, leaving the terminator as the current token.
+    fn parse_block(&mut self, terminators: &[TokenType]) -> Result<Block, String> {
+        let mut block = Block::new();
+        self.advance_tokens();
+        self.skip_separators();
+
+        while !terminators.contains(&self.current_token.token_type) {
+            if self.current_token.token_type == TokenType::EOF {
+                return Err(format!(
+                    "Unexpected end of input expected 'end' {}.{}",
+                    self.current_token.row, self.current_token.col
+                ));
+            }
+
+            block.push(self.parse_statement()?);
+            self.advance_tokens();
+            self.expect_separator(terminators)?;
+            self.skip_separators();
+        }
+
+        Ok(block)
+    }
+
+    fn skip_separators(&mut self) {
+        while let TokenType::NewLine | TokenType::SemiColon = self.current_token.token_type {
+            self.advance_tokens();
+        }
+    }
+
+    fn expect_separator(&self, terminators: &[TokenType]) -> Result<(), String> {
+        match &self.current_token.token_type {
+            TokenType::NewLine | TokenType::SemiColon | TokenType::EOF => Ok(()),
+            token_type if terminators.contains(token_type) => Ok(()),
+            _ => Err(format!(
+                "Expected new line, got {} {}.{}",
+                self.current_token, self.current_token.row, self.current_token.col
+            )),
+        }
+    }
+
+    /// Newlines are insignificant inside brackets and braces.
+    fn skip_peek_newlines(&mut self) {
+        while self.peek_token.token_type == TokenType::NewLine {
+            self.advance_tokens();
+        }
+    }
+
+    fn expect_peek(&mut self, expected: TokenType, display: &str) -> Result<(), String> {
+        if self.peek_token.token_type == expected {
+            self.advance_tokens();
+            Ok(())
+        } else {
+            Err(format!(
+                "Unexpected token expected '{}', got {} {}.{}",
+                display, self.peek_token, self.peek_token.row, self.peek_token.col
+            ))
+        }
+    }
+
+    /// A statement outside of any block, where definitions can be marked
+    /// `pub`.
+    fn parse_top_level_statement(&mut self) -> Result<Statement, String> {
+        if self.current_token.token_type != TokenType::Pub {
+            return self.parse_statement();
+        }
+
+        self.advance_tokens();
+        let mut statement = match (&self.current_token.token_type, &self.peek_token.token_type) {
+            (TokenType::Let, _) => self.parse_let()?,
+            (TokenType::Function, TokenType::Identity(_)) => self.parse_function()?,
+            _ => {
+                return Err(format!(
+                    "Unexpected token expected 'let' or 'fn' after 'pub', got {} {}.{}",
+                    self.current_token, self.current_token.row, self.current_token.col
+                ))
+            }
+        };
+
+        match &mut statement {
+            Statement::Let(ls) => ls.public = true,
+            Statement::Function(clause) => clause.public = true,
+            _ => {}
+        }
+
+        Ok(statement)
+    }
+
     fn parse_statement(&mut self) -> Result<Statement, String> {
-        match self.current_token.token_type {
-            TokenType::Let => self.parse_let(),
-            TokenType::Return => self.parse_return(),
-            TokenType::End => self.parse_end(),
-            TokenType::Not | TokenType::Minus => self.parse_not(),
-            _ => Err(format!("Error")),
+        match (&self.current_token.token_type, &self.peek_token.token_type) {
+            (TokenType::Pub, _) => Err(format!(
+                "Only top-level definitions can be pub {}.{}",
+                self.current_token.row, self.current_token.col
+            )),
+            (TokenType::Import, _) => self.parse_import(),
+            (TokenType::Let, _) => self.parse_let(),
+            (TokenType::Function, TokenType::Identity(_)) => self.parse_function(),
+            (TokenType::While, _) => self.parse_while(),
+            (TokenType::For, _) => self.parse_for(),
+            (TokenType::Break, _) | (TokenType::Continue, _) => self.parse_loop_jump(),
+            (TokenType::Return, _) => self.parse_return(),
+            (TokenType::End, _) => self.parse_end(),
+            (TokenType::Identity(_), TokenType::Assign) => self.parse_assign(),
+            _ => self.parse_expression_statement(),
         }
     }
 
-    fn parse_not(&mut self) -> Result<Statement, String> {
-        let operator = mem::take(&mut self.current_token);
+    /// An expression, or an index assignment when an index expression is
+    /// followed by `=`.
+    fn parse_expression_statement(&mut self) -> Result<Statement, String> {
+        let expression = self.parse_expression(Precedence::Lowest)?;
+
+        if self.peek_token.token_type != TokenType::Assign {
+            return Ok(Statement::Expr(expression));
+        }
+
+        let target = match expression {
+            Expression::Index(index) => index,
+            other => {
+                let token = other.token();
+                return Err(format!(
+                    "Cannot assign to {} {}.{}",
+                    token, token.row, token.col
+                ));
+            }
+        };
+
+        self.advance_tokens();
         self.advance_tokens();
 
-        let expression = self.parse_expression()?;
+        let expression = self.parse_expression(Precedence::Lowest)?;
 
-        Ok(Statement::new_prefix_statement(operator, expression))
+        Ok(Statement::IndexAssign(IndexAssignStatement {
+            target,
+            expression,
+        }))
+    }
+
+    fn parse_import(&mut self) -> Result<Statement, String> {
+        let token = mem::take(&mut self.current_token);
+
+        match self.peek_token.token_type {
+            TokenType::Str(_) | TokenType::Identity(_) => {
+                self.advance_tokens();
+                Ok(Statement::Import(ImportStatement {
+                    token,
+                    module: mem::take(&mut self.current_token),
+                }))
+            }
+            _ => Err(format!(
+                "Unexpected token expected a path or module name, got {} {}.{}",
+                self.peek_token, self.peek_token.row, self.peek_token.col
+            )),
+        }
     }
 
     fn parse_let(&mut self) -> Result<Statement, String> {
         self.advance_tokens();
 
-        let identifier = self.parse_identifier()?;
+        let pattern = self.parse_pattern()?;
+
+        self.expect_peek(TokenType::Assign, "=")?;
+        self.advance_tokens();
+
+        let expr = self.parse_expression(Precedence::Lowest)?;
+
+        Ok(Statement::new_let_statement(pattern, expr))
+    }
+
+    fn parse_assign(&mut self) -> Result<Statement, String> {
+        let name = mem::take(&mut self.current_token);
+        self.advance_tokens();
+        self.advance_tokens();
+
+        let expression = self.parse_expression(Precedence::Lowest)?;
+
+        Ok(Statement::Assign(AssignStatement { name, expression }))
+    }
 
+    fn parse_function(&mut self) -> Result<Statement, String> {
         self.advance_tokens();
 
-        if let TokenType::Colon = self.current_token.token_type {
+        let name = self.parse_identifier()?;
+
+        let parameters = self.parse_parameters()?;
+
+        let mut return_type = None;
+        if self.peek_token.token_type == TokenType::Output {
             self.advance_tokens();
-        } else {
-            return Err(format!(
-                "Unexpected token expected ':', got {} {}.{}",
-                self.current_token, self.current_token.row, self.current_token.col
-            ));
+            self.advance_tokens();
+            return_type = Some(self.parse_type()?);
+        }
+
+        let mut guard = None;
+        if self.peek_token.token_type == TokenType::When {
+            self.advance_tokens();
+            self.advance_tokens();
+            guard = Some(self.parse_expression(Precedence::Lowest)?);
         }
 
-        let let_type = self.parse_type()?;
+        self.expect_peek(TokenType::Colon, ":")?;
 
-        self.advance_tokens();
+        let body = self.parse_function_body()?;
 
-        if let TokenType::Assign = self.current_token.token_type {
-            self.advance_tokens()
-        } else {
-            return Err(format!(
-                "Unexpected token expected '=', got {} {}.{}",
-                self.current_token, self.current_token.row, self.current_token.col
-            ));
+        Ok(Statement::Function(FunctionClause {
+            name,
+            parameters,
+            return_type,
+            guard,
+            body,
+            public: false,
+        }))
+    }
+
+    /// `fn(x) -> expression` or `fn(x): block end`.
+    fn parse_function_literal(&mut self) -> Result<Expression, String> {
+        let name = mem::take(&mut self.current_token);
+        let parameters = self.parse_parameters()?;
+
+        let body = match self.peek_token.token_type {
+            TokenType::Output => {
+                self.advance_tokens();
+                self.advance_tokens();
+
+                let loop_depth = mem::replace(&mut self.loop_depth, 0);
+                let expression = self.parse_expression(Precedence::Lowest);
+                self.loop_depth = loop_depth;
+
+                vec![Statement::Expr(expression?)]
+            }
+            TokenType::Colon => {
+                self.advance_tokens();
+                self.parse_function_body()?
+            }
+            _ => {
+                return Err(format!(
+                    "Unexpected token expected '->' or ':', got {} {}.{}",
+                    self.peek_token, self.peek_token.row, self.peek_token.col
+                ))
+            }
+        };
+
+        Ok(Expression::Function(Rc::new(FunctionClause {
+            name,
+            parameters,
+            return_type: None,
+            guard: None,
+            body,
+            public: false,
+        })))
+    }
+
+    /// Parses `(pattern, ...)` starting from the token before the `(`.
+    fn parse_parameters(&mut self) -> Result<Vec<Pattern>, String> {
+        self.expect_peek(TokenType::LParen, "(")?;
+
+        let mut parameters = Vec::new();
+        while self.peek_token.token_type != TokenType::RParen {
+            self.advance_tokens();
+            parameters.push(self.parse_pattern()?);
+
+            if self.peek_token.token_type == TokenType::Comma {
+                self.advance_tokens();
+            } else if self.peek_token.token_type != TokenType::RParen {
+                return Err(format!(
+                    "Unexpected token expected ')', got {} {}.{}",
+                    self.peek_token, self.peek_token.row, self.peek_token.col
+                ));
+            }
         }
+        self.advance_tokens();
+
+        Ok(parameters)
+    }
+
+    /// Loops outside the function do not extend into its body.
+    fn parse_function_body(&mut self) -> Result<Block, String> {
+        let loop_depth = mem::replace(&mut self.loop_depth, 0);
+        let body = self.parse_block(&[TokenType::End]);
+        self.loop_depth = loop_depth;
+
+        body
+    }
+
+    fn parse_while(&mut self) -> Result<Statement, String> {
+        let token = mem::take(&mut self.current_token);
+        self.advance_tokens();
+
+        let condition = self.parse_expression(Precedence::Lowest)?;
 
-        let expr = self.parse_expression()?;
+        self.expect_peek(TokenType::Colon, ":")?;
 
+        let body = self.parse_loop_body()?;
+
+        Ok(Statement::While(WhileStatement {
+            token,
+            condition,
+            body,
+        }))
+    }
+
+    fn parse_for(&mut self) -> Result<Statement, String> {
+        let token = mem::take(&mut self.current_token);
         self.advance_tokens();
 
-        Ok(Statement::new_let_statement(identifier, let_type, expr))
+        let pattern = self.parse_pattern()?;
+
+        self.expect_peek(TokenType::In, "in")?;
+        self.advance_tokens();
+
+        let iterable = self.parse_expression(Precedence::Lowest)?;
+
+        self.expect_peek(TokenType::Colon, ":")?;
+
+        let body = self.parse_loop_body()?;
+
+        Ok(Statement::For(ForStatement {
+            token,
+            pattern,
+            iterable,
+            body,
+        }))
+    }
+
+    fn parse_loop_body(&mut self) -> Result<Block, String> {
+        self.loop_depth += 1;
+        let body = self.parse_block(&[TokenType::End]);
+        self.loop_depth -= 1;
+
+        body
+    }
+
+    /// `break` and `continue` are only allowed inside a loop in the same
+    /// function.
+    fn parse_loop_jump(&mut self) -> Result<Statement, String> {
+        let token = mem::take(&mut self.current_token);
+
+        if self.loop_depth == 0 {
+            return Err(format!(
+                "{} outside of a loop {}.{}",
+                token, token.row, token.col
+            ));
+        }
+
+        match token.token_type {
+            TokenType::Break => Ok(Statement::Break(token)),
+            _ => Ok(Statement::Continue(token)),
+        }
     }
 
     fn parse_return(&mut self) -> Result<Statement, String> {
         self.advance_tokens();
 
-        match self.parse_expression() {
+        match self.parse_expression(Precedence::Lowest) {
             Ok(expr) => Ok(Statement::Return(expr)),
             Err(e) => Err(e),
         }
@@ -102,7 +544,6 @@ impl<'a> Parser<'a> {
 
     fn parse_end(&mut self) -> Result<Statement, String> {
         if let TokenType::NewLine = self.peek_token.token_type {
-            self.advance_tokens();
             Ok(Statement::End)
         } else {
             Err(format!(
@@ -112,14 +553,53 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn parse_expression(&mut self) -> Result<Expression, String> {
+    fn parse_expression(&mut self, precedence: Precedence) -> Result<Expression, String> {
+        let mut left = self.parse_prefix()?;
+
+        while precedence < Precedence::of(&self.peek_token.token_type) {
+            self.advance_tokens();
+            left = self.parse_infix(left)?;
+        }
+
+        Ok(left)
+    }
+
+    fn parse_prefix(&mut self) -> Result<Expression, String> {
         match &self.current_token.token_type {
+            TokenType::Identity(_) => self.parse_identity_expression(),
             TokenType::Str(_)
             | TokenType::Int(_)
             | TokenType::Character(_)
             | TokenType::False
-            | TokenType::Identity(_)
             | TokenType::True => Ok(Expression::Value(mem::take(&mut self.current_token))),
+            TokenType::Not | TokenType::Minus => {
+                let operator = mem::take(&mut self.current_token);
+                self.advance_tokens();
+
+                let right = Box::new(self.parse_expression(Precedence::Prefix)?);
+
+                Ok(Expression::Prefix(PrefixExpr { operator, right }))
+            }
+            TokenType::LParen => {
+                self.advance_tokens();
+
+                let expression = self.parse_expression(Precedence::Lowest)?;
+
+                self.expect_peek(TokenType::RParen, ")")?;
+
+                Ok(expression)
+            }
+            TokenType::If => self.parse_if(),
+            TokenType::Match => self.parse_match(),
+            TokenType::Function => self.parse_function_literal(),
+            TokenType::LBracket => self.parse_list(),
+            TokenType::LArray => {
+                let token = mem::take(&mut self.current_token);
+                let elements = self.parse_expression_list(TokenType::RArray, "/]")?;
+
+                Ok(Expression::Array(ArrayExpr { token, elements }))
+            }
+            TokenType::LBrace => self.parse_dict(),
             _ => Err(format!(
                 "Unexpected token expected value, got {} {}.{}",
                 self.current_token, self.current_token.row, self.current_token.col
@@ -127,6 +607,430 @@ impl<'a> Parser<'a> {
         }
     }
 
+    /// An identifier directly followed by a value on the same line is a call
+    /// with its single argument's parenthesis omitted, as in `add_one 3`.
+    fn parse_identity_expression(&mut self) -> Result<Expression, String> {
+        let identity = Expression::Value(mem::take(&mut self.current_token));
+
+        let omitted_parens = matches!(
+            self.peek_token.token_type,
+            TokenType::Identity(_)
+                | TokenType::Str(_)
+                | TokenType::Int(_)
+                | TokenType::Character(_)
+                | TokenType::True
+                | TokenType::False
+        );
+
+        if !omitted_parens {
+            return Ok(identity);
+        }
+
+        self.advance_tokens();
+        let argument = self.parse_expression(Precedence::Prefix)?;
+
+        Ok(Expression::Call(CallExpr {
+            function: Box::new(identity),
+            arguments: vec![argument],
+        }))
+    }
+
+    fn parse_infix(&mut self, left: Expression) -> Result<Expression, String> {
+        match self.current_token.token_type {
+            TokenType::LParen => {
+                let arguments = self.parse_arguments()?;
+
+                Ok(Expression::Call(CallExpr {
+                    function: Box::new(left),
+                    arguments,
+                }))
+            }
+            TokenType::Output => self.parse_pipe(left),
+            TokenType::Dot => {
+                let token = mem::take(&mut self.current_token);
+                self.advance_tokens();
+
+                let name = self.parse_identifier()?;
+
+                Ok(Expression::Access(AccessExpr {
+                    token,
+                    left: Box::new(left),
+                    name,
+                }))
+            }
+            TokenType::LBracket => {
+                let token = mem::take(&mut self.current_token);
+                self.advance_tokens();
+
+                let index = self.parse_expression(Precedence::Lowest)?;
+
+                self.expect_peek(TokenType::RBracket, "]")?;
+
+                Ok(Expression::Index(IndexExpr {
+                    token,
+                    left: Box::new(left),
+                    index: Box::new(index),
+                }))
+            }
+            _ => {
+                let operator = mem::take(&mut self.current_token);
+                let precedence = Precedence::of(&operator.token_type);
+                self.advance_tokens();
+
+                let right = self.parse_expression(precedence)?;
+
+                Ok(Expression::Infix(InfixExpr {
+                    operator,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                }))
+            }
+        }
+    }
+
+    /// `value -> f(a)` is sugar for `f(value, a)`.
+    fn parse_pipe(&mut self, left: Expression) -> Result<Expression, String> {
+        self.advance_tokens();
+
+        match self.parse_expression(Precedence::Prefix)? {
+            Expression::Call(mut call) => {
+                call.arguments.insert(0, left);
+                Ok(Expression::Call(call))
+            }
+            function => Ok(Expression::Call(CallExpr {
+                function: Box::new(function),
+                arguments: vec![left],
+            })),
+        }
+    }
+
+    fn parse_arguments(&mut self) -> Result<Vec<Expression>, String> {
+        self.parse_expression_list(TokenType::RParen, ")")
+    }
+
+    /// Parses comma separated expressions up to `end`, allowing a trailing
+    /// comma, and leaves `end` as the current token.
+    fn parse_expression_list(
+        &mut self,
+        end: TokenType,
+        display: &str,
+    ) -> Result<Vec<Expression>, String> {
+        let mut list = Vec::new();
+        self.skip_peek_newlines();
+
+        while self.peek_token.token_type != end {
+            self.advance_tokens();
+            list.push(self.parse_expression(Precedence::Lowest)?);
+            self.skip_peek_newlines();
+
+            if self.peek_token.token_type != TokenType::Comma {
+                break;
+            }
+
+            self.advance_tokens();
+            self.skip_peek_newlines();
+        }
+
+        self.expect_peek(end, display)?;
+
+        Ok(list)
+    }
+
+    /// A list literal, optionally prepended to an existing list with
+    /// `[head | tail]`.
+    fn parse_list(&mut self) -> Result<Expression, String> {
+        let token = mem::take(&mut self.current_token);
+        let mut elements = Vec::new();
+        let mut tail = None;
+
+        loop {
+            self.skip_peek_newlines();
+
+            match self.peek_token.token_type {
+                TokenType::RBracket => break,
+                TokenType::Bar => {
+                    self.advance_tokens();
+                    self.advance_tokens();
+                    tail = Some(Box::new(self.parse_expression(Precedence::Lowest)?));
+                    self.skip_peek_newlines();
+                    break;
+                }
+                _ => {
+                    self.advance_tokens();
+                    elements.push(self.parse_expression(Precedence::Lowest)?);
+                    self.skip_peek_newlines();
+                }
+            }
+
+            match self.peek_token.token_type {
+                TokenType::Comma => self.advance_tokens(),
+                TokenType::Bar | TokenType::RBracket => {}
+                _ => {
+                    return Err(format!(
+                        "Unexpected token expected ']', got {} {}.{}",
+                        self.peek_token, self.peek_token.row, self.peek_token.col
+                    ))
+                }
+            }
+        }
+
+        self.expect_peek(TokenType::RBracket, "]")?;
+
+        Ok(Expression::List(ListExpr {
+            token,
+            elements,
+            tail,
+        }))
+    }
+
+    fn parse_dict(&mut self) -> Result<Expression, String> {
+        let token = mem::take(&mut self.current_token);
+        let mut entries = Vec::new();
+        self.skip_peek_newlines();
+
+        while self.peek_token.token_type != TokenType::RBrace {
+            self.advance_tokens();
+
+            let key = match self.parse_bare_key() {
+                Some(key) => Expression::Value(key),
+                None => self.parse_expression(Precedence::Lowest)?,
+            };
+
+            self.expect_peek(TokenType::Colon, ":")?;
+            self.advance_tokens();
+
+            entries.push((key, self.parse_expression(Precedence::Lowest)?));
+            self.skip_peek_newlines();
+
+            if self.peek_token.token_type != TokenType::Comma {
+                break;
+            }
+
+            self.advance_tokens();
+            self.skip_peek_newlines();
+        }
+
+        self.expect_peek(TokenType::RBrace, "}")?;
+
+        Ok(Expression::Dict(DictExpr { token, entries }))
+    }
+
+    /// A bare identifier before a `:` in a dictionary is a string key, so
+    /// `{language: "Slice"}` is `{"language": "Slice"}`.
+    fn parse_bare_key(&mut self) -> Option<Token> {
+        match (&self.current_token.token_type, &self.peek_token.token_type) {
+            (TokenType::Identity(name), TokenType::Colon) => Some(Token::new(
+                TokenType::Str(name.clone()),
+                self.current_token.row,
+                self.current_token.col,
+            )),
+            _ => None,
+        }
+    }
+
+    fn parse_match(&mut self) -> Result<Expression, String> {
+        let token = mem::take(&mut self.current_token);
+        self.advance_tokens();
+
+        let subject = Box::new(self.parse_expression(Precedence::Lowest)?);
+
+        self.expect_peek(TokenType::Colon, ":")?;
+        self.advance_tokens();
+        self.skip_separators();
+
+        let mut arms = Vec::new();
+        while self.current_token.token_type != TokenType::End {
+            if self.current_token.token_type == TokenType::EOF {
+                return Err(format!(
+                    "Unexpected end of input expected 'end' {}.{}",
+                    self.current_token.row, self.current_token.col
+                ));
+            }
+
+            let pattern = self.parse_pattern()?;
+
+            let mut guard = None;
+            if self.peek_token.token_type == TokenType::When {
+                self.advance_tokens();
+                self.advance_tokens();
+                guard = Some(self.parse_expression(Precedence::Pipe)?);
+            }
+
+            self.expect_peek(TokenType::Output, "->")?;
+            self.advance_tokens();
+
+            let body = self.parse_expression(Precedence::Lowest)?;
+            arms.push(MatchArm {
+                pattern,
+                guard,
+                body,
+            });
+
+            self.advance_tokens();
+            self.expect_separator(&[TokenType::End])?;
+            self.skip_separators();
+        }
+
+        Ok(Expression::Match(MatchExpr {
+            token,
+            subject,
+            arms,
+        }))
+    }
+
+    fn parse_if(&mut self) -> Result<Expression, String> {
+        let token = mem::take(&mut self.current_token);
+        let terminators = [TokenType::Elif, TokenType::Else, TokenType::End];
+        let mut branches = Vec::new();
+        let mut alternative = None;
+
+        loop {
+            self.advance_tokens();
+            let condition = self.parse_expression(Precedence::Lowest)?;
+            self.expect_peek(TokenType::Colon, ":")?;
+            branches.push((condition, self.parse_block(&terminators)?));
+
+            match self.current_token.token_type {
+                TokenType::Elif => continue,
+                TokenType::Else => {
+                    self.expect_peek(TokenType::Colon, ":")?;
+                    alternative = Some(self.parse_block(&[TokenType::End])?);
+                    break;
+                }
+                _ => break,
+            }
+        }
+
+        Ok(Expression::If(IfExpr {
+            token,
+            branches,
+            alternative,
+        }))
+    }
+
+    fn parse_pattern(&mut self) -> Result<Pattern, String> {
+        match &self.current_token.token_type {
+            TokenType::Identity(name) if name == "_" => {
+                Ok(Pattern::Wildcard(mem::take(&mut self.current_token)))
+            }
+            TokenType::Identity(_) => {
+                let name = mem::take(&mut self.current_token);
+
+                if self.peek_token.token_type == TokenType::Colon {
+                    self.advance_tokens();
+                    self.advance_tokens();
+                    return Ok(Pattern::Binding(name, Some(self.parse_type()?)));
+                }
+
+                Ok(Pattern::Binding(name, None))
+            }
+            TokenType::Str(_)
+            | TokenType::Int(_)
+            | TokenType::Character(_)
+            | TokenType::False
+            | TokenType::True => Ok(Pattern::Literal(mem::take(&mut self.current_token))),
+            TokenType::Pin => {
+                self.advance_tokens();
+                Ok(Pattern::Pin(self.parse_identifier()?))
+            }
+            TokenType::LBracket => self.parse_list_pattern(),
+            TokenType::LBrace => self.parse_dict_pattern(),
+            _ => Err(format!(
+                "Unexpected token expected pattern, got {} {}.{}",
+                self.current_token, self.current_token.row, self.current_token.col
+            )),
+        }
+    }
+
+    fn parse_list_pattern(&mut self) -> Result<Pattern, String> {
+        let token = mem::take(&mut self.current_token);
+        let mut elements = Vec::new();
+        let mut tail = None;
+
+        loop {
+            self.skip_peek_newlines();
+
+            match self.peek_token.token_type {
+                TokenType::RBracket => break,
+                TokenType::Bar => {
+                    self.advance_tokens();
+                    self.advance_tokens();
+                    tail = Some(Box::new(self.parse_pattern()?));
+                    self.skip_peek_newlines();
+                    break;
+                }
+                _ => {
+                    self.advance_tokens();
+                    elements.push(self.parse_pattern()?);
+                    self.skip_peek_newlines();
+                }
+            }
+
+            match self.peek_token.token_type {
+                TokenType::Comma => self.advance_tokens(),
+                TokenType::Bar | TokenType::RBracket => {}
+                _ => {
+                    return Err(format!(
+                        "Unexpected token expected ']', got {} {}.{}",
+                        self.peek_token, self.peek_token.row, self.peek_token.col
+                    ))
+                }
+            }
+        }
+
+        self.expect_peek(TokenType::RBracket, "]")?;
+
+        Ok(Pattern::List(ListPattern {
+            token,
+            elements,
+            tail,
+        }))
+    }
+
+    fn parse_dict_pattern(&mut self) -> Result<Pattern, String> {
+        let token = mem::take(&mut self.current_token);
+        let mut entries = Vec::new();
+        self.skip_peek_newlines();
+
+        while self.peek_token.token_type != TokenType::RBrace {
+            self.advance_tokens();
+
+            let key = match self.parse_bare_key() {
+                Some(key) => key,
+                None => match self.current_token.token_type {
+                    TokenType::Str(_)
+                    | TokenType::Int(_)
+                    | TokenType::Character(_)
+                    | TokenType::False
+                    | TokenType::True => mem::take(&mut self.current_token),
+                    _ => {
+                        return Err(format!(
+                            "Unexpected token expected dictionary key, got {} {}.{}",
+                            self.current_token, self.current_token.row, self.current_token.col
+                        ))
+                    }
+                },
+            };
+
+            self.expect_peek(TokenType::Colon, ":")?;
+            self.advance_tokens();
+
+            entries.push((key, self.parse_pattern()?));
+            self.skip_peek_newlines();
+
+            if self.peek_token.token_type != TokenType::Comma {
+                break;
+            }
+
+            self.advance_tokens();
+            self.skip_peek_newlines();
+        }
+
+        self.expect_peek(TokenType::RBrace, "}")?;
+
+        Ok(Pattern::Dict(DictPattern { token, entries }))
+    }
+
     fn parse_type(&mut self) -> Result<Token, String> {
         match &self.current_token.token_type {
             TokenType::StringType
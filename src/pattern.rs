@@ -0,0 +1,85 @@
+use crate::ast::Pattern;
+use crate::environment::Env;
+use crate::token::TokenType;
+use crate::value::Value;
+
+/// Matches `value` against `pattern`, defining any bound names in `env`.
+/// Pinned names are looked up through `env` and compared rather than rebound.
+pub fn match_pattern(pattern: &Pattern, value: &Value, env: &Env) -> Result<bool, String> {
+    match pattern {
+        Pattern::Wildcard(_) => Ok(true),
+        Pattern::Binding(name, var_type) => {
+            if let Some(var_type) = var_type {
+                if !value.is_type(var_type) {
+                    return Ok(false);
+                }
+            }
+
+            if let TokenType::Identity(name) = &name.token_type {
+                env.borrow_mut().define(name, value.clone());
+            }
+
+            Ok(true)
+        }
+        Pattern::Literal(token) => Ok(Value::from_literal(token).as_ref() == Some(value)),
+        Pattern::Pin(token) => {
+            let name = match &token.token_type {
+                TokenType::Identity(name) => name,
+                _ => return Ok(false),
+            };
+
+            match env.borrow().get(name) {
+                Some(pinned) => Ok(&pinned == value),
+                None => Err(format!(
+                    "Undefined variable {} in pin {}.{}",
+                    name, token.row, token.col
+                )),
+            }
+        }
+        Pattern::List(list) => {
+            let elements = match value {
+                Value::List(list) => list,
+                _ => return Ok(false),
+            };
+
+            let fits = match list.tail {
+                Some(_) => elements.len() >= list.elements.len(),
+                None => elements.len() == list.elements.len(),
+            };
+
+            if !fits {
+                return Ok(false);
+            }
+
+            for (pattern, element) in list.elements.iter().zip(elements.iter()) {
+                if !match_pattern(pattern, element, env)? {
+                    return Ok(false);
+                }
+            }
+
+            match (&list.tail, elements.skip(list.elements.len())) {
+                (Some(tail), Some(rest)) => match_pattern(tail, &Value::List(rest.clone()), env),
+                _ => Ok(true),
+            }
+        }
+        Pattern::Dict(dict) => {
+            if !matches!(value, Value::Dict(_)) {
+                return Ok(false);
+            }
+
+            for (key, pattern) in &dict.entries {
+                let key = match Value::from_literal(key) {
+                    Some(key) => key,
+                    None => return Ok(false),
+                };
+
+                match value.dict_get(&key) {
+                    Some(entry) if match_pattern(pattern, entry, env)? => {}
+                    _ => return Ok(false),
+                }
+            }
+
+            Ok(true)
+        }
+    }
+}
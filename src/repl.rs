@@ -0,0 +1,223 @@
+//! The interactive session. Input is evaluated against one interpreter so
+//! bindings persist between lines, and input that ends inside an unfinished
+//! block is held back until the rest of it is entered. Each input is a file
+//! named `<input N>` to the positions of errors.
+
+use crate::ast::Ast;
+use crate::dict::Key;
+use crate::interpreter::Interpreter;
+use crate::lexer::{Lexer, KEYWORDS};
+use crate::parser::Parser;
+use crate::source::FileId;
+use crate::tree::tree;
+use crate::typecheck::type_of;
+use crate::value::Value;
+
+pub const PROMPT: &str = ">> ";
+pub const CONTINUATION_PROMPT: &str = ".. ";
+
+pub struct Repl {
+    interpreter: Interpreter,
+    /// Lines of input that do not parse on their own yet.
+    pending: String,
+    /// How many inputs have been parsed in full or failed to parse.
+    inputs: usize,
+}
+
+impl Repl {
+    pub fn new(interpreter: Interpreter) -> Self {
+        Repl {
+            interpreter,
+            pending: String::new(),
+            inputs: 0,
+        }
+    }
+
+    pub fn prompt(&self) -> &'static str {
+        if self.pending.is_empty() {
+            PROMPT
+        } else {
+            CONTINUATION_PROMPT
+        }
+    }
+
+    /// Handles one line of input, returning what to print, if anything. An
+    /// empty line after incomplete input evaluates it anyway, reporting why
+    /// it does not parse.
+    pub fn eval_line(&mut self, line: &str) -> Result<Option<String>, String> {
+        let line = line.trim_end_matches(['\n', '\r']);
+
+        if self.pending.is_empty() {
+            if line.trim().is_empty() {
+                return Ok(None);
+            }
+            if line.trim_start().starts_with(':') {
+                return self.command(line.trim());
+            }
+        }
+
+        let force = !self.pending.is_empty() && line.trim().is_empty();
+        self.pending.push_str(line);
+        self.pending.push('\n');
+
+        let pending = self.pending.clone();
+        let (ast, file) = match self.parse(&pending) {
+            Ok(parsed) => parsed,
+            Err((_, true)) if !force => return Ok(None),
+            Err((error, _)) => {
+                self.pending.clear();
+                self.inputs += 1;
+                return Err(error);
+            }
+        };
+        self.pending.clear();
+        self.inputs += 1;
+
+        let value = self
+            .interpreter
+            .run_from(&ast, file)
+            .map_err(|error| self.interpreter.describe(&error))?;
+
+        match value {
+            Value::Unit => Ok(None),
+            value => Ok(Some(value.repr())),
+        }
+    }
+
+    /// Parses `source` as the next input. A parse error comes placed in the
+    /// input, with whether it is at the end of it, where more input may
+    /// still complete it.
+    fn parse(&mut self, source: &str) -> Result<(Ast, FileId), (String, bool)> {
+        let name = format!("<input {}>", self.inputs + 1);
+        let file = self.interpreter.set_source(&name, source.to_string());
+
+        let mut parser = Parser::new(Lexer::new(source).in_file(file));
+        match parser.parse_program() {
+            Ok(ast) => Ok((ast, file)),
+            Err(error) => Err((
+                self.interpreter.sources().place(file, &error),
+                parser.at_eof(),
+            )),
+        }
+    }
+
+    /// Drops any incomplete input.
+    pub fn cancel(&mut self) {
+        self.pending.clear();
+    }
+
+    /// Completes the word at the end of `line`, returning the byte offset the
+    /// word starts at and the sorted words it could be completed to. After a
+    /// `.` the candidates are the members of the module or dict before it,
+    /// otherwise they are keywords and variables.
+    pub fn complete(&self, line: &str) -> (usize, Vec<String>) {
+        let start = line
+            .char_indices()
+            .rev()
+            .find(|(_, c)| !Lexer::is_letter(*c) && *c != '.')
+            .map_or(0, |(i, c)| i + c.len_utf8());
+        let word = &line[start..];
+
+        let (start, prefix, mut candidates) = match word.rfind('.') {
+            Some(dot) => (
+                start + dot + 1,
+                &word[dot + 1..],
+                self.members(&word[..dot]),
+            ),
+            None => {
+                let mut names = self.interpreter.names();
+                names.extend(KEYWORDS.iter().map(|(keyword, _)| keyword.to_string()));
+                (start, word, names)
+            }
+        };
+
+        candidates.retain(|candidate| candidate.starts_with(prefix));
+        candidates.sort();
+        candidates.dedup();
+
+        (start, candidates)
+    }
+
+    fn members(&self, name: &str) -> Vec<String> {
+        match self.interpreter.lookup(name) {
+            Some(Value::Module(module)) => module
+                .functions
+                .iter()
+                .map(|builtin| builtin.name.to_string())
+                .collect(),
+            Some(Value::Namespace(namespace)) => namespace.exports.clone(),
+            Some(Value::Dict(dict)) => dict
+                .entries()
+                .iter()
+                .filter_map(|(key, _)| match key {
+                    Key::Str(key) => Some(key.clone()),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    fn command(&mut self, line: &str) -> Result<Option<String>, String> {
+        let (command, argument) = match line.find(char::is_whitespace) {
+            Some(i) => (&line[..i], line[i..].trim()),
+            None => (line, ""),
+        };
+
+        match (command, argument) {
+            (":type", "") | (":ast", "") => Err(format!("{} expects an expression", command)),
+            (":type", source) => {
+                let (ast, _) = self.parse_command(source)?;
+                let globals: Vec<(String, Value)> = self
+                    .interpreter
+                    .names()
+                    .into_iter()
+                    .filter_map(|name| Some((name.clone(), self.interpreter.lookup(&name)?)))
+                    .collect();
+
+                let sources = self.interpreter.sources();
+                let kind = type_of(&ast, &globals).map_err(|diagnostics| {
+                    let errors: Vec<String> = diagnostics
+                        .iter()
+                        .map(|d| {
+                            format!("{}: {}", sources.location(d.file, d.row, d.col), d.message)
+                        })
+                        .collect();
+                    errors.join("\n")
+                })?;
+
+                Ok(Some(kind.to_string()))
+            }
+            (":ast", source) => {
+                let (ast, _) = self.parse_command(source)?;
+
+                Ok(Some(tree(&ast).trim_end().to_string()))
+            }
+            (":load", "") => Err(String::from(":load expects a file")),
+            (":load", path) => {
+                self.interpreter
+                    .run_file(path)
+                    .map_err(|error| self.interpreter.describe(&error))?;
+
+                Ok(None)
+            }
+            (":reset", "") => {
+                self.interpreter.reset();
+
+                Ok(None)
+            }
+            (":reset", _) => Err(String::from(":reset takes no arguments")),
+            _ => Err(format!(
+                "Unknown command {}, expected :type, :ast, :load or :reset",
+                command
+            )),
+        }
+    }
+
+    /// Parses the expression of a command, which is an input of its own.
+    fn parse_command(&mut self, source: &str) -> Result<(Ast, FileId), String> {
+        let parsed = self.parse(source).map_err(|(error, _)| error);
+        self.inputs += 1;
+        parsed
+    }
+}
@@ -0,0 +1,71 @@
+//! Signals the REPL handles itself. Once `catch` is called, Ctrl-C sets
+//! `INTERRUPTED` instead of killing the process, so a long evaluation can be
+//! stopped, and resizing the terminal sets `RESIZED` so the line editor knows
+//! to look up its width again.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+pub static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+pub static RESIZED: AtomicBool = AtomicBool::new(false);
+
+/// The thread that called `catch`, which Ctrl-C is passed on to so that it
+/// interrupts that thread's reads rather than those of whichever thread the
+/// signal happened to be delivered to.
+static THREAD: AtomicUsize = AtomicUsize::new(0);
+
+/// Clears `flag`, returning whether it was set.
+pub fn take(flag: &AtomicBool) -> bool {
+    flag.load(Ordering::Relaxed) && flag.swap(false, Ordering::Relaxed)
+}
+
+#[cfg(unix)]
+mod unix {
+    use std::os::raw::c_int;
+
+    pub const SIGINT: c_int = 2;
+    pub const SIGWINCH: c_int = 28;
+
+    extern "C" {
+        pub fn signal(signum: c_int, handler: extern "C" fn(c_int)) -> usize;
+        pub fn siginterrupt(signum: c_int, flag: c_int) -> c_int;
+        pub fn pthread_self() -> usize;
+        pub fn pthread_kill(thread: usize, signum: c_int) -> c_int;
+    }
+}
+
+#[cfg(unix)]
+extern "C" fn handle(signum: std::os::raw::c_int) {
+    if signum != unix::SIGINT {
+        RESIZED.store(true, Ordering::Relaxed);
+        return;
+    }
+
+    let thread = THREAD.load(Ordering::Relaxed);
+    // Safety: both are signal safe, and `thread` runs until the process ends.
+    unsafe {
+        if thread != unix::pthread_self() {
+            unix::pthread_kill(thread, signum);
+            return;
+        }
+    }
+    INTERRUPTED.store(true, Ordering::Relaxed);
+}
+
+/// Installs the handlers for the calling thread. A read from the terminal
+/// that Ctrl-C interrupts fails with `ErrorKind::Interrupted` rather than
+/// carrying on, so the line editor sees it straight away.
+#[cfg(unix)]
+pub fn catch() {
+    // Safety: the handler only stores to atomics and signals a thread, which
+    // is signal safe.
+    unsafe {
+        THREAD.store(unix::pthread_self(), Ordering::Relaxed);
+        unix::signal(unix::SIGINT, handle);
+        unix::siginterrupt(unix::SIGINT, 1);
+        unix::signal(unix::SIGWINCH, handle);
+    }
+}
+
+#[cfg(not(unix))]
+pub fn catch() {}
@@ -0,0 +1,98 @@
+//! The files a program is loaded from. Tokens carry the `FileId` of the file
+//! they were lexed from, so the `SourceMap` that gave out the id can say
+//! which file a row and column are in.
+
+use crate::diagnostic::{split_position, Diagnostic};
+use crate::token::Token;
+
+/// A file in a `SourceMap`. Tokens lexed without a file have the default
+/// id, which no file is given.
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Default, Hash)]
+pub struct FileId(u32);
+
+pub struct SourceFile {
+    /// The path the file was loaded from, or the URI of an editor document.
+    pub name: String,
+    pub text: String,
+}
+
+#[derive(Default)]
+pub struct SourceMap {
+    files: Vec<SourceFile>,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    pub fn add(&mut self, name: String, text: String) -> FileId {
+        self.files.push(SourceFile { name, text });
+        FileId(self.files.len() as u32)
+    }
+
+    /// The file with `name`, added if there is none yet, holding `text`.
+    pub fn set(&mut self, name: &str, text: String) -> FileId {
+        match self.find(name) {
+            Some(id) => {
+                self.files[id.0 as usize - 1].text = text;
+                id
+            }
+            None => self.add(name.to_string(), text),
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<FileId> {
+        self.files
+            .iter()
+            .position(|file| file.name == name)
+            .map(|index| FileId(index as u32 + 1))
+    }
+
+    pub fn get(&self, id: FileId) -> Option<&SourceFile> {
+        self.files.get((id.0 as usize).checked_sub(1)?)
+    }
+
+    pub fn name(&self, id: FileId) -> Option<&str> {
+        self.get(id).map(|file| file.name.as_str())
+    }
+
+    /// The line at `row` of a file, without its line break.
+    pub fn line(&self, id: FileId, row: u32) -> Option<&str> {
+        self.get(id)?.text.lines().nth(row as usize - 1)
+    }
+
+    /// A position as `file:row:col`, or `row:col` when the file is unknown.
+    pub fn location(&self, id: FileId, row: u32, col: u32) -> String {
+        match self.name(id) {
+            Some(name) => format!("{}:{}:{}", name, row, col),
+            None => format!("{}:{}", row, col),
+        }
+    }
+
+    pub fn locate(&self, token: &Token) -> String {
+        self.location(token.file, token.row, token.col)
+    }
+
+    /// An error message ending in the `row.col` it happened at in `file`,
+    /// as the parser and interpreter report them, as `file:row:col: message`.
+    pub fn place(&self, id: FileId, error: &str) -> String {
+        match (split_position(error), self.name(id)) {
+            (Some((message, row, col)), _) => {
+                format!("{}: {}", self.location(id, row, col), message)
+            }
+            (None, Some(name)) => format!("{}: {}", name, error),
+            (None, None) => error.to_string(),
+        }
+    }
+
+    /// A diagnostic as `severity: file:row:col: message`.
+    pub fn render(&self, diagnostic: &Diagnostic) -> String {
+        format!(
+            "{}: {}: {}",
+            diagnostic.severity,
+            self.location(diagnostic.file, diagnostic.row, diagnostic.col),
+            diagnostic.message
+        )
+    }
+}
@@ -0,0 +1,130 @@
+//! The standard streams a script can use. The interpreter only reaches them
+//! through the `Io` trait, so an embedder or a test can supply its own.
+
+use std::cell::RefCell;
+use std::io::{self, BufRead, Read, Write};
+use std::rc::Rc;
+
+pub trait Io {
+    fn write_out(&mut self, text: &str) -> io::Result<()>;
+
+    fn write_err(&mut self, text: &str) -> io::Result<()>;
+
+    /// The next line of input without its line ending, or `None` at the end
+    /// of input.
+    fn read_line(&mut self) -> io::Result<Option<String>>;
+
+    /// The rest of the input.
+    fn read_all(&mut self) -> io::Result<String>;
+}
+
+/// The process's real stdin, stdout and stderr.
+#[derive(Default)]
+pub struct StdIo;
+
+impl Io for StdIo {
+    fn write_out(&mut self, text: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(text.as_bytes())?;
+        stdout.flush()
+    }
+
+    fn write_err(&mut self, text: &str) -> io::Result<()> {
+        io::stderr().write_all(text.as_bytes())
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let mut line = String::new();
+
+        if io::stdin().lock().read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+
+        Ok(Some(trim_line_ending(line)))
+    }
+
+    fn read_all(&mut self) -> io::Result<String> {
+        let mut input = String::new();
+        io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    }
+}
+
+/// Reads from a fixed string and collects everything written, for tests and
+/// embedders that want to capture a script's output.
+#[derive(Default)]
+pub struct BufferIo {
+    input: String,
+    position: usize,
+    pub out: String,
+    pub err: String,
+}
+
+impl BufferIo {
+    pub fn new(input: &str) -> Self {
+        BufferIo {
+            input: input.to_string(),
+            ..BufferIo::default()
+        }
+    }
+}
+
+impl Io for BufferIo {
+    fn write_out(&mut self, text: &str) -> io::Result<()> {
+        self.out.push_str(text);
+        Ok(())
+    }
+
+    fn write_err(&mut self, text: &str) -> io::Result<()> {
+        self.err.push_str(text);
+        Ok(())
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        let rest = &self.input[self.position..];
+
+        if rest.is_empty() {
+            return Ok(None);
+        }
+
+        let end = rest.find('\n').map_or(rest.len(), |i| i + 1);
+        self.position += end;
+
+        Ok(Some(trim_line_ending(rest[..end].to_string())))
+    }
+
+    fn read_all(&mut self) -> io::Result<String> {
+        let rest = self.input[self.position..].to_string();
+        self.position = self.input.len();
+        Ok(rest)
+    }
+}
+
+/// Lets the caller keep a handle on the streams it gave the interpreter.
+impl<T: Io> Io for Rc<RefCell<T>> {
+    fn write_out(&mut self, text: &str) -> io::Result<()> {
+        self.borrow_mut().write_out(text)
+    }
+
+    fn write_err(&mut self, text: &str) -> io::Result<()> {
+        self.borrow_mut().write_err(text)
+    }
+
+    fn read_line(&mut self) -> io::Result<Option<String>> {
+        self.borrow_mut().read_line()
+    }
+
+    fn read_all(&mut self) -> io::Result<String> {
+        self.borrow_mut().read_all()
+    }
+}
+
+fn trim_line_ending(mut line: String) -> String {
+    if line.ends_with('\n') {
+        line.pop();
+        if line.ends_with('\r') {
+            line.pop();
+        }
+    }
+    line
+}
@@ -0,0 +1,202 @@
+use crate::analysis::analyze;
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::source::FileId;
+
+fn diagnostics(input: &str) -> Vec<Diagnostic> {
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    analyze(&ast)
+}
+
+#[test]
+fn catch_all_first_shadows_later_clauses() {
+    let input = "fn greet(_):
+        \"Huh\"
+    end
+
+    fn greet(name: string):
+        \"Hello \" + name
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].to_string(),
+        "warning: Unreachable clause, greet(_: string) is always matched by an earlier clause 5.8"
+    );
+}
+
+#[test]
+fn different_arities_do_not_shadow() {
+    let input = "fn greet(_):
+        1
+    end
+    fn greet(name, greeting):
+        2
+    end";
+
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn guarded_clause_does_not_cover() {
+    let input = "fn classify(n: int) when n > 100:
+        \"big\"
+    end
+    fn classify(n: int):
+        \"small\"
+    end";
+
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn typed_clauses_cover_their_types() {
+    let input = "fn show(n: int):
+        1
+    end
+    fn show(s: string):
+        2
+    end
+    fn show(_):
+        3
+    end";
+
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn missing_bool_case() {
+    let input = "fn to_int(True):
+        1
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].message,
+        "Non-exhaustive clauses, to_int(False) is not matched"
+    );
+    assert_eq!((found[0].row, found[0].col), (1, 4));
+}
+
+#[test]
+fn literal_clauses_need_a_fallback() {
+    let input = "fn favorite_bread(\"white\"):
+        1
+    end
+    fn favorite_bread(\"rye\"):
+        2
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].message,
+        "Non-exhaustive clauses, favorite_bread(_: string) is not matched"
+    );
+}
+
+#[test]
+fn duplicate_literal_is_unreachable() {
+    let input = "fn f(0):
+        1
+    end
+    fn f(0):
+        2
+    end
+    fn f(n: int):
+        3
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].row, found[0].col), (4, 8));
+}
+
+#[test]
+fn match_list_shapes() {
+    let input = "match list:
+        [] -> 0
+        [x] -> 1
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].message,
+        "Non-exhaustive clauses, match on [_, _ | _] is not matched"
+    );
+
+    let input = "match list:
+        [] -> 0
+        [h | t] -> 1
+        [x] -> 2
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!((found[0].row, found[0].col), (4, 9));
+}
+
+#[test]
+fn nested_match_in_function_body() {
+    let input = "fn f(flag: bool, other: bool):
+        match [flag, other]:
+            [] -> 0
+            [_] -> 1
+            [True, _] -> 2
+            [_, True] -> 3
+            [_, _, _ | _] -> 4
+        end
+    end";
+
+    let found = diagnostics(input);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].message,
+        "Non-exhaustive clauses, match on [False, False] is not matched"
+    );
+}
+
+#[test]
+fn dict_and_pin_patterns_never_cover() {
+    let input = "match request:
+        {method: \"GET\"} -> 1
+        {method: \"GET\"} -> 2
+        ^expected -> 3
+        _ -> 4
+    end";
+
+    assert!(diagnostics(input).is_empty());
+}
+
+#[test]
+fn diagnostic_from_parse_error() {
+    let diagnostic = Diagnostic::from_error(
+        "Unexpected token expected pattern, got Assign 1.5",
+        FileId::default(),
+    );
+
+    assert_eq!(
+        diagnostic.message,
+        "Unexpected token expected pattern, got Assign"
+    );
+    assert_eq!((diagnostic.row, diagnostic.col), (1, 5));
+
+    let diagnostic = Diagnostic::from_error("Something went wrong", FileId::default());
+    assert_eq!(diagnostic.message, "Something went wrong");
+    assert_eq!((diagnostic.row, diagnostic.col), (1, 1));
+}
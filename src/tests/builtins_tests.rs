@@ -0,0 +1,457 @@
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::stdio::BufferIo;
+use crate::value::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn run(input: &str) -> Result<Value, String> {
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program()?;
+
+    Interpreter::new().run(&ast)
+}
+
+/// Runs a program reading `stdin`, returning its result and what it wrote to
+/// stdout and stderr.
+fn run_io(input: &str, stdin: &str) -> (Result<Value, String>, String, String) {
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    let io = Rc::new(RefCell::new(BufferIo::new(stdin)));
+    let result = Interpreter::with_io(Rc::clone(&io)).run(&ast);
+
+    let io = io.borrow();
+    (result, io.out.clone(), io.err.clone())
+}
+
+fn display(input: &str) -> String {
+    run(input).unwrap().to_string()
+}
+
+#[test]
+fn list_access() {
+    assert_eq!(display("List.nth([1, 2, 3], 1)"), "2");
+    assert_eq!(display("List.length([1, 2, 3])"), "3");
+    assert_eq!(display("List.head([1, 2, 3])"), "1");
+    assert_eq!(display("List.tail([1, 2, 3])"), "[2, 3]");
+    assert_eq!(display("List.reverse([1, 2, 3])"), "[3, 2, 1]");
+    assert_eq!(display("List.length([])"), "0");
+
+    assert_eq!(
+        run("List.nth([1, 2, 3], 3)").unwrap_err(),
+        "Index 3 out of bounds for list 1.6"
+    );
+    assert_eq!(
+        run("List.head([])").unwrap_err(),
+        "Cannot take the head of an empty list 1.6"
+    );
+    assert_eq!(
+        run("List.tail([])").unwrap_err(),
+        "Cannot take the tail of an empty list 1.6"
+    );
+    assert_eq!(
+        run("List.length([/1/])").unwrap_err(),
+        "List.length expected list or range, got array 1.6"
+    );
+}
+
+#[test]
+fn list_higher_order() {
+    let input = "fn add_one(n: int):
+        n + 1
+    end
+    fn even?(n: int):
+        n % 2 == 0
+    end
+    let numbers = [1, 2, 3, 4]
+    [
+        numbers -> List.map(add_one),
+        numbers -> List.filter(even?),
+        numbers -> List.fold(0, fn(sum, n) -> sum + n),
+        numbers -> List.any?(fn(n) -> n > 3),
+        numbers -> List.all?(fn(n) -> n > 3),
+        [] -> List.all?(even?),
+    ]";
+
+    assert_eq!(
+        display(input),
+        "[[2, 3, 4, 5], [2, 4], 10, True, False, True]"
+    );
+
+    assert_eq!(
+        run("List.filter([1], fn(n) -> n)").unwrap_err(),
+        "List.filter expected the function to return bool, got int 1.6"
+    );
+}
+
+#[test]
+fn list_find_result() {
+    let input = "fn first_big(numbers):
+        match List.find(numbers, fn(n) -> n > 10):
+            {ok: n} -> n
+            {error: reason} -> reason
+        end
+    end
+    [first_big([5, 20, 30]), first_big([1])]";
+
+    assert_eq!(display(input), "[20, \"not found\"]");
+}
+
+#[test]
+fn list_combining() {
+    assert_eq!(display("List.append([1, 2], [3])"), "[1, 2, 3]");
+    assert_eq!(display("List.concat([[1], [], [2, 3]])"), "[1, 2, 3]");
+    assert_eq!(
+        display("List.zip([1, 2, 3], ['a', 'b'])"),
+        "[[1, 'a'], [2, 'b']]"
+    );
+
+    assert_eq!(
+        run("List.concat([[1], 2])").unwrap_err(),
+        "List.concat expected list or range, got int 1.6"
+    );
+}
+
+#[test]
+fn list_functions_take_ranges() {
+    assert_eq!(display("List.nth(3..10, 2)"), "5");
+    assert_eq!(display("List.length(3..10)"), "7");
+    assert_eq!(display("List.length(10..3)"), "0");
+    assert_eq!(display("List.head(3..10)"), "3");
+    assert_eq!(display("List.tail(3..10)"), "4..10");
+    assert_eq!(display("List.reverse(1..=3)"), "[3, 2, 1]");
+    assert_eq!(display("List.map(1..4, fn(n) -> n * n)"), "[1, 4, 9]");
+    assert_eq!(
+        display("List.filter(1..=6, fn(n) -> n % 2 == 0)"),
+        "[2, 4, 6]"
+    );
+    assert_eq!(display("List.append(1..3, 3..5)"), "[1, 2, 3, 4]");
+    assert_eq!(display("List.concat([1..3, [5]])"), "[1, 2, 5]");
+    assert_eq!(display("List.sort(1..4)"), "[1, 2, 3]");
+    assert_eq!(
+        display("List.zip(1..3, ['a', 'b'])"),
+        "[[1, 'a'], [2, 'b']]"
+    );
+
+    assert_eq!(
+        run("List.nth(3..10, 7)").unwrap_err(),
+        "Index 7 out of bounds for list 1.6"
+    );
+    assert_eq!(
+        run("List.tail(3..3)").unwrap_err(),
+        "Cannot take the tail of an empty list 1.6"
+    );
+}
+
+#[test]
+fn ranges_are_consumed_lazily() {
+    assert_eq!(
+        display("List.fold(0..1000000, 0, fn(sum, n) -> sum + n)"),
+        "499999500000"
+    );
+    assert_eq!(
+        display("List.any?(0..9223372036854775807, fn(n) -> n == 5)"),
+        "True"
+    );
+    assert_eq!(
+        display("List.find(0..9223372036854775807, fn(n) -> n * n > 50)"),
+        "{\"ok\": 8}"
+    );
+    assert_eq!(
+        display("List.nth(0..9223372036854775807, 1000000000000)"),
+        "1000000000000"
+    );
+}
+
+#[test]
+fn list_sort() {
+    assert_eq!(display("List.sort([3, 1, 2])"), "[1, 2, 3]");
+    assert_eq!(
+        display("List.sort([\"rye\", \"bagel\", \"white\"])"),
+        "[\"bagel\", \"rye\", \"white\"]"
+    );
+    assert_eq!(
+        display("List.sort([[2, 'a'], [1, 'b'], [1, 'a']])"),
+        "[[1, 'a'], [1, 'b'], [2, 'a']]"
+    );
+
+    assert_eq!(
+        run("List.sort([1, \"one\"])").unwrap_err(),
+        "List.sort cannot compare string and int 1.6"
+    );
+}
+
+#[test]
+fn string_unicode_lengths() {
+    assert_eq!(display("String.length(\"héllo wörld\")"), "11");
+    assert_eq!(display("\"héllo\"[1]"), "é");
+    assert_eq!(display("String.chars(\"añb\")"), "['a', 'ñ', 'b']");
+    assert_eq!(display("String.to_list(\"añb\")"), "[\"a\", \"ñ\", \"b\"]");
+    assert_eq!(display("String.upcase(\"straße\")"), "STRASSE");
+    assert_eq!(display("String.downcase(\"ÀB\")"), "àb");
+}
+
+#[test]
+fn string_splitting_and_joining() {
+    assert_eq!(
+        display("\"a,b,,c\" -> String.split(\",\")"),
+        "[\"a\", \"b\", \"\", \"c\"]"
+    );
+    assert_eq!(display("String.join([\"a\", \"b\", 'c'], \"-\")"), "a-b-c");
+    assert_eq!(display("String.trim(\"  padded\t\n\")"), "padded");
+    assert_eq!(
+        display("String.replace(\"one fish two fish\", \"fish\", \"bread\")"),
+        "one bread two bread"
+    );
+
+    assert_eq!(
+        run("String.split(\"abc\", \"\")").unwrap_err(),
+        "String.split expected a non-empty string 1.8"
+    );
+    assert_eq!(
+        run("String.join([1], \",\")").unwrap_err(),
+        "String.join expected a list of strings, got int 1.8"
+    );
+}
+
+#[test]
+fn string_predicates() {
+    let input = "let bread = \"sourdough rye\"
+    [
+        String.contains?(bread, \"dough\"),
+        String.starts_with?(bread, \"sour\"),
+        String.ends_with?(bread, \"sour\"),
+        String.contains?(bread, \"\"),
+    ]";
+
+    assert_eq!(display(input), "[True, True, False, True]");
+}
+
+#[test]
+fn char_module() {
+    let input = "[
+        Char.is_digit?('7'),
+        Char.is_digit?('a'),
+        Char.is_digit?('٣'),
+        Char.is_letter?('ß'),
+        Char.is_whitespace?(' '),
+        Char.is_upper?('A'),
+        Char.is_lower?('A'),
+        Char.upcase('é'),
+        Char.downcase('Q'),
+        Char.upcase('ß'),
+        Char.to_int('a'),
+        Char.to_string('x'),
+    ]";
+
+    assert_eq!(
+        display(input),
+        "[True, False, True, True, True, True, False, 'É', 'q', 'ß', 97, \"x\"]"
+    );
+
+    assert_eq!(
+        run("Char.to_int(\"a\")").unwrap_err(),
+        "Char.to_int expected char, got string 1.6"
+    );
+}
+
+#[test]
+fn parsing_conversions_return_results() {
+    assert_eq!(display("int_of_string(\"-42\")"), "{\"ok\": -42}");
+    assert_eq!(
+        display("int_of_string(\"4x\")"),
+        "{\"error\": \"Cannot convert \\\"4x\\\" to int\"}"
+    );
+    assert_eq!(display("bool_of_string(\"True\")"), "{\"ok\": True}");
+    assert_eq!(
+        display("bool_of_string(\"true\")"),
+        "{\"error\": \"Cannot convert \\\"true\\\" to bool\"}"
+    );
+    assert_eq!(display("char_of_string(\"é\")"), "{\"ok\": 'é'}");
+    assert_eq!(
+        display("char_of_string(\"ab\")"),
+        "{\"error\": \"Cannot convert \\\"ab\\\" to char\"}"
+    );
+    assert_eq!(display("char_of_int(97)"), "{\"ok\": 'a'}");
+    assert_eq!(
+        display("char_of_int(-1)"),
+        "{\"error\": \"-1 is not a unicode code point\"}"
+    );
+}
+
+#[test]
+fn matching_on_conversion_results() {
+    let input = "fn parse(text: string) -> int:
+        match text -> int_of_string:
+            {ok: n} -> n
+            {error: _} -> 0
+        end
+    end
+    [parse(\"6\"), parse(\"six\")]";
+
+    assert_eq!(display(input), "[6, 0]");
+
+    let input = "let {ok: n} = int_of_string(\"12\")
+    n";
+
+    assert_eq!(run(input).unwrap(), Value::Int(12));
+}
+
+#[test]
+fn infallible_conversions() {
+    assert_eq!(
+        display(
+            "[string_of_int(-3), string_of_bool(False), string_of_char('x'), int_of_char('a')]"
+        ),
+        "[\"-3\", \"False\", \"x\", 97]"
+    );
+    assert_eq!(display("int_of_string"), "<fn int_of_string>");
+
+    assert_eq!(
+        run("string_of_int(True)").unwrap_err(),
+        "string_of_int expected int, got bool 1.1"
+    );
+}
+
+#[test]
+fn output_builtins() {
+    let input = "fn greet(name: string):
+        puts (\"Hello \" + name)
+    end
+    greet(\"Brett\")
+    print 1
+    print 'a'
+    puts([\"list\", 'c'])
+    eputs \"oops\"";
+
+    let (result, out, err) = run_io(input, "");
+
+    assert_eq!(result.unwrap(), Value::Unit);
+    assert_eq!(out, "Hello Brett\n1a[\"list\", 'c']\n");
+    assert_eq!(err, "oops\n");
+}
+
+#[test]
+fn reading_lines() {
+    let input = "let {ok: first} = IO.read_line()
+    let rest = IO.lines()
+    [first, rest, IO.read_line(), IO.read_all()]";
+
+    let (result, _, _) = run_io(input, "one\r\ntwo\nthree");
+
+    assert_eq!(
+        result.unwrap().to_string(),
+        "[\"one\", [\"two\", \"three\"], {\"error\": \"end of input\"}, \"\"]"
+    );
+}
+
+#[test]
+fn reading_all_input() {
+    let input = "let text = IO.read_all()
+    for word in String.split(text, \" \"):
+        puts String.upcase(word)
+    end";
+
+    let (result, out, _) = run_io(input, "rye bread");
+
+    assert!(result.is_ok());
+    assert_eq!(out, "RYE\nBREAD\n");
+}
+
+/// A fresh directory under the system temp directory for one test.
+fn temp_dir(name: &str) -> std::path::PathBuf {
+    let dir = std::env::temp_dir().join(format!("slice-{}-{}", name, std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    dir
+}
+
+#[test]
+fn file_round_trip() {
+    let dir = temp_dir("file_round_trip");
+    let input = format!(
+        "let dir = \"{}\"
+        let report = Path.join(dir, \"report.txt\")
+        let existed = File.exists?(report)
+        let {{ok: _}} = File.write(report, \"one\n\")
+        let {{ok: _}} = File.append(report, \"two\n\")
+        let {{ok: _}} = File.append(Path.join(dir, \"notes.txt\"), \"new\")
+        [existed, File.exists?(report), File.read(report), File.lines(report), Dir.list(dir)]",
+        dir.display()
+    );
+
+    assert_eq!(
+        display(&input),
+        "[False, True, {\"ok\": \"one\\ntwo\\n\"}, {\"ok\": [\"one\", \"two\"]}, \
+         {\"ok\": [\"notes.txt\", \"report.txt\"]}]"
+    );
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn file_errors_are_results() {
+    let dir = temp_dir("file_errors_are_results");
+    let input = format!(
+        "match File.read(Path.join(\"{}\", \"missing.txt\")):
+            {{ok: contents}} -> contents
+            {{error: _}} -> \"missing\"
+        end",
+        dir.display()
+    );
+
+    assert_eq!(display(&input), "missing");
+
+    let input = format!("Dir.list(Path.join(\"{}\", \"nowhere\"))", dir.display());
+    assert!(display(&input).starts_with("{\"error\": "));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+#[test]
+fn filesystem_can_be_disabled() {
+    let input = "let path = Path.join(\"/tmp\", \"slice.txt\")
+    File.exists?(path)";
+
+    let lexer = Lexer::new(input);
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    let mut interpreter = Interpreter::new();
+    interpreter.allow_filesystem(false);
+
+    assert_eq!(
+        interpreter.run(&ast).unwrap_err(),
+        "File.exists? is not allowed, filesystem access is disabled 2.10"
+    );
+}
+
+#[test]
+fn json_module() {
+    let input = "let {ok: config} = Json.decode(IO.read_all())
+    let updated = Dict.put(config, \"tags\", [/1, 'a'/])
+    [config.version, Json.encode(updated), Json.encode({1: 2}), Json.decode(\"[1,\")]";
+
+    let (result, _, _) = run_io(
+        input,
+        r#"{"name": "slice", "version": 1.5, "tags": [1, 2]}"#,
+    );
+
+    assert_eq!(
+        result.unwrap().to_string(),
+        "[1.5, {\"ok\": \"{\\\"name\\\":\\\"slice\\\",\\\"version\\\":1.5,\\\"tags\\\":[1,\\\"a\\\"]}\"}, \
+         {\"error\": \"Cannot encode int dict key 1 as JSON\"}, \
+         {\"error\": \"Expected a value, got end of input at line 1, column 4\"}]"
+    );
+
+    assert_eq!(
+        display("Json.encode_pretty([1])"),
+        "{\"ok\": \"[\\n  1\\n]\"}"
+    );
+}
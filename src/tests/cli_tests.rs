@@ -0,0 +1,233 @@
+use crate::cli::{check, main, parse_args, Command, EXIT_FAILURE};
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::lint::{Level, LintConfig};
+use crate::parser::Parser;
+
+fn parse(args: &[&str]) -> Result<Command, String> {
+    let args: Vec<String> = args.iter().map(|arg| arg.to_string()).collect();
+    parse_args(&args)
+}
+
+fn strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(|arg| arg.to_string()).collect()
+}
+
+#[test]
+fn subcommands() {
+    assert_eq!(parse(&[]), Ok(Command::Repl));
+    assert_eq!(parse(&["repl"]), Ok(Command::Repl));
+    assert_eq!(parse(&["lsp"]), Ok(Command::Lsp));
+    assert_eq!(parse(&["help"]), Ok(Command::Help));
+    assert_eq!(parse(&["--help"]), Ok(Command::Help));
+    assert_eq!(
+        parse(&["check", "main.slice"]),
+        Ok(Command::Check {
+            path: String::from("main.slice"),
+            lints: LintConfig::default()
+        })
+    );
+    assert_eq!(
+        parse(&["check", "--lint", "predicate-names=deny", "main.slice"]),
+        Ok(Command::Check {
+            path: String::from("main.slice"),
+            lints: LintConfig {
+                predicate_names: Level::Deny
+            }
+        })
+    );
+    assert_eq!(
+        parse(&["tokens", "main.slice"]),
+        Ok(Command::Tokens {
+            path: String::from("main.slice")
+        })
+    );
+    assert_eq!(
+        parse(&["fmt"]),
+        Ok(Command::Fmt {
+            paths: Vec::new(),
+            check: false
+        })
+    );
+    assert_eq!(
+        parse(&["fmt", "--check", "a.slice", "b.slice"]),
+        Ok(Command::Fmt {
+            paths: strings(&["a.slice", "b.slice"]),
+            check: true
+        })
+    );
+    assert_eq!(
+        parse(&["ast", "main.slice"]),
+        Ok(Command::Ast {
+            path: String::from("main.slice")
+        })
+    );
+}
+
+#[test]
+fn run_arguments() {
+    assert_eq!(
+        parse(&["run", "main.slice"]),
+        Ok(Command::Run {
+            path: String::from("main.slice"),
+            args: Vec::new()
+        })
+    );
+    assert_eq!(
+        parse(&["run", "main.slice", "a", "b"]),
+        Ok(Command::Run {
+            path: String::from("main.slice"),
+            args: strings(&["a", "b"])
+        })
+    );
+    assert_eq!(
+        parse(&["run", "main.slice", "a", "--", "-b", "--"]),
+        Ok(Command::Run {
+            path: String::from("main.slice"),
+            args: strings(&["a", "-b", "--"])
+        })
+    );
+}
+
+#[test]
+fn usage_errors() {
+    assert_eq!(
+        parse(&["build"]),
+        Err(String::from("Unknown command build"))
+    );
+    assert_eq!(parse(&["run"]), Err(String::from("run expects a file")));
+    assert_eq!(parse(&["check"]), Err(String::from("check expects a file")));
+    assert_eq!(
+        parse(&["check", "main.slice", "--lint"]),
+        Err(String::from("--lint expects lint=level"))
+    );
+    assert_eq!(
+        parse(&["check", "--lint", "unused=deny", "main.slice"]),
+        Err(String::from("Unknown lint unused"))
+    );
+    assert_eq!(
+        parse(&["ast", "a.slice", "b.slice"]),
+        Err(String::from("ast expects exactly one file"))
+    );
+    assert_eq!(
+        parse(&["run", "main.slice", "-v"]),
+        Err(String::from(
+            "Unknown option -v for run, use -- to pass it to the script"
+        ))
+    );
+    assert_eq!(
+        parse(&["fmt", "-w"]),
+        Err(String::from("Unknown option -w for fmt"))
+    );
+    assert_eq!(
+        parse(&["repl", "main.slice"]),
+        Err(String::from("repl takes no arguments"))
+    );
+    assert_eq!(
+        parse(&["lsp", "--stdio"]),
+        Err(String::from("lsp takes no arguments"))
+    );
+}
+
+#[test]
+fn script_arguments() {
+    let ast = Parser::new(Lexer::new("Env.args")).parse_program().unwrap();
+
+    let mut interpreter = Interpreter::new();
+    assert_eq!(interpreter.run(&ast).unwrap().to_string(), "[]");
+
+    interpreter.set_args(strings(&["a", "-b"]));
+    assert_eq!(
+        interpreter.run(&ast).unwrap().to_string(),
+        "[\"a\", \"-b\"]"
+    );
+}
+
+#[test]
+fn check_lint_levels() {
+    let path = std::env::temp_dir().join(format!("slice-check-lint-{}.slice", std::process::id()));
+    std::fs::write(&path, "fn positive(n) -> bool:\n  n > 0\nend\n").unwrap();
+    let path = path.to_str().unwrap();
+
+    let report = |lints: LintConfig| {
+        let mut out = Vec::new();
+        let result = check(path, &lints, &mut out);
+        (result.is_ok(), String::from_utf8(out).unwrap())
+    };
+
+    let (passed, out) = report(LintConfig::default());
+    assert!(passed);
+    assert_eq!(
+        out,
+        format!(
+            "warning: {}:1:4: Function positive returns bool and should be named positive?\n",
+            path
+        )
+    );
+
+    let (passed, out) = report(LintConfig {
+        predicate_names: Level::Deny,
+    });
+    assert!(!passed);
+    assert!(out.starts_with("error: "));
+
+    assert_eq!(
+        report(LintConfig {
+            predicate_names: Level::Allow
+        }),
+        (true, String::new())
+    );
+
+    let exit = |lint: &str| main(&strings(&["check", "--lint", lint, path]));
+    assert_eq!(exit("predicate-names=deny"), EXIT_FAILURE);
+    assert_eq!(exit("predicate-names=allow"), 0);
+
+    std::fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn check_follows_imports() {
+    let dir = std::env::temp_dir().join(format!("slice-check-imports-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(dir.join("lib")).unwrap();
+    let files = [
+        (
+            "main.slice",
+            "import \"lib/shapes.slice\"\nimport Missing\nShapes.area(2)\n",
+        ),
+        (
+            "lib/shapes.slice",
+            "import Broken\npub fn area(n): n * \"two\" end\n",
+        ),
+        ("lib/broken.slice", "let = 1\nimport \"shapes.slice\"\n"),
+    ];
+    for (name, text) in files {
+        std::fs::write(dir.join(name), text).unwrap();
+    }
+    let main = dir.join("main.slice");
+
+    let mut out = Vec::new();
+    assert!(check(main.to_str().unwrap(), &LintConfig::default(), &mut out).is_err());
+    let shapes = dir.join("lib/shapes.slice");
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        format!(
+            "error: {main}:2:1: Cannot import {missing}: No such file or directory (os error 2)\n\
+             error: {shapes}:2:19: Unsupported operand string for Multiply\n\
+             error: {broken}:1:5: Unexpected token expected pattern, got Assign\n",
+            main = main.display(),
+            missing = dir.join("missing.slice").display(),
+            shapes = shapes.display(),
+            broken = dir.join("lib/broken.slice").display(),
+        )
+    );
+
+    std::fs::write(dir.join("lib/broken.slice"), "import \"shapes.slice\"\n").unwrap();
+    let mut out = Vec::new();
+    assert!(check(main.to_str().unwrap(), &LintConfig::default(), &mut out).is_err());
+    assert!(String::from_utf8(out)
+        .unwrap()
+        .contains("1:1: Import cycle shapes.slice -> broken.slice -> shapes.slice"));
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,55 @@
+use crate::dict::{Dict, Key};
+use crate::value::Value;
+
+fn keys(dict: &Dict) -> Vec<Key> {
+    dict.entries().iter().map(|(key, _)| key.clone()).collect()
+}
+
+#[test]
+fn keys_of_different_types_are_distinct() {
+    let mut dict = Dict::new();
+    dict.insert(Key::Int(1), Value::Int(1));
+    dict.insert(Key::Str(String::from("1")), Value::Int(2));
+    dict.insert(Key::Char('1'), Value::Int(3));
+    dict.insert(Key::Bool(true), Value::Int(4));
+
+    assert_eq!(dict.len(), 4);
+    assert_eq!(dict.get(&Key::Int(1)), Some(&Value::Int(1)));
+    assert_eq!(dict.get(&Key::Str(String::from("1"))), Some(&Value::Int(2)));
+    assert_eq!(dict.get(&Key::Char('1')), Some(&Value::Int(3)));
+    assert_eq!(dict.get(&Key::Bool(true)), Some(&Value::Int(4)));
+    assert_eq!(dict.get(&Key::Bool(false)), None);
+}
+
+#[test]
+fn insertion_order_survives_updates_and_removals() {
+    let mut dict = Dict::new();
+    for i in 0..5 {
+        dict.insert(Key::Int(i), Value::Int(i));
+    }
+
+    dict.insert(Key::Int(0), Value::Int(10));
+    assert_eq!(dict.remove(&Key::Int(1)), Some(Value::Int(1)));
+    assert_eq!(dict.remove(&Key::Int(1)), None);
+    dict.insert(Key::Int(1), Value::Int(1));
+
+    assert_eq!(
+        keys(&dict),
+        vec![
+            Key::Int(0),
+            Key::Int(2),
+            Key::Int(3),
+            Key::Int(4),
+            Key::Int(1)
+        ]
+    );
+    assert_eq!(dict.get(&Key::Int(0)), Some(&Value::Int(10)));
+    assert_eq!(dict.get(&Key::Int(4)), Some(&Value::Int(4)));
+}
+
+#[test]
+fn unhashable_values_are_not_keys() {
+    assert_eq!(Key::from_value(&Value::Int(1)), Some(Key::Int(1)));
+    assert_eq!(Key::from_value(&Value::Unit), None);
+    assert_eq!(Key::from_value(&Value::Range(0, 1)), None);
+}
@@ -0,0 +1,171 @@
+use crate::editor::{common_prefix, read_key, Display, History, Key, Line};
+use std::fs;
+
+fn keys(input: &str) -> Vec<Key> {
+    let mut input = input.as_bytes();
+    let mut keys = Vec::new();
+
+    while let Some(key) = read_key(&mut input).unwrap() {
+        keys.push(key);
+    }
+
+    keys
+}
+
+#[test]
+fn decode_keys() {
+    assert_eq!(
+        keys("aé→\r\t\x7f"),
+        vec![
+            Key::Char('a'),
+            Key::Char('é'),
+            Key::Char('→'),
+            Key::Enter,
+            Key::Tab,
+            Key::Backspace
+        ]
+    );
+    assert_eq!(
+        keys("\x1b[A\x1b[B\x1b[C\x1b[D\x1bOH\x1b[F\x1b[3~\x1b[1;5C\x1b[9~"),
+        vec![
+            Key::Up,
+            Key::Down,
+            Key::Right,
+            Key::Left,
+            Key::Home,
+            Key::End,
+            Key::Delete,
+            Key::Right,
+            Key::Unknown
+        ]
+    );
+    assert_eq!(
+        keys("\x01\x05\x03\x04\x0b\x15\x07"),
+        vec![
+            Key::Home,
+            Key::End,
+            Key::Interrupt,
+            Key::EndOfInput,
+            Key::KillToEnd,
+            Key::KillToStart,
+            Key::Unknown
+        ]
+    );
+}
+
+#[test]
+fn edit_line() {
+    let mut line = Line::new();
+    for c in "let x".chars() {
+        line.insert(c);
+    }
+
+    line.left();
+    line.left();
+    line.backspace();
+    assert_eq!(line.text(), "le x");
+    assert_eq!(line.cursor(), 2);
+
+    line.insert('t');
+    line.home();
+    line.delete();
+    line.end();
+    line.right();
+    assert_eq!(line.text(), "et x");
+    assert_eq!(line.cursor(), 4);
+
+    line.left();
+    line.kill_to_end();
+    assert_eq!(line.text(), "et ");
+
+    line.left();
+    line.kill_to_start();
+    assert_eq!(line.text(), " ");
+    assert_eq!(line.cursor(), 0);
+
+    line.set("List.re(xs)");
+    for _ in 0..4 {
+        line.left();
+    }
+    assert_eq!(line.before_cursor(), "List.re");
+    line.replace_before_cursor(5, "reverse");
+    assert_eq!(line.text(), "List.reverse(xs)");
+    assert_eq!(line.cursor(), 12);
+}
+
+#[test]
+fn redraw_wrapped_lines() {
+    let mut display = Display::new(10);
+    let mut line = Line::new();
+    let mut out = Vec::new();
+    let mut draw = |display: &mut Display, line: &Line| {
+        out.clear();
+        display.refresh("> ", line, &mut out).unwrap();
+        String::from_utf8(out.clone()).unwrap()
+    };
+
+    line.set("abcdefghijkl");
+    assert_eq!(draw(&mut display, &line), "\r\x1b[J> abcdefghijkl\r\x1b[4C");
+
+    line.home();
+    assert_eq!(
+        draw(&mut display, &line),
+        "\x1b[1A\r\x1b[J> abcdefghijkl\x1b[1A\r\x1b[2C"
+    );
+
+    line.set("abcdefgh");
+    assert_eq!(draw(&mut display, &line), "\r\x1b[J> abcdefgh\n\r");
+
+    line.left();
+    assert_eq!(
+        draw(&mut display, &line),
+        "\x1b[1A\r\x1b[J> abcdefgh\r\x1b[9C"
+    );
+
+    let mut out = Vec::new();
+    line.set("abcdefghijklmnopqrstuvwxyz");
+    line.home();
+    display.refresh("> ", &line, &mut out).unwrap();
+    out.clear();
+    display.finish("> ", &line, &mut out).unwrap();
+    assert_eq!(String::from_utf8(out).unwrap(), "\x1b[2B\n");
+}
+
+#[test]
+fn prefixes() {
+    let words = |words: &[&str]| -> Vec<String> { words.iter().map(|w| w.to_string()).collect() };
+
+    assert_eq!(common_prefix(&words(&["filter", "find", "fold"])), "f");
+    assert_eq!(common_prefix(&words(&["starts_with?", "start"])), "start");
+    assert_eq!(common_prefix(&words(&["éa", "éb"])), "é");
+    assert_eq!(common_prefix(&words(&["a", "b"])), "");
+    assert_eq!(common_prefix(&[]), "");
+}
+
+#[test]
+fn history_file() {
+    let path = std::env::temp_dir().join("slice_editor_history");
+    let _ = fs::remove_file(&path);
+
+    let mut history = History::load(Some(path.clone()));
+    assert!(history.entries().is_empty());
+
+    history.add("let x = 1");
+    history.add("let x = 1");
+    history.add("  ");
+    history.add("x");
+    assert_eq!(history.entries(), ["let x = 1", "x"]);
+
+    let history = History::load(Some(path.clone()));
+    assert_eq!(history.entries(), ["let x = 1", "x"]);
+
+    let lines: Vec<String> = (0..1005).map(|i| i.to_string()).collect();
+    fs::write(&path, lines.join("\n")).unwrap();
+
+    let history = History::load(Some(path.clone()));
+    assert_eq!(history.entries().len(), 1000);
+    assert_eq!(history.entries()[0], "5");
+    assert_eq!(fs::read_to_string(&path).unwrap().lines().count(), 1000);
+
+    fs::remove_file(&path).unwrap();
+}
@@ -0,0 +1,111 @@
+use crate::cst::{self, Element, NodeKind};
+use crate::format::format;
+
+fn formatted(input: &str) -> String {
+    format(input).unwrap()
+}
+
+#[test]
+fn tree_is_lossless() {
+    let input = "  let xs = [1 ,2] # list\n\nfn f(x):\n\tif x: [/x/] else: {a : x} end\nend\n";
+    let tree = cst::parse(input);
+
+    assert_eq!(tree.to_string(), input);
+    assert_eq!(cst::parse("let x = (1").to_string(), "let x = (1");
+}
+
+#[test]
+fn tree_nodes() {
+    let tree = cst::parse("let f = fn(x) -> x\nlet y: int = 1\nif y: [y] end");
+
+    let kinds: Vec<NodeKind> = tree
+        .children
+        .iter()
+        .filter_map(|child| match child {
+            Element::Node(node) => Some(node.kind),
+            Element::Token(_) => None,
+        })
+        .collect();
+    assert_eq!(kinds, [NodeKind::Group, NodeKind::Block]);
+
+    let block = match tree.children.last() {
+        Some(Element::Node(block)) => block,
+        _ => panic!("expected the if block last"),
+    };
+    assert_eq!(block.to_string(), " [y] end");
+}
+
+#[test]
+fn indentation() {
+    assert_eq!(
+        formatted("fn f(n):\nif n == 1:\n      \"one\"\n    elif n == 2:\n\"two\"\n else:\nmatch n:\n3 -> \"three\"\n_ -> \"many\"\nend\nend\nend\n"),
+        "fn f(n):\n  if n == 1:\n    \"one\"\n  elif n == 2:\n    \"two\"\n  else:\n    match n:\n      3 -> \"three\"\n      _ -> \"many\"\n    end\n  end\nend\n"
+    );
+    assert_eq!(
+        formatted("let xs = [\n1,\n      2\n  ]\n"),
+        "let xs = [\n  1,\n  2\n]\n"
+    );
+    assert_eq!(
+        formatted("List.map(xs, fn(x):\n      x * 2\n    end)\n"),
+        "List.map(xs, fn(x):\n  x * 2\nend)\n"
+    );
+    assert_eq!(
+        formatted("while True: break end\n"),
+        "while True: break end\n"
+    );
+}
+
+#[test]
+fn spacing() {
+    assert_eq!(formatted("let x=1+2*3"), "let x = 1 + 2 * 3\n");
+    assert_eq!(formatted("x=x-1"), "x = x - 1\n");
+    assert_eq!(formatted("let y = - x"), "let y = -x\n");
+    assert_eq!(formatted("let y = - 1"), "let y = - 1\n");
+    assert_eq!(formatted("let y = ! True"), "let y = !True\n");
+    assert_eq!(
+        formatted("xs->List.map(fn (x)->x*2)"),
+        "xs -> List.map(fn(x) -> x * 2)\n"
+    );
+    assert_eq!(formatted("f( a ,b )[ 0 ]"), "f(a, b)[0]\n");
+    assert_eq!(formatted("let r = xs[1 .. 3]"), "let r = xs[1..3]\n");
+    assert_eq!(formatted("let x : int = 1"), "let x: int = 1\n");
+    assert_eq!(
+        formatted("for x in [1,2]: puts x end"),
+        "for x in [1, 2]: puts x end\n"
+    );
+}
+
+#[test]
+fn collections() {
+    assert_eq!(formatted("[ 1 ,2,3 ]"), "[1, 2, 3]\n");
+    assert_eq!(formatted("[h|t]"), "[h | t]\n");
+    assert_eq!(formatted("{ ok : 1 ,error:2 }"), "{ok: 1, error: 2}\n");
+    assert_eq!(formatted("[/ 1,2 /]"), "[/1, 2/]\n");
+    assert_eq!(formatted("Dict.keys( { } )"), "Dict.keys({})\n");
+}
+
+#[test]
+fn comments_and_blank_lines() {
+    assert_eq!(
+        formatted("\n\n# top   \nlet x = 1    # one\n\n\n\nfn f():\n\n  # inside\n  x\nend"),
+        "# top\nlet x = 1 # one\n\nfn f():\n\n  # inside\n  x\nend\n"
+    );
+    assert_eq!(formatted(""), "");
+}
+
+#[test]
+fn idempotent() {
+    let input =
+        "fn add( a,b ):\nreturn a+b\nend\nlet t = List.fold([1,2], 0, fn(s, n):\n s+n\n end)\n";
+    let once = formatted(input);
+
+    assert_eq!(formatted(&once), once);
+}
+
+#[test]
+fn invalid_source() {
+    assert_eq!(
+        format("let = 1").unwrap_err(),
+        "Unexpected token expected pattern, got Assign 1.5"
+    );
+}
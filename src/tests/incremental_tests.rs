@@ -0,0 +1,221 @@
+use crate::incremental::{Damage, Program};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::token::TokenType;
+
+const PROGRAMS: &[&str] = &[
+    "fn fact(0): 1 end
+fn fact(n: int) -> int:
+  n * fact(n - 1)
+end
+
+let total = fact(5) # 120
+puts(total)
+",
+    "let words = [\"a\", \"b\"]; let count = 0
+for word in words:
+  if word == \"a\":
+    count = count + 1
+  elif word != \"b\":
+    break
+  else:
+    continue
+  end
+end
+let grid = [/1, 2, 3/]
+grid[0] = -1
+",
+    "fn describe(x):
+  match x:
+    0 -> \"zero\"
+    [head | tail] -> head
+    {name: n} -> n
+    _ -> \"other\"
+  end
+end
+
+let text = \"one
+two\"
+let add = fn(a, b) -> a + b
+while False:
+  puts('c')
+end
+",
+    "",
+];
+
+const FRAGMENTS: &[&str] = &[
+    "",
+    "\n",
+    "\n\n",
+    " ",
+    "\t",
+    "x",
+    "1",
+    "-1",
+    "- ",
+    "end",
+    "end\n",
+    ":",
+    ";",
+    "(",
+    ")",
+    "[",
+    "]",
+    "[/",
+    "/]",
+    "{",
+    "\"",
+    "'",
+    "#",
+    "# note\n",
+    "fn f(a):\n",
+    "let y = 2\n",
+    "if x:\n",
+    "else:\n",
+    "match x:\n  1 -> 2\n",
+    "->",
+    "é",
+];
+
+/// A xorshift generator, so the test needs no dependencies and every run
+/// tries the same edits.
+struct Random(u64);
+
+impl Random {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+}
+
+/// A random edit: a range of `text` on character boundaries and the text to
+/// put in its place.
+fn random_edit(random: &mut Random, text: &str) -> (usize, usize, String) {
+    let mut boundaries: Vec<usize> = text.char_indices().map(|(i, _)| i).collect();
+    boundaries.push(text.len());
+
+    let start = random.below(boundaries.len());
+    let end = (start + random.below(12)).min(boundaries.len() - 1);
+
+    let replacement = match random.below(4) {
+        // Something cut from the text itself, to move lines around.
+        0 => {
+            let from = random.below(boundaries.len());
+            let to = (from + random.below(30)).min(boundaries.len() - 1);
+            text[boundaries[from]..boundaries[to]].to_string()
+        }
+        _ => FRAGMENTS[random.below(FRAGMENTS.len())].to_string(),
+    };
+
+    (boundaries[start], boundaries[end], replacement)
+}
+
+/// The tokens, statements and errors of `text` parsed from scratch.
+fn from_scratch(text: &str) -> (String, String, Vec<String>) {
+    let mut lexer = Lexer::new(text);
+    let mut tokens = Vec::new();
+    loop {
+        let token = lexer.next();
+        let eof = token.token_type == TokenType::EOF;
+        tokens.push(token);
+        if eof {
+            break;
+        }
+    }
+
+    let (ast, errors) = Parser::new(Lexer::new(text)).parse_program_with_errors();
+
+    (format!("{:?}", tokens), format!("{:?}", ast), errors)
+}
+
+fn assert_matches_scratch(program: &Program, context: &str) {
+    let (tokens, ast, errors) = from_scratch(program.text());
+
+    assert_eq!(format!("{:?}", program.tokens()), tokens, "{}", context);
+    assert_eq!(format!("{:?}", program.ast()), ast, "{}", context);
+    assert_eq!(program.errors(), errors, "{}", context);
+}
+
+#[test]
+fn edits_match_a_full_reparse() {
+    for seed in 1..=60u64 {
+        let mut random = Random(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+        let source = PROGRAMS[random.below(PROGRAMS.len())];
+        let mut program = Program::new(source.to_string());
+        assert_matches_scratch(&program, source);
+
+        for step in 0..40 {
+            let (start, end, replacement) = random_edit(&mut random, program.text());
+            let before = program.text().to_string();
+            program.edit(start..end, &replacement);
+
+            let context = format!(
+                "seed {} step {}: replacing {}..{} of {:?} with {:?}",
+                seed, step, start, end, before, replacement
+            );
+            assert_matches_scratch(&program, &context);
+        }
+    }
+}
+
+#[test]
+fn edit_inside_a_function_reparses_it_alone() {
+    let mut program = Program::new(String::from(PROGRAMS[0]));
+
+    let offset = PROGRAMS[0].find("n - 1").unwrap();
+    let damage = program.edit(offset..offset + 1, "(n)");
+
+    assert_eq!(
+        damage,
+        Damage {
+            tokens: 11,
+            statements: 1
+        }
+    );
+    assert_matches_scratch(&program, "edit inside fact");
+}
+
+#[test]
+fn lines_added_above_move_the_statements_below() {
+    let mut program = Program::new(String::from(PROGRAMS[0]));
+
+    let damage = program.edit(0..0, "let first = 0\n\n");
+
+    assert_eq!(
+        damage,
+        Damage {
+            tokens: 6,
+            statements: 1
+        }
+    );
+    assert_matches_scratch(&program, "lines added above");
+
+    let clause = format!("{:?}", program.ast()[2]);
+    assert!(clause.contains("row: 4"));
+}
+
+#[test]
+fn unclosed_block_swallows_the_rest() {
+    let mut program = Program::new(String::from(PROGRAMS[0]));
+
+    let offset = PROGRAMS[0].find("end\n\n").unwrap();
+    let damage = program.edit(offset..offset + 3, "");
+
+    assert_eq!(damage.statements, 1);
+    assert_eq!(
+        program.errors(),
+        ["Unexpected end of input expected 'end' 8.1"]
+    );
+    assert_matches_scratch(&program, "unclosed block");
+
+    program.edit(offset..offset, "end");
+    assert!(program.errors().is_empty());
+    assert_matches_scratch(&program, "closed again");
+}
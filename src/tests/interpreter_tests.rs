@@ -0,0 +1,899 @@
+use crate::interpreter::{Interpreter, MAX_CALL_DEPTH, STACK_SIZE};
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::value::Value;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+use std::time::Duration;
+
+fn run(input: &str) -> Result<Value, String> {
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program()?;
+
+    Interpreter::new().run(&ast)
+}
+
+#[test]
+fn arithmetic_precedence() {
+    assert_eq!(run("1 + 2 * 3 - 4 / 2").unwrap(), Value::Int(5));
+    assert_eq!(run("(1 + 2) * 3").unwrap(), Value::Int(9));
+    assert_eq!(run("let n: int = 10\nn-1").unwrap(), Value::Int(9));
+}
+
+#[test]
+fn arithmetic_overflow() {
+    let max = "let max = 9223372036854775807\n";
+    let min = "let min = -9223372036854775807 - 1\n";
+
+    for (expression, col) in [
+        ("max + 1", 5),
+        ("min - 1", 5),
+        ("max * 2", 5),
+        ("min * -1", 5),
+        ("min / -1", 5),
+        ("min % -1", 5),
+        ("-min", 1),
+    ] {
+        let input = format!("{}{}{}", max, min, expression);
+        assert_eq!(
+            run(&input),
+            Err(format!("Integer overflow 3.{}", col)),
+            "{}",
+            expression
+        );
+    }
+
+    let input = format!("{}{}[max - 1 + 1, min + 1 - 1, max / -1, -max]", max, min);
+    assert_eq!(
+        run(&input).unwrap().to_string(),
+        "[9223372036854775807, -9223372036854775808, -9223372036854775807, -9223372036854775807]"
+    );
+
+    assert_eq!(run("1 / 0"), Err(String::from("Division by zero 1.3")));
+    assert_eq!(run("1 % 0"), Err(String::from("Division by zero 1.3")));
+}
+
+#[test]
+fn let_type_mismatch() {
+    assert!(run("let x: int = \"five\"").is_err());
+}
+
+#[test]
+fn reassign_same_type() {
+    let input = "let name: string = \"Brett\"
+    name = \"John\"
+    name";
+
+    assert_eq!(run(input).unwrap(), Value::Str(String::from("John")));
+
+    let input = "let last_name: string = \"Kolodny\"
+    last_name = True";
+
+    assert!(run(input).is_err());
+}
+
+#[test]
+fn if_elif_else() {
+    let input = "fn favorite_bread(bread: string) -> string:
+        if bread == \"rye\":
+            \"A good choice\"
+        elif bread == \"white\":
+            \"A bland choice\"
+        else:
+            \"No opinion\"
+        end
+    end
+    favorite_bread(\"white\")";
+
+    assert_eq!(
+        run(input).unwrap(),
+        Value::Str(String::from("A bland choice"))
+    );
+}
+
+#[test]
+fn function_implicit_and_explicit_return() {
+    let input = "fn language_is_awesome?(language: string) -> bool:
+        if language == \"slice\":
+            return True
+        end
+
+        False
+    end
+    language_is_awesome?(\"slice\")";
+
+    assert_eq!(run(input).unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn function_clauses_by_literal_and_arity() {
+    let input = "fn greet(name: string, greeting: string):
+        greeting + \" \" + name
+    end
+
+    fn greet(\"Brett\"):
+        \"Hey you\"
+    end
+
+    fn greet(name: string):
+        \"Hello \" + name
+    end
+
+    fn greet(_):
+        \"Huh, that's a strange name.\"
+    end
+    ";
+
+    let cases = [
+        ("greet(\"Ada\", \"Hi\")", "Hi Ada"),
+        ("greet(\"Brett\")", "Hey you"),
+        ("greet(\"Ada\")", "Hello Ada"),
+        ("greet(3)", "Huh, that's a strange name."),
+    ];
+
+    for (call, expected) in cases.iter() {
+        let program = format!("{}\n{}", input, call);
+        assert_eq!(run(&program).unwrap(), Value::Str(String::from(*expected)));
+    }
+}
+
+#[test]
+fn omitted_parens_and_pipe() {
+    let input = "fn add_one(num: int) -> int:
+        num + 1
+    end
+    fn add(a: int, b: int) -> int:
+        a + b
+    end
+    let four = add_one 3
+    four -> add_one -> add(10)";
+
+    assert_eq!(run(input).unwrap(), Value::Int(15));
+}
+
+#[test]
+fn guard_selects_clause() {
+    let input = "fn classify(n: int) when n > 100:
+        \"big\"
+    end
+
+    fn classify(n: int) -> string when n < 0:
+        \"negative\"
+    end
+
+    fn classify(_):
+        \"small\"
+    end
+    ";
+
+    let cases = [
+        ("classify(1000)", "big"),
+        ("classify(-5)", "negative"),
+        ("classify(5)", "small"),
+    ];
+
+    for (call, expected) in cases.iter() {
+        let program = format!("{}\n{}", input, call);
+        assert_eq!(run(&program).unwrap(), Value::Str(String::from(*expected)));
+    }
+}
+
+#[test]
+fn guard_sees_bindings_and_closure_scope() {
+    let input = "let limit: int = 3
+    fn over?(n: int) when n > limit:
+        True
+    end
+    fn over?(_):
+        False
+    end
+    over?(4) and !over?(3)";
+
+    assert_eq!(run(input).unwrap(), Value::Bool(true));
+}
+
+#[test]
+fn guard_must_be_bool() {
+    let input = "fn bad(n: int) when n:
+        n
+    end
+    bad(1)";
+
+    assert!(run(input).is_err());
+}
+
+#[test]
+fn no_matching_clause() {
+    let input = "fn only_ints(n: int) when n > 0:
+        n
+    end
+    only_ints(0)";
+
+    let error = run(input).unwrap_err();
+    assert!(error.starts_with("No clause of only_ints matches (int)"));
+}
+
+#[test]
+fn recursion() {
+    let input = "fn factorial(0):
+        1
+    end
+    fn factorial(n: int) -> int:
+        n * factorial(n - 1)
+    end
+    factorial(10)";
+
+    assert_eq!(run(input).unwrap(), Value::Int(3628800));
+}
+
+#[test]
+fn interrupted_evaluation() {
+    static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+    let run_interrupted = |input: &str| {
+        let ast = Parser::new(Lexer::new(input)).parse_program().unwrap();
+        let mut interpreter = Interpreter::new();
+        interpreter.interrupt_on(&INTERRUPTED);
+        interpreter.run(&ast)
+    };
+
+    let interrupt = thread::spawn(|| {
+        thread::sleep(Duration::from_millis(50));
+        INTERRUPTED.store(true, Ordering::Relaxed);
+    });
+    assert_eq!(
+        run_interrupted("while True:\n  1\nend"),
+        Err(String::from("Interrupted 1.1"))
+    );
+    interrupt.join().unwrap();
+
+    INTERRUPTED.store(true, Ordering::Relaxed);
+    assert_eq!(
+        run_interrupted("let total = 0\nfor i in 0..10:\n  total = total + i\nend"),
+        Err(String::from("Interrupted 2.1"))
+    );
+
+    INTERRUPTED.store(true, Ordering::Relaxed);
+    assert_eq!(
+        run_interrupted("fn double(n): n * 2 end\ndouble(2)"),
+        Err(String::from("Interrupted 2.1"))
+    );
+    assert!(!INTERRUPTED.load(Ordering::Relaxed));
+}
+
+#[test]
+fn recursion_past_the_depth_limit() {
+    let deep = thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(|| {
+            let down = "fn down(0): 0 end\nfn down(n): 1 + down(n - 1) end\n";
+            let within = run(&format!("{}down({})", down, MAX_CALL_DEPTH - 1));
+            let past = run(&format!("{}down({})", down, MAX_CALL_DEPTH));
+            let text = |result: Result<Value, String>| result.map(|value| value.to_string());
+            (text(within), text(past))
+        })
+        .unwrap();
+
+    let (within, past) = deep.join().unwrap();
+    assert_eq!(within, Ok((MAX_CALL_DEPTH - 1).to_string()));
+    assert_eq!(
+        past,
+        Err(format!(
+            "Stack overflow calling down, more than {} calls deep 2.17",
+            MAX_CALL_DEPTH
+        ))
+    );
+}
+
+#[test]
+fn let_list_tail_pattern() {
+    let input = "let [1, 2, | tl] = [1, 2, 3]
+    tl";
+
+    assert_eq!(
+        run(input).unwrap(),
+        Value::List(vec![Value::Int(3)].into_iter().collect())
+    );
+
+    assert!(run("let [1, x] = [2, 3]").is_err());
+}
+
+#[test]
+fn let_dict_pattern_with_pin() {
+    let input = "let awesome: bool = True
+    let {\"language\": language, \"is_awesome\": ^awesome} = {\"language\": \"Slice\", \"is_awesome\": True}
+    language";
+
+    assert_eq!(run(input).unwrap(), Value::Str(String::from("Slice")));
+
+    let input = "let awesome: bool = False
+    let {\"is_awesome\": ^awesome} = {is_awesome: True}";
+
+    assert!(run(input).is_err());
+}
+
+#[test]
+fn match_literals_and_wildcard() {
+    let input = "fn describe(value):
+        match value:
+            0 -> \"zero\"
+            \"rye\" -> \"bread\"
+            True -> \"yes\"
+            _ -> \"other\"
+        end
+    end
+    [describe(0), describe(\"rye\"), describe(True), describe('c')]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[\"zero\", \"bread\", \"yes\", \"other\"]"
+    );
+}
+
+#[test]
+fn match_list_shapes() {
+    let input = "fn sum(list) -> int:
+        match list:
+            [] -> 0
+            [head | tail] -> head + sum(tail)
+        end
+    end
+    sum([1, 2, 3, 4])";
+
+    assert_eq!(run(input).unwrap(), Value::Int(10));
+}
+
+#[test]
+fn match_dict_payload_with_guard() {
+    let input = "fn route(request):
+        match request:
+            {\"method\": \"GET\", \"path\": path} when path == \"/\" -> \"index\"
+            {method: \"GET\", path: path} -> \"get \" + path
+            {method: method} -> \"unsupported \" + method
+        end
+    end
+    [route({method: \"GET\", path: \"/\"}), route({method: \"GET\", path: \"/a\"}), route({method: \"PUT\"})]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[\"index\", \"get /a\", \"unsupported PUT\"]"
+    );
+}
+
+#[test]
+fn match_pin_and_typed_binding() {
+    let input = "let expected: int = 3
+    fn check(n):
+        match n:
+            ^expected -> \"expected\"
+            n: int -> \"int\"
+            _ -> \"not an int\"
+        end
+    end
+    [check(3), check(4), check(\"x\")]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[\"expected\", \"int\", \"not an int\"]"
+    );
+}
+
+#[test]
+fn match_non_exhaustive() {
+    let input = "match [1, 2]:
+        [] -> 0
+        [x] -> x
+    end";
+
+    let error = run(input).unwrap_err();
+    assert!(error.starts_with("No match for [1, 2]"));
+}
+
+#[test]
+fn while_loop() {
+    let input = "let i: int = 0
+    let total: int = 0
+    while i < 5:
+        i = i + 1
+        total = total + i
+    end
+    total";
+
+    assert_eq!(run(input).unwrap(), Value::Int(15));
+}
+
+#[test]
+fn for_over_list_with_break_and_continue() {
+    let input = "let total: int = 0
+    for n in [1, 2, 3, 4, 5, 6]:
+        if n == 2:
+            continue
+        end
+        if n > 4:
+            break
+        end
+        total = total + n
+    end
+    total";
+
+    assert_eq!(run(input).unwrap(), Value::Int(8));
+}
+
+#[test]
+fn for_over_dict_entries() {
+    let input = "let keys: string = \"\"
+    let total: int = 0
+    for [key, value] in {a: 1, b: 2, c: 3}:
+        keys = keys + key
+        total = total + value
+    end
+    [keys, total]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[\"abc\", 6]");
+}
+
+#[test]
+fn return_from_inside_loop() {
+    let input = "fn first_even(list):
+        for n in list:
+            if n % 2 == 0:
+                return n
+            end
+        end
+        -1
+    end
+    [first_even([1, 3, 4, 6]), first_even([1])]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[4, -1]");
+}
+
+#[test]
+fn for_over_non_iterable() {
+    let error = run("for x in 3:\n x\nend").unwrap_err();
+    assert!(error.starts_with("Cannot iterate over int"));
+}
+
+#[test]
+fn for_over_ranges() {
+    let input = "let total: int = 0
+    for i in 1..5:
+        total = total + i
+    end
+    for i in 1..=5:
+        total = total + i
+    end
+    total";
+
+    assert_eq!(run(input).unwrap(), Value::Int(25));
+}
+
+#[test]
+fn range_bounds_are_expressions() {
+    let input = "let n: int = 3
+    let count: int = 0
+    for _ in 0..n + 1:
+        count = count + 1
+    end
+    [count, 0..n, 5..1 == 5..1]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[4, 0..3, True]");
+}
+
+#[test]
+fn large_range_is_lazy() {
+    let input = "let found: int = 0
+    for i in 0..9223372036854775807:
+        if i == 3:
+            found = i
+            break
+        end
+    end
+    found";
+
+    assert_eq!(run(input).unwrap(), Value::Int(3));
+}
+
+#[test]
+fn string_index_and_slice_by_chars() {
+    let input = "let word: string = \"héllo wörld\"
+    [word[1], word[0..5], word[6..=10], word[3..3]]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "['é', \"héllo\", \"wörld\", \"\"]"
+    );
+}
+
+#[test]
+fn string_index_out_of_bounds() {
+    let error = run("\"abc\"[3]").unwrap_err();
+    assert!(error.starts_with("Index 3 out of bounds for string"));
+
+    let error = run("\"abc\"[1..4]").unwrap_err();
+    assert!(error.starts_with("Index 1..4 out of bounds for string"));
+}
+
+#[test]
+fn anonymous_function_call() {
+    let input = "let add_one = fn(x) -> x + 1
+    let double = fn(x: int):
+        let doubled: int = x * 2
+        doubled
+    end
+    [add_one(1), double(4), (fn(a, b) -> a - b)(5, 3), 2 -> add_one]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[2, 8, 2, 3]");
+}
+
+#[test]
+fn closures_capture_by_value() {
+    let input = "let n: int = 1
+    let add_n = fn(x) -> x + n
+    n = 100
+    add_n(1)";
+
+    assert_eq!(run(input).unwrap(), Value::Int(2));
+}
+
+#[test]
+fn closures_returned_from_clauses() {
+    let input = "fn make_adder(0):
+        fn(x) -> x
+    end
+    fn make_adder(n: int):
+        fn(x) -> x + n
+    end
+    let add_five = make_adder(5)
+    [add_five(1), make_adder(0)(1), make_adder(2)(make_adder(3)(0))]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[6, 1, 5]");
+}
+
+#[test]
+fn closures_passed_to_clauses() {
+    let input = "fn apply_twice(f, x):
+        f(f(x))
+    end
+    apply_twice(fn(x) -> x * 3, 2)";
+
+    assert_eq!(run(input).unwrap(), Value::Int(18));
+}
+
+#[test]
+fn local_closures_call_themselves() {
+    let input = "fn count_down(start):
+        let g = fn(n): if n == 0: [] else: [n | g(n - 1)] end end
+        g(start)
+    end
+    fn shadowed():
+        let g = 1
+        let g = fn(n) -> if n == 0: g else: g(n - 1) end
+        g(2)
+    end
+    [count_down(3), shadowed()]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[[3, 2, 1], <fn>]");
+}
+
+#[test]
+fn closure_sees_later_globals() {
+    let input = "let call_helper = fn() -> helper()
+    fn helper():
+        \"late\"
+    end
+    call_helper()";
+
+    assert_eq!(run(input).unwrap(), Value::Str(String::from("late")));
+}
+
+#[test]
+fn closure_patterns_and_display() {
+    let input = "let head = fn([h | _]) -> h
+    [head([1, 2]), head]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[1, <fn>]");
+
+    let error = run("(fn([h | _]) -> h)([])").unwrap_err();
+    assert!(error.starts_with("No clause of fn matches (list)"));
+}
+
+#[test]
+fn function_references() {
+    let input = "fn add_one(n: int):
+        n + 1
+    end
+    fn map_pair([a, b], f):
+        [f(a), f(b)]
+    end
+    let increment = add_one
+    [[1, 2] -> map_pair(add_one), increment(1), add_one]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[[2, 3], 2, <fn add_one>]");
+}
+
+#[test]
+fn partial_application() {
+    let input = "fn add(a, b, c):
+        a + b + c
+    end
+    let add_one = add(1)
+    let add_three = add_one(2)
+    [add_one(2, 3), add_three(4), 10 -> add(20, 30), add(1)(2)(3), add_one]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[6, 7, 60, 6, <fn add(1, ..)>]"
+    );
+}
+
+#[test]
+fn partial_application_respects_arity_overloads() {
+    let input = "fn greet(name):
+        \"Hello \" + name
+    end
+    fn greet(greeting, name, punctuation):
+        greeting + \" \" + name + punctuation
+    end
+    let hi = greet(\"Hi\", \"Brett\")
+    [greet(\"Brett\"), hi(\"!\")]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[\"Hello Brett\", \"Hi Brett!\"]"
+    );
+}
+
+#[test]
+fn missing_and_extra_arguments() {
+    let input = "fn add(a, b):
+        a + b
+    end
+    add()";
+
+    assert_eq!(run(input).unwrap_err(), "add takes 2 arguments, got 0 4.5");
+
+    let input = "fn f(a):
+        a
+    end
+    fn f(a, b, c):
+        a
+    end
+    f(1, 2, 3, 4)";
+
+    assert_eq!(
+        run(input).unwrap_err(),
+        "f takes 1 or 3 arguments, got 4 7.5"
+    );
+}
+
+#[test]
+fn prepending_to_lists() {
+    let input = "let tail = [3]
+    let [first | rest] = [1, 2 | tail]
+    [[0 | []], [first | rest], rest, [[] | tail]]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[[0], [1, 2, 3], [2, 3], [[], 3]]"
+    );
+
+    assert_eq!(run("[1 | 2]").unwrap_err(), "Cannot prepend to int 1.1");
+}
+
+#[test]
+fn building_long_lists() {
+    let input = "let numbers = []
+    for i in 0..100000:
+        numbers = [i | numbers]
+    end
+    let [last, second_last | _] = numbers
+    [last, second_last]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[99999, 99998]");
+}
+
+#[test]
+fn array_index_read_and_write() {
+    let input = "let numbers = [/1, 2, 3/]
+    numbers[1] = 5
+    numbers[0] = numbers[1] + numbers[2]
+    [numbers, numbers[0], numbers[1..3], Array.length(numbers)]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[[/8, 5, 3/], 8, [/5, 3/], 3]"
+    );
+}
+
+#[test]
+fn array_push_and_pop() {
+    let input = "let stack = [/ /]
+    for i in 1..=3:
+        stack -> Array.push(i * 10)
+    end
+    let top = Array.pop(stack)
+    [top, stack, Array.make(2, 'a')]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[30, [/10, 20/], [/'a', 'a'/]]"
+    );
+}
+
+#[test]
+fn array_from_sequences() {
+    let input = "let numbers = [/1, 2/]
+    let copied = Array.from(numbers)
+    Array.push(copied, 3)
+    [Array.from([1, 2]), Array.from(1..=3), numbers, copied]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[[/1, 2/], [/1, 2, 3/], [/1, 2/], [/1, 2, 3/]]"
+    );
+    assert_eq!(
+        run("Array.from(3)").unwrap_err(),
+        "Array.from expected list, range or array, got int 1.7"
+    );
+}
+
+#[test]
+fn arrays_are_passed_by_reference() {
+    let input = "fn zero_first(array):
+        array[0] = 0
+    end
+    let numbers = [/1, 2/]
+    let alias = numbers
+    let copied = Array.copy(numbers)
+    zero_first(numbers)
+    [numbers, alias, copied, [/[/1/]/][0] == [/1/]]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[[/0, 2/], [/0, 2/], [/1, 2/], True]"
+    );
+}
+
+#[test]
+fn arrays_holding_themselves() {
+    let input = "let a = [/1/]
+    let b = [/1/]
+    Array.push(a, a)
+    Array.push(b, [b])
+    let c = [/1/]
+    Array.push(c, c)
+    [a, b, a == a, a == c, a == b]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[[/1, [/.../]/], [/1, [[/.../]]/], True, True, False]"
+    );
+}
+
+#[test]
+fn array_errors() {
+    let input = "let numbers = [/1, 2/]
+    numbers[2]";
+    assert_eq!(
+        run(input).unwrap_err(),
+        "Index 2 out of bounds for array 2.12"
+    );
+
+    let input = "let numbers = [/1, 2/]
+    numbers[-1] = 3";
+    assert_eq!(
+        run(input).unwrap_err(),
+        "Index -1 out of bounds for array 2.12"
+    );
+
+    assert_eq!(
+        run("Array.pop([/ /])").unwrap_err(),
+        "Cannot pop from an empty array 1.7"
+    );
+    assert_eq!(
+        run("Array.push([1], 2)").unwrap_err(),
+        "Array.push expected array, got list 1.7"
+    );
+    assert_eq!(
+        run("let letters = [1]\nletters[0] = 2").unwrap_err(),
+        "Cannot assign to list index int 2.8"
+    );
+    assert_eq!(
+        run("Array.shuffle").unwrap_err(),
+        "Array has no function shuffle 1.7"
+    );
+}
+
+#[test]
+fn dict_dot_access() {
+    let input = "let language = {name: \"Slice\", awesome: True}
+    [language.name, language.awesome]";
+
+    assert_eq!(run(input).unwrap().to_string(), "[\"Slice\", True]");
+
+    assert_eq!(
+        run("{name: 1}.version").unwrap_err(),
+        "Key \"version\" not found 1.11"
+    );
+}
+
+#[test]
+fn dict_keys_of_any_hashable_type() {
+    let input =
+        "let dict = {\"language\": \"Slice\", True: \"Is awesome\", 1: 'a', 'b': 2, language: 3}
+    [dict[True], dict[1], dict['b'], dict.language, dict]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[\"Is awesome\", 'a', 2, 3, {\"language\": 3, True: \"Is awesome\", 1: 'a', 'b': 2}]"
+    );
+}
+
+#[test]
+fn dict_module() {
+    let input = "let stock = {bread: 2, milk: 0}
+    let restocked = stock -> Dict.put(\"milk\", 5) -> Dict.put(\"eggs\", 12)
+    let merged = Dict.merge(restocked, {bread: 1, jam: 3})
+    [
+        Dict.get(restocked, \"milk\"),
+        stock,
+        Dict.delete(merged, \"milk\"),
+        Dict.keys(merged),
+        Dict.values(merged),
+        Dict.has_key?(stock, \"eggs\"),
+        Dict.has_key?(restocked, \"eggs\"),
+    ]";
+
+    assert_eq!(
+        run(input).unwrap().to_string(),
+        "[{\"ok\": 5}, {\"bread\": 2, \"milk\": 0}, {\"bread\": 1, \"eggs\": 12, \"jam\": 3}, \
+         [\"bread\", \"milk\", \"eggs\", \"jam\"], [1, 5, 12, 3], False, True]"
+    );
+}
+
+#[test]
+fn dict_equality_ignores_order() {
+    assert_eq!(
+        run("{a: 1, b: 2} == {b: 2, a: 1}").unwrap(),
+        Value::Bool(true)
+    );
+    assert_eq!(run("{a: 1} == {a: 1, b: 2}").unwrap(), Value::Bool(false));
+}
+
+#[test]
+fn unhashable_dict_keys() {
+    assert_eq!(
+        run("{[1]: 2}").unwrap_err(),
+        "Cannot use list as a dict key 1.2"
+    );
+    assert_eq!(
+        run("Dict.put({}, [/1/], 2)").unwrap_err(),
+        "Cannot use array as a dict key 1.6"
+    );
+    assert_eq!(
+        run("let dict = {a: 1}\ndict[0..1]").unwrap_err(),
+        "Cannot use range as a dict key 2.5"
+    );
+    assert_eq!(
+        run("Dict.get({a: 1}, [1])").unwrap_err(),
+        "Cannot use list as a dict key 1.6"
+    );
+}
+
+#[test]
+fn dict_get_returns_a_result() {
+    assert_eq!(
+        run("Dict.get({a: 1}, 'a')").unwrap().to_string(),
+        "{\"error\": \"Key 'a' not found\"}"
+    );
+    assert_eq!(
+        run("match Dict.get({a: 1}, \"a\"):\n  {ok: n} -> n\n  {error: _} -> 0\nend").unwrap(),
+        Value::Int(1)
+    );
+    assert_eq!(
+        run("let prices = {tea: 3}\nprices[\"coffee\"]").unwrap_err(),
+        "Key \"coffee\" not found 2.7"
+    );
+}
@@ -0,0 +1,115 @@
+use crate::json::{decode, encode, JsonError};
+use crate::value::Value;
+use std::cell::RefCell;
+use std::rc::Rc;
+
+fn round_trip(text: &str) -> String {
+    encode(&decode(text).unwrap(), false).unwrap()
+}
+
+#[test]
+fn decodes_scalars() {
+    assert_eq!(decode("42").unwrap(), Value::Int(42));
+    assert_eq!(decode("-0").unwrap(), Value::Int(0));
+    assert_eq!(decode("1.5").unwrap(), Value::Float(1.5));
+    assert_eq!(decode("2e3").unwrap(), Value::Float(2000.0));
+    assert_eq!(
+        decode("123456789012345678901234567890").unwrap(),
+        Value::Float(1.2345678901234568e29)
+    );
+    assert_eq!(decode(" true ").unwrap(), Value::Bool(true));
+    assert_eq!(decode("null").unwrap(), Value::Unit);
+    assert_eq!(
+        decode(r#""tab\t \"quoted\" é 🍞""#).unwrap(),
+        Value::Str(String::from("tab\t \"quoted\" é 🍞"))
+    );
+}
+
+#[test]
+fn decodes_collections_in_order() {
+    let value = decode(r#"{"z": 1, "a": [true, null, {}], "m": {"x": []}}"#).unwrap();
+
+    assert_eq!(
+        value.to_string(),
+        "{\"z\": 1, \"a\": [True, (), {}], \"m\": {\"x\": []}}"
+    );
+}
+
+#[test]
+fn round_trips_key_order() {
+    let text = r#"{"zebra":1,"apple":{"b":2.5,"a":"\n"},"list":[1,"two",false,null]}"#;
+
+    assert_eq!(round_trip(text), text);
+}
+
+#[test]
+fn pretty_printing() {
+    let value =
+        decode(r#"{"name": "Slice", "tags": ["fast", "fun"], "meta": {}, "empty": []}"#).unwrap();
+
+    assert_eq!(
+        encode(&value, true).unwrap(),
+        "{\n  \"name\": \"Slice\",\n  \"tags\": [\n    \"fast\",\n    \"fun\"\n  ],\n  \
+         \"meta\": {},\n  \"empty\": []\n}"
+    );
+}
+
+#[test]
+fn errors_have_positions() {
+    let error = |text: &str| decode(text).unwrap_err();
+
+    assert_eq!(
+        error("{\n  \"a\": 1,\n  \"b\" 2\n}"),
+        JsonError {
+            message: String::from("Expected ':', got '2'"),
+            line: 3,
+            col: 7,
+        }
+    );
+    assert_eq!(
+        error("[1, 2").to_string(),
+        "Expected ',' or ']', got end of input at line 1, column 6"
+    );
+    assert_eq!(
+        error("[1,]").to_string(),
+        "Expected a value, got ']' at line 1, column 4"
+    );
+    assert_eq!(
+        error("{1: 2}").to_string(),
+        "Expected a string key, got '1' at line 1, column 2"
+    );
+    assert_eq!(
+        error("nul").to_string(),
+        "Expected null at line 1, column 1"
+    );
+    assert_eq!(
+        error("01").to_string(),
+        "Unexpected '1' after the value at line 1, column 2"
+    );
+    assert_eq!(
+        error("\"abc").to_string(),
+        "Unterminated string at line 1, column 5"
+    );
+    assert_eq!(
+        error(&"[".repeat(1000)).to_string(),
+        "Nested more than 512 levels deep at line 1, column 513"
+    );
+}
+
+#[test]
+fn unencodable_values() {
+    assert_eq!(
+        encode(&Value::Range(0, 1), false).unwrap_err(),
+        "Cannot encode range as JSON"
+    );
+
+    let dict = decode("{}").unwrap();
+    assert_eq!(encode(&dict, true).unwrap(), "{}");
+
+    let array = Rc::new(RefCell::new(vec![Value::Int(1)]));
+    array.borrow_mut().push(Value::Array(Rc::clone(&array)));
+    assert_eq!(
+        encode(&Value::Array(array), false).unwrap_err(),
+        "Cannot encode an array holding itself as JSON"
+    );
+}
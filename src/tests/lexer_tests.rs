@@ -228,11 +228,17 @@ fn string_empty() {
 #[test]
 fn string_within_string() {
     let input = "\"\"\"";
-    let expected = Str(String::from("\""));
+    let expected = [Str(String::from("")), Illegal];
 
     let mut lexer = Lexer::new(input);
 
-    assert_eq!(lexer.next().token_type, expected);
+    for token_type in expected {
+        assert_eq!(lexer.next().token_type, token_type);
+    }
+
+    let mut lexer = Lexer::new("'\"'");
+
+    assert_eq!(lexer.next().token_type, Character('"'));
 }
 
 #[test]
@@ -558,3 +564,119 @@ fn column_character() {
         assert_eq!(token.col, expected[i]);
     }
 }
+
+#[test]
+fn ranges() {
+    let input = "1..10 x..=y a.b";
+
+    let expected = [
+        Int(1),
+        Range,
+        Int(10),
+        Identity(String::from("x")),
+        RangeInclusive,
+        Identity(String::from("y")),
+        Identity(String::from("a")),
+        Dot,
+        Identity(String::from("b")),
+    ];
+
+    let mut lexer = Lexer::new(input);
+
+    for expected_token in expected.iter() {
+        let token = lexer.next();
+        assert_eq!(token.token_type, *expected_token);
+    }
+}
+
+#[test]
+fn unicode_character() {
+    let input = "'é' 'ß' '語' 'éa'";
+
+    let expected = [Character('é'), Character('ß'), Character('語'), Illegal];
+
+    let mut lexer = Lexer::new(input);
+
+    for expected_token in expected.iter() {
+        let token = lexer.next();
+        assert_eq!(token.token_type, *expected_token);
+    }
+}
+
+#[test]
+fn trivia() {
+    let input = "let x = -1 # one\n\tx";
+    let expected = [
+        (Let, "let"),
+        (Whitespace, " "),
+        (Identity(String::from("x")), "x"),
+        (Whitespace, " "),
+        (Assign, "="),
+        (Whitespace, " "),
+        (Int(-1), "-1"),
+        (Whitespace, " "),
+        (Comment, "# one"),
+        (NewLine, "\n"),
+        (Whitespace, "\t"),
+        (Identity(String::from("x")), "x"),
+        (EOF, ""),
+    ];
+
+    let mut lexer = Lexer::with_trivia(input);
+
+    for (expected_token, expected_text) in expected.iter() {
+        let start = lexer.offset();
+        let token = lexer.next();
+        assert_eq!(token.token_type, *expected_token);
+        assert_eq!(&input[start..lexer.offset()], *expected_text);
+    }
+}
+
+#[test]
+fn trivia_keeps_operand_context() {
+    let input = "x -1";
+    let expected = [Identity(String::from("x")), Whitespace, Minus, Int(1)];
+
+    let mut lexer = Lexer::with_trivia(input);
+
+    for expected_token in expected.iter() {
+        let token = lexer.next();
+        assert_eq!(token.token_type, *expected_token);
+    }
+}
+
+#[test]
+fn column_after_strings_and_arrays() {
+    let input = "puts(\"hi\", x) [/1/] fn(a) -> a";
+
+    let expected = [1, 5, 6, 10, 12, 13, 15, 17, 18, 21, 23, 24, 25, 27, 30];
+
+    let mut lexer = Lexer::new(input);
+
+    for expected_token in expected.iter() {
+        let token = lexer.next();
+        assert_eq!(token.col, *expected_token);
+    }
+}
+
+#[test]
+fn ints_out_of_range_are_illegal() {
+    let input =
+        "9223372036854775807 99999999999999999999 -9223372036854775808 x -99999999999999999999";
+    let expected = [
+        Int(isize::MAX),
+        Illegal,
+        Int(isize::MIN),
+        Identity(String::from("x")),
+        Minus,
+        Illegal,
+        EOF,
+    ];
+
+    let mut lexer = Lexer::new(input);
+
+    for expected_token in expected.iter() {
+        let token = lexer.next();
+        assert_eq!(token.token_type, *expected_token);
+    }
+}
@@ -0,0 +1,122 @@
+use crate::diagnostic::{Diagnostic, Severity};
+use crate::lexer::Lexer;
+use crate::lint::{lint, Level, LintConfig};
+use crate::parser::Parser;
+
+fn lint_with(input: &str, predicate_names: Level) -> Vec<Diagnostic> {
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    lint(&ast, &LintConfig { predicate_names })
+}
+
+#[test]
+fn predicate_must_return_bool() {
+    let input = "fn valid?(n: int) -> int:
+        n
+    end";
+
+    let found = lint_with(input, Level::Warn);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].to_string(),
+        "warning: Predicate valid? should return bool 1.4"
+    );
+}
+
+#[test]
+fn bool_function_needs_question_mark() {
+    let input = "fn is_even(n: int) -> bool:
+        n % 2 == 0
+    end";
+
+    let found = lint_with(input, Level::Warn);
+
+    assert_eq!(found.len(), 1);
+    assert_eq!(
+        found[0].message,
+        "Function is_even returns bool and should be named is_even?"
+    );
+}
+
+#[test]
+fn inferred_from_returned_expressions() {
+    let input = "fn positive(n):
+        if n > 0:
+            return True
+        end
+        False
+    end
+
+    fn name?(n):
+        match n:
+            0 -> \"zero\"
+            _ -> \"other\"
+        end
+    end
+
+    fn unknown(n):
+        n
+    end
+
+    fn even?(n):
+        n % 2 == 0
+    end";
+
+    let found = lint_with(input, Level::Warn);
+    let messages: Vec<&str> = found.iter().map(|d| d.message.as_str()).collect();
+
+    assert_eq!(
+        messages,
+        [
+            "Function positive returns bool and should be named positive?",
+            "Predicate name? should return bool"
+        ]
+    );
+}
+
+#[test]
+fn reported_once_per_function() {
+    let input = "fn flag(0) -> bool:
+        False
+    end
+    fn flag(_) -> bool:
+        True
+    end";
+
+    assert_eq!(lint_with(input, Level::Warn).len(), 1);
+}
+
+#[test]
+fn levels() {
+    let input = "fn check(n) -> bool:
+        True
+    end";
+
+    assert!(lint_with(input, Level::Allow).is_empty());
+    assert_eq!(lint_with(input, Level::Deny)[0].severity, Severity::Error);
+    assert_eq!(lint_with(input, Level::Warn)[0].severity, Severity::Warning);
+
+    assert_eq!("deny".parse::<Level>(), Ok(Level::Deny));
+    assert!("loud".parse::<Level>().is_err());
+}
+
+#[test]
+fn settings() {
+    let mut config = LintConfig::default();
+    config.parse_setting("predicate-names=deny").unwrap();
+    assert_eq!(config.predicate_names, Level::Deny);
+
+    assert_eq!(
+        config.parse_setting("predicate-names"),
+        Err(String::from("Expected lint=level, got predicate-names"))
+    );
+    assert_eq!(
+        config.parse_setting("unused=allow"),
+        Err(String::from("Unknown lint unused"))
+    );
+    assert!(config.parse_setting("predicate-names=loud").is_err());
+}
@@ -0,0 +1,52 @@
+use crate::list::List;
+use crate::value::Value;
+
+fn ints(values: &[isize]) -> List {
+    values.iter().map(|i| Value::Int(*i)).collect()
+}
+
+#[test]
+fn prepend_shares_the_tail() {
+    let tail = ints(&[2, 3]);
+    let list = tail.prepend(Value::Int(1));
+
+    assert_eq!(list, ints(&[1, 2, 3]));
+    assert_eq!(list.len(), 3);
+    assert_eq!(tail.len(), 2);
+    assert!(list.tail().unwrap().ptr_eq(&tail));
+}
+
+#[test]
+fn head_tail_and_nth() {
+    let list = ints(&[1, 2, 3]);
+
+    assert_eq!(list.head(), Some(&Value::Int(1)));
+    assert_eq!(list.tail().unwrap(), &ints(&[2, 3]));
+    assert_eq!(list.nth(2), Some(&Value::Int(3)));
+    assert_eq!(list.nth(3), None);
+    assert!(list.skip(3).unwrap().is_empty());
+    assert!(list.skip(4).is_none());
+    assert!(List::new().tail().is_none());
+}
+
+#[test]
+fn equality_compares_elements() {
+    assert_eq!(ints(&[1, 2]), ints(&[1, 2]));
+    assert_ne!(ints(&[1, 2]), ints(&[1]));
+    assert_ne!(ints(&[1, 2]), ints(&[2, 1]));
+    assert_eq!(List::new(), ints(&[]));
+}
+
+#[test]
+fn dropping_a_long_list_does_not_overflow() {
+    let mut list = List::new();
+    for i in 0..1_000_000 {
+        list = list.prepend(Value::Int(i));
+    }
+
+    let shared = list.skip(500_000).unwrap().clone();
+    drop(list);
+
+    assert_eq!(shared.len(), 500_000);
+    assert_eq!(shared.head(), Some(&Value::Int(499_999)));
+}
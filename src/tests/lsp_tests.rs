@@ -0,0 +1,552 @@
+use crate::json;
+use crate::lsp::{get, serve, Server};
+use crate::value::Value;
+use std::fs;
+
+const URI: &str = "file:///main.slice";
+
+const SOURCE: &str = "fn fact(0): 1 end
+fn fact(n: int) -> int:
+  n * fact(n - 1)
+end
+
+let total = fact(3)
+puts(\"hi\", total)
+List.map([1], fact)
+";
+
+fn initialized() -> Server {
+    let mut server = Server::default();
+    server.handle(r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#);
+    server
+}
+
+fn open(server: &mut Server, text: &str) -> Vec<Value> {
+    open_at(server, URI, text)
+}
+
+fn open_at(server: &mut Server, uri: &str, text: &str) -> Vec<Value> {
+    let message = format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didOpen", "params": {{"textDocument": {{"uri": "{}", "languageId": "slice", "version": 1, "text": {}}}}}}}"#,
+        uri,
+        json::encode(&Value::Str(text.to_string()), false).unwrap()
+    );
+    server.handle(&message)
+}
+
+/// The result of a request at a 0-based line and character.
+fn request(server: &mut Server, method: &str, line: usize, character: usize) -> Value {
+    request_at(server, URI, method, line, character)
+}
+
+fn request_at(
+    server: &mut Server,
+    uri: &str,
+    method: &str,
+    line: usize,
+    character: usize,
+) -> Value {
+    let message = format!(
+        r#"{{"jsonrpc": "2.0", "id": 2, "method": "{}", "params": {{"textDocument": {{"uri": "{}"}}, "position": {{"line": {}, "character": {}}}}}}}"#,
+        method, uri, line, character
+    );
+    let responses = server.handle(&message);
+
+    assert_eq!(responses.len(), 1);
+    get(&responses[0], &["result"]).unwrap().clone()
+}
+
+fn items(value: &Value) -> Vec<Value> {
+    match value {
+        Value::List(list) => list.iter().cloned().collect(),
+        _ => panic!("expected a list, got {}", value.repr()),
+    }
+}
+
+fn text(value: &Value, path: &[&str]) -> String {
+    match get(value, path) {
+        Some(Value::Str(s)) => s.clone(),
+        other => panic!("expected a string at {:?}, got {:?}", path, other),
+    }
+}
+
+fn int(value: &Value, path: &[&str]) -> isize {
+    match get(value, path) {
+        Some(Value::Int(i)) => *i,
+        other => panic!("expected an int at {:?}, got {:?}", path, other),
+    }
+}
+
+fn framed(messages: &[&str]) -> Vec<u8> {
+    messages
+        .iter()
+        .flat_map(|message| {
+            format!("Content-Length: {}\r\n\r\n{}", message.len(), message).into_bytes()
+        })
+        .collect()
+}
+
+#[test]
+fn initialize_advertises_capabilities() {
+    let mut server = Server::default();
+    let responses =
+        server.handle(r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#);
+
+    assert_eq!(int(&responses[0], &["id"]), 1);
+    let capabilities = get(&responses[0], &["result", "capabilities"]).unwrap();
+    assert_eq!(int(capabilities, &["textDocumentSync"]), 2);
+    assert_eq!(
+        get(capabilities, &["hoverProvider"]),
+        Some(&Value::Bool(true))
+    );
+    assert_eq!(
+        get(capabilities, &["definitionProvider"]),
+        Some(&Value::Bool(true))
+    );
+}
+
+#[test]
+fn requests_before_initialize_fail() {
+    let mut server = Server::default();
+    let responses = server.handle(r#"{"jsonrpc": "2.0", "id": 1, "method": "shutdown"}"#);
+
+    assert_eq!(int(&responses[0], &["error", "code"]), -32002);
+
+    let responses = server.handle("{not json");
+    assert_eq!(int(&responses[0], &["error", "code"]), -32700);
+}
+
+#[test]
+fn unknown_methods() {
+    let mut server = initialized();
+
+    let responses = server.handle(r#"{"jsonrpc": "2.0", "id": 3, "method": "workspace/symbol"}"#);
+    assert_eq!(int(&responses[0], &["error", "code"]), -32601);
+
+    let responses = server.handle(r#"{"jsonrpc": "2.0", "method": "$/cancelRequest"}"#);
+    assert!(responses.is_empty());
+}
+
+#[test]
+fn publishes_diagnostics() {
+    let mut server = initialized();
+
+    let published = open(
+        &mut server,
+        "let = 2\nfn positive(n) -> bool:\n  n > 0\nend\n",
+    );
+    assert_eq!(published.len(), 1);
+    assert_eq!(
+        text(&published[0], &["method"]),
+        "textDocument/publishDiagnostics"
+    );
+
+    let diagnostics = items(get(&published[0], &["params", "diagnostics"]).unwrap());
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(int(&diagnostics[0], &["severity"]), 1);
+    assert_eq!(int(&diagnostics[0], &["range", "start", "line"]), 0);
+    assert_eq!(int(&diagnostics[0], &["range", "start", "character"]), 4);
+    assert_eq!(int(&diagnostics[1], &["severity"]), 2);
+    assert_eq!(int(&diagnostics[1], &["range", "start", "line"]), 1);
+    assert_eq!(int(&diagnostics[1], &["range", "start", "character"]), 3);
+    assert_eq!(int(&diagnostics[1], &["range", "end", "character"]), 11);
+
+    let change = format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didChange", "params": {{"textDocument": {{"uri": "{}", "version": 2}}, "contentChanges": [{{"text": "let y = 1\nputs(y)\n"}}]}}}}"#,
+        URI
+    );
+    let published = server.handle(&change);
+    assert!(items(get(&published[0], &["params", "diagnostics"]).unwrap()).is_empty());
+
+    let close = format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didClose", "params": {{"textDocument": {{"uri": "{}"}}}}}}"#,
+        URI
+    );
+    let published = server.handle(&close);
+    assert!(items(get(&published[0], &["params", "diagnostics"]).unwrap()).is_empty());
+}
+
+#[test]
+fn lint_levels_from_initialization_options() {
+    let source = "fn positive(n) -> bool:\n  n > 0\nend\n";
+    let severities = |options: &str| {
+        let mut server = Server::default();
+        let responses = server.handle(&format!(
+            r#"{{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {{"initializationOptions": {}}}}}"#,
+            options
+        ));
+        if get(&responses[0], &["error"]).is_some() {
+            return Err(text(&responses[0], &["error", "message"]));
+        }
+
+        let published = open(&mut server, source);
+        let diagnostics = items(get(&published[0], &["params", "diagnostics"]).unwrap());
+        Ok(diagnostics
+            .iter()
+            .map(|diagnostic| int(diagnostic, &["severity"]))
+            .collect::<Vec<_>>())
+    };
+
+    assert_eq!(severities("{}"), Ok(vec![2]));
+    assert_eq!(
+        severities(r#"{"lint": {"predicate-names": "deny"}}"#),
+        Ok(vec![1])
+    );
+    assert_eq!(
+        severities(r#"{"lint": {"predicate-names": "allow"}}"#),
+        Ok(vec![])
+    );
+    assert_eq!(
+        severities(r#"{"lint": {"unused": "deny"}}"#),
+        Err(String::from("Unknown lint unused"))
+    );
+}
+
+#[test]
+fn ints_out_of_range_are_diagnostics() {
+    let mut server = initialized();
+
+    let published = open(&mut server, "let big = 99999999999999999999\n");
+    let diagnostics = items(get(&published[0], &["params", "diagnostics"]).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(int(&diagnostics[0], &["severity"]), 1);
+    assert_eq!(int(&diagnostics[0], &["range", "start", "line"]), 0);
+}
+
+#[test]
+fn range_changes() {
+    let mut server = initialized();
+    open(&mut server, SOURCE);
+
+    // Add a line above everything, then break the first clause.
+    let change = format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didChange", "params": {{"textDocument": {{"uri": "{}", "version": 2}}, "contentChanges": [{{"range": {{"start": {{"line": 0, "character": 0}}, "end": {{"line": 0, "character": 0}}}}, "text": "let first = 1\n"}}, {{"range": {{"start": {{"line": 1, "character": 8}}, "end": {{"line": 1, "character": 9}}}}, "text": "0 +"}}]}}}}"#,
+        URI
+    );
+    let published = server.handle(&change);
+
+    let diagnostics = items(get(&published[0], &["params", "diagnostics"]).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(int(&diagnostics[0], &["range", "start", "line"]), 1);
+
+    let locations = items(&request(&mut server, "textDocument/definition", 6, 13));
+    assert_eq!(locations.len(), 1);
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 2);
+
+    let change = format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didChange", "params": {{"textDocument": {{"uri": "{}", "version": 3}}, "contentChanges": [{{"range": {{"start": {{"line": 1, "character": 8}}, "end": {{"line": 1, "character": 11}}}}, "text": "0"}}]}}}}"#,
+        URI
+    );
+    let published = server.handle(&change);
+    assert!(items(get(&published[0], &["params", "diagnostics"]).unwrap()).is_empty());
+
+    let locations = items(&request(&mut server, "textDocument/definition", 6, 13));
+    assert_eq!(locations.len(), 2);
+}
+
+#[test]
+fn positions_count_utf16_code_units() {
+    let mut server = initialized();
+    open(&mut server, "let face = \"😀\" + 1\nputs(\"😀\", face)\n");
+
+    // The emoji is two code units, so the `1` is at character 18.
+    let change = format!(
+        r#"{{"jsonrpc": "2.0", "method": "textDocument/didChange", "params": {{"textDocument": {{"uri": "{}", "version": 2}}, "contentChanges": [{{"range": {{"start": {{"line": 0, "character": 18}}, "end": {{"line": 0, "character": 19}}}}, "text": "'a'"}}]}}}}"#,
+        URI
+    );
+    let published = server.handle(&change);
+
+    let diagnostics = items(get(&published[0], &["params", "diagnostics"]).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        text(&diagnostics[0], &["message"]),
+        "Unsupported operands string and char for Plus"
+    );
+    assert_eq!(int(&diagnostics[0], &["range", "start", "character"]), 16);
+    assert_eq!(int(&diagnostics[0], &["range", "end", "character"]), 17);
+
+    let result = request(&mut server, "textDocument/hover", 1, 12);
+    assert_eq!(int(&result, &["range", "start", "character"]), 11);
+    assert_eq!(int(&result, &["range", "end", "character"]), 15);
+}
+
+#[test]
+fn hover() {
+    let mut server = initialized();
+    open(&mut server, SOURCE);
+
+    let result = request(&mut server, "textDocument/hover", 2, 7);
+    assert_eq!(
+        text(&result, &["contents", "value"]),
+        "```slice\nfn fact(0)\nfn fact(n: int) -> int\n```"
+    );
+    assert_eq!(int(&result, &["range", "start", "character"]), 6);
+    assert_eq!(int(&result, &["range", "end", "character"]), 10);
+
+    // `fact(0)` declares no return type, so the type of `total` is unknown.
+    let result = request(&mut server, "textDocument/hover", 6, 12);
+    assert_eq!(
+        text(&result, &["contents", "value"]),
+        "```slice\ntotal\n```"
+    );
+
+    let result = request(&mut server, "textDocument/hover", 2, 3);
+    assert_eq!(
+        text(&result, &["contents", "value"]),
+        "```slice\nn: int\n```"
+    );
+
+    let result = request(&mut server, "textDocument/hover", 7, 6);
+    assert!(text(&result, &["contents", "value"]).contains("List.map"));
+
+    let result = request(&mut server, "textDocument/hover", 3, 1);
+    assert_eq!(result, Value::Unit);
+}
+
+#[test]
+fn definition_of_every_clause() {
+    let mut server = initialized();
+    open(&mut server, SOURCE);
+
+    let locations = items(&request(&mut server, "textDocument/definition", 5, 13));
+    assert_eq!(locations.len(), 2);
+    assert_eq!(text(&locations[0], &["uri"]), URI);
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 0);
+    assert_eq!(int(&locations[1], &["range", "start", "line"]), 1);
+    assert_eq!(int(&locations[1], &["range", "start", "character"]), 3);
+
+    let locations = items(&request(&mut server, "textDocument/definition", 6, 12));
+    assert_eq!(locations.len(), 1);
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 5);
+    assert_eq!(int(&locations[0], &["range", "start", "character"]), 4);
+
+    let locations = items(&request(&mut server, "textDocument/definition", 2, 2));
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 1);
+    assert_eq!(int(&locations[0], &["range", "start", "character"]), 8);
+}
+
+#[test]
+fn definition_follows_scopes() {
+    let mut server = initialized();
+    open(
+        &mut server,
+        "let n = 0
+fn first(n):
+  n + 1
+end
+fn second(x):
+  let n = x * 2
+  for n in 0..n:
+    puts(n)
+  end
+  n
+end
+fn third():
+  let step = fn(k): if k == 0: n else: step(k - 1) end end
+  step(2)
+end
+",
+    );
+    let definition = |server: &mut Server, line, character| {
+        let locations = items(&request(server, "textDocument/definition", line, character));
+        assert_eq!(locations.len(), 1);
+        (
+            int(&locations[0], &["range", "start", "line"]),
+            int(&locations[0], &["range", "start", "character"]),
+        )
+    };
+
+    // The parameter of first, not the global or the let in second.
+    assert_eq!(definition(&mut server, 2, 2), (1, 9));
+    // The loop variable, and the range over the let before the loop.
+    assert_eq!(definition(&mut server, 7, 9), (6, 6));
+    assert_eq!(definition(&mut server, 6, 14), (5, 6));
+    // The let in second, as the loop variable is out of scope.
+    assert_eq!(definition(&mut server, 9, 2), (5, 6));
+    // The global, and a closure calling itself.
+    assert_eq!(definition(&mut server, 12, 31), (0, 4));
+    assert_eq!(definition(&mut server, 12, 39), (12, 6));
+}
+
+#[test]
+fn definition_in_an_imported_file() {
+    let dir = std::env::temp_dir().join(format!("slice-lsp-{}", std::process::id()));
+    fs::create_dir_all(dir.join("lib")).unwrap();
+    fs::write(
+        dir.join("lib/helpers.slice"),
+        "fn secret(x): x end\npub fn double(x): secret(x) * 2 end\npub let limit = 10\n",
+    )
+    .unwrap();
+
+    let main = format!("file://{}", dir.join("main.slice").display());
+    let helpers = format!("file://{}", dir.join("lib/helpers.slice").display());
+    let mut server = initialized();
+    open_at(
+        &mut server,
+        &main,
+        "import \"lib/helpers.slice\"\nHelpers.double(Helpers.limit)\nHelpers.secret(1)\n",
+    );
+
+    let locations = items(&request_at(
+        &mut server,
+        &main,
+        "textDocument/definition",
+        1,
+        9,
+    ));
+    assert_eq!(locations.len(), 1);
+    assert_eq!(text(&locations[0], &["uri"]), helpers);
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 1);
+    assert_eq!(int(&locations[0], &["range", "start", "character"]), 7);
+
+    let locations = items(&request_at(
+        &mut server,
+        &main,
+        "textDocument/definition",
+        1,
+        24,
+    ));
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 2);
+
+    let locations = items(&request_at(
+        &mut server,
+        &main,
+        "textDocument/definition",
+        2,
+        9,
+    ));
+    assert!(locations.is_empty());
+
+    // An open document is used instead of the file on disk.
+    open_at(&mut server, &helpers, "\n\npub fn double(x): x + x end\n");
+    let locations = items(&request_at(
+        &mut server,
+        &main,
+        "textDocument/definition",
+        1,
+        9,
+    ));
+    assert_eq!(text(&locations[0], &["uri"]), helpers);
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 2);
+}
+
+#[test]
+fn definition_through_percent_encoded_uris() {
+    let dir = std::env::temp_dir().join(format!("slice lsp é {}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("helpers.slice"), "pub fn double(x): x * 2 end\n").unwrap();
+
+    let encoded = dir
+        .display()
+        .to_string()
+        .replace(' ', "%20")
+        .replace('é', "%C3%A9");
+    let main = format!("file://{}/main.slice", encoded);
+    let mut server = initialized();
+    open_at(
+        &mut server,
+        &main,
+        "import \"helpers.slice\"\nHelpers.double(1)\n",
+    );
+
+    let locations = items(&request_at(
+        &mut server,
+        &main,
+        "textDocument/definition",
+        1,
+        9,
+    ));
+    assert_eq!(locations.len(), 1);
+    assert_eq!(
+        text(&locations[0], &["uri"]),
+        format!("file://{}/helpers.slice", encoded)
+    );
+
+    // An open document is found however its URI is encoded.
+    let helpers = format!(
+        "file://{}/helpers.slice",
+        encoded.replace("%C3%A9", "%c3%a9")
+    );
+    open_at(&mut server, &helpers, "\npub fn double(x): x + x end\n");
+    let locations = items(&request_at(
+        &mut server,
+        &main,
+        "textDocument/definition",
+        1,
+        9,
+    ));
+    assert_eq!(text(&locations[0], &["uri"]), helpers);
+    assert_eq!(int(&locations[0], &["range", "start", "line"]), 1);
+}
+
+#[test]
+fn document_symbols() {
+    let mut server = initialized();
+    open(&mut server, SOURCE);
+
+    let message = format!(
+        r#"{{"jsonrpc": "2.0", "id": 4, "method": "textDocument/documentSymbol", "params": {{"textDocument": {{"uri": "{}"}}}}}}"#,
+        URI
+    );
+    let responses = server.handle(&message);
+    let symbols = items(get(&responses[0], &["result"]).unwrap());
+
+    let names: Vec<String> = symbols.iter().map(|s| text(s, &["name"])).collect();
+    assert_eq!(names, ["fact", "fact", "total"]);
+    assert_eq!(int(&symbols[0], &["kind"]), 12);
+    assert_eq!(int(&symbols[2], &["kind"]), 13);
+
+    assert_eq!(text(&symbols[1], &["detail"]), "fn fact(n: int) -> int");
+
+    open(
+        &mut server,
+        "fn outer(a):\n  let b = a\n  fn inner(c): c end\n  inner(b)\nend\n",
+    );
+    let responses = server.handle(&message);
+    let symbols = items(get(&responses[0], &["result"]).unwrap());
+
+    let children = items(get(&symbols[0], &["children"]).unwrap());
+    let names: Vec<String> = children.iter().map(|s| text(s, &["name"])).collect();
+    assert_eq!(names, ["b", "inner"]);
+}
+
+#[test]
+fn completion() {
+    let mut server = initialized();
+    open(&mut server, "let total = 1\nto\nList.ma\n");
+
+    let labels = |result: Value| -> Vec<String> {
+        items(&result)
+            .iter()
+            .map(|item| text(item, &["label"]))
+            .collect()
+    };
+
+    let found = labels(request(&mut server, "textDocument/completion", 1, 2));
+    assert!(found.contains(&String::from("total")));
+
+    let found = labels(request(&mut server, "textDocument/completion", 2, 7));
+    assert_eq!(found, ["map"]);
+}
+
+#[test]
+fn exit_codes() {
+    let session = framed(&[
+        r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize", "params": {}}"#,
+        r#"{"jsonrpc": "2.0", "method": "initialized", "params": {}}"#,
+        r#"{"jsonrpc": "2.0", "id": 2, "method": "shutdown"}"#,
+        r#"{"jsonrpc": "2.0", "method": "exit"}"#,
+    ]);
+    let mut output = Vec::new();
+
+    assert_eq!(serve(&mut session.as_slice(), &mut output), Ok(0));
+    let output = String::from_utf8(output).unwrap();
+    assert!(output.starts_with("Content-Length: "));
+    assert!(output.contains(r#""id":2,"result":null"#));
+
+    let session = framed(&[r#"{"jsonrpc": "2.0", "method": "exit"}"#]);
+    assert_eq!(serve(&mut session.as_slice(), &mut Vec::new()), Ok(1));
+
+    let session = framed(&[r#"{"jsonrpc": "2.0", "id": 1, "method": "initialize"}"#]);
+    assert_eq!(serve(&mut session.as_slice(), &mut Vec::new()), Ok(1));
+}
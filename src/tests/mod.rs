@@ -1,5 +1,20 @@
+mod analysis_tests;
+mod builtins_tests;
+mod cli_tests;
+mod dict_tests;
+mod editor_tests;
+mod format_tests;
+mod incremental_tests;
+mod interpreter_tests;
+mod json_tests;
+#[allow(clippy::needless_range_loop)]
 mod lexer_tests;
+mod lint_tests;
+mod list_tests;
+mod lsp_tests;
+mod module_tests;
 mod parser_tests;
-
-pub use self::lexer_tests::*;
-pub use self::parser_tests::*;
+mod repl_tests;
+mod source_tests;
+mod tree_tests;
+mod typecheck_tests;
@@ -0,0 +1,290 @@
+use crate::interpreter::Interpreter;
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::stdio::BufferIo;
+use std::cell::RefCell;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+/// A directory holding `files`, given as names and contents.
+fn project(name: &str, files: &[(&str, &str)]) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("slice-{}-{}", name, std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+
+    for (file, contents) in files {
+        let path = dir.join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    dir
+}
+
+/// Runs `main.slice` in the project, returning the result and the output.
+fn run_main(dir: &Path) -> (Result<String, String>, String) {
+    let io = Rc::new(RefCell::new(BufferIo::new("")));
+    let mut interpreter = Interpreter::with_io(Rc::clone(&io));
+
+    let result = interpreter
+        .run_file(dir.join("main.slice").to_str().unwrap())
+        .map(|value| value.to_string());
+
+    let out = io.borrow().out.clone();
+    (result, out)
+}
+
+#[test]
+fn imports_by_path_and_by_name() {
+    let dir = project(
+        "imports_by_path_and_by_name",
+        &[
+            (
+                "main.slice",
+                "import \"lib/string_utils.slice\"
+                import Helpers
+                [StringUtils.shout(\"hi\"), Helpers.double(4), Helpers.limit]",
+            ),
+            (
+                "lib/string_utils.slice",
+                "import Punctuation
+                pub fn shout(s): Punctuation.exclaim(s) end",
+            ),
+            ("lib/punctuation.slice", "pub fn exclaim(s): s + \"!\" end"),
+            (
+                "helpers.slice",
+                "pub let limit = 10
+                fn secret(x): x end
+                pub fn double(x): secret(x) * 2 end",
+            ),
+        ],
+    );
+
+    let (result, _) = run_main(&dir);
+    assert_eq!(result, Ok(String::from("[\"hi!\", 8, 10]")));
+}
+
+#[test]
+fn each_file_has_its_own_namespace() {
+    let dir = project(
+        "each_file_has_its_own_namespace",
+        &[
+            (
+                "main.slice",
+                "let name = \"main\"
+                import Counter
+                [Counter.label(), Counter.apply(fn(x) -> x + 1), name]",
+            ),
+            (
+                "counter.slice",
+                "let name = \"counter\"
+                pub fn label(): name end
+                pub fn apply(f):
+                  let g = fn(x) -> f(f(x))
+                  g(0)
+                end",
+            ),
+        ],
+    );
+
+    let (result, _) = run_main(&dir);
+    assert_eq!(result, Ok(String::from("[\"counter\", 2, \"main\"]")));
+}
+
+#[test]
+fn private_definitions() {
+    let dir = project(
+        "private_definitions",
+        &[
+            ("main.slice", "import Helpers\nHelpers.secret(1)"),
+            ("helpers.slice", "fn secret(x): x end"),
+        ],
+    );
+
+    let (result, _) = run_main(&dir);
+    assert_eq!(
+        result,
+        Err(String::from("secret is private to Helpers 2.9"))
+    );
+
+    fs::write(dir.join("main.slice"), "import Helpers\nHelpers.missing").unwrap();
+    let (result, _) = run_main(&dir);
+    assert_eq!(
+        result,
+        Err(String::from("missing is not defined in Helpers 2.9"))
+    );
+}
+
+#[test]
+fn modules_load_once() {
+    let dir = project(
+        "modules_load_once",
+        &[
+            (
+                "main.slice",
+                "import Log
+                import \"shared.slice\"
+                import Shared
+                Log.count()",
+            ),
+            (
+                "log.slice",
+                "import Shared
+                pub fn count(): Shared.loads end",
+            ),
+            ("shared.slice", "puts(\"loading\")\npub let loads = 1"),
+        ],
+    );
+
+    let (result, out) = run_main(&dir);
+    assert_eq!(result, Ok(String::from("1")));
+    assert_eq!(out, "loading\n");
+}
+
+#[test]
+fn import_cycles() {
+    let dir = project(
+        "import_cycles",
+        &[
+            ("main.slice", "import A"),
+            ("a.slice", "import B"),
+            ("b.slice", "\nimport A"),
+        ],
+    );
+
+    let (result, _) = run_main(&dir);
+    let error = result.unwrap_err();
+    assert!(
+        error.ends_with("Import cycle a.slice -> b.slice -> a.slice 2.1"),
+        "{}",
+        error
+    );
+
+    fs::write(dir.join("b.slice"), "import Main").unwrap();
+    let (result, _) = run_main(&dir);
+    assert!(result
+        .unwrap_err()
+        .ends_with("Import cycle main.slice -> a.slice -> b.slice -> main.slice 1.1"));
+}
+
+#[test]
+fn import_errors() {
+    let dir = project(
+        "import_errors",
+        &[
+            ("main.slice", "import Missing"),
+            ("broken.slice", "let = 1"),
+        ],
+    );
+
+    let (result, _) = run_main(&dir);
+    assert!(result.unwrap_err().starts_with(&format!(
+        "Cannot import {}",
+        dir.join("missing.slice").display()
+    )));
+
+    fs::write(dir.join("main.slice"), "import Broken").unwrap();
+    let (result, _) = run_main(&dir);
+    assert_eq!(
+        result,
+        Err(String::from(
+            "Unexpected token expected pattern, got Assign 1.5"
+        ))
+    );
+
+    let mut interpreter = Interpreter::new();
+    interpreter.allow_filesystem(false);
+    let ast = Parser::new(Lexer::new("import Broken"))
+        .parse_program()
+        .unwrap();
+    assert_eq!(
+        interpreter.run(&ast).unwrap_err(),
+        "Cannot import Broken, the filesystem is not allowed 1.1"
+    );
+}
+
+#[test]
+fn pub_only_at_the_top_level() {
+    let ast = Parser::new(Lexer::new("pub fn f(x): x end\npub let y = 1")).parse_program();
+    assert!(ast.is_ok());
+
+    let error = Parser::new(Lexer::new("fn f(x):\n  pub let y = x\nend"))
+        .parse_program()
+        .unwrap_err();
+    assert_eq!(error, "Only top-level definitions can be pub 2.3");
+
+    let error = Parser::new(Lexer::new("pub x = 1"))
+        .parse_program()
+        .unwrap_err();
+    assert_eq!(
+        error,
+        "Unexpected token expected 'let' or 'fn' after 'pub', got Identity(\"x\") 1.5"
+    );
+}
+
+#[test]
+fn errors_are_placed_in_their_file() {
+    let dir = project(
+        "errors_are_placed_in_their_file",
+        &[
+            (
+                "main.slice",
+                "import Shapes\nlet sides = [3, 4]\nList.map(sides, Shapes.area)",
+            ),
+            (
+                "shapes.slice",
+                "fn square(n): n * n end\npub fn area(sides):\n  square(sides) + missing\nend",
+            ),
+            ("broken.slice", "let = 1"),
+        ],
+    );
+    let main = dir.join("main.slice");
+    let shapes = dir.join("shapes.slice");
+
+    let mut interpreter = Interpreter::new();
+    let error = interpreter.run_file(main.to_str().unwrap()).unwrap_err();
+    assert_eq!(error, "Undefined variable missing 3.19");
+    assert_eq!(
+        interpreter.describe(&error),
+        format!(
+            "{}:3:19: Undefined variable missing\n    at area ({}:3:6)",
+            shapes.display(),
+            main.display()
+        )
+    );
+
+    let trace = interpreter.trace().unwrap();
+    assert_eq!(
+        interpreter.sources().name(trace.file),
+        Some(shapes.to_str().unwrap())
+    );
+
+    fs::write(&main, "\n  import Broken").unwrap();
+    let error = interpreter.run_file(main.to_str().unwrap()).unwrap_err();
+    assert_eq!(
+        interpreter.describe(&error),
+        format!(
+            "{}:1:5: Unexpected token expected pattern, got Assign\n    at import Broken ({}:2:3)",
+            dir.join("broken.slice").display(),
+            main.display()
+        )
+    );
+
+    fs::write(&main, "let x = 1\nx()").unwrap();
+    let error = interpreter.run_file(main.to_str().unwrap()).unwrap_err();
+    assert_eq!(
+        interpreter.describe(&error),
+        format!("{}:2:1: int is not a function", main.display())
+    );
+}
+
+#[test]
+fn errors_outside_files_are_placed_at_their_position() {
+    let ast = Parser::new(Lexer::new("let x = 1\nx()"))
+        .parse_program()
+        .unwrap();
+
+    let mut interpreter = Interpreter::new();
+    let error = interpreter.run(&ast).unwrap_err();
+    assert_eq!(interpreter.describe(&error), "2:1: int is not a function");
+}
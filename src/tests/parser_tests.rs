@@ -1,4 +1,4 @@
-use crate::ast::{Expression, Statement};
+use crate::ast::{Expression, Pattern, Statement};
 use crate::lexer::Lexer;
 use crate::parser::Parser;
 use crate::token::TokenType;
@@ -14,8 +14,12 @@ fn let_int_basic() {
     let stmnt = &ast[0];
 
     if let Statement::Let(ls) = stmnt {
-        assert_eq!(ls.name.token_type, TokenType::Identity(String::from("x")));
-        assert_eq!(ls.var_type.token_type, TokenType::IntType);
+        if let Pattern::Binding(name, var_type) = &ls.pattern {
+            assert_eq!(name.token_type, TokenType::Identity(String::from("x")));
+            assert_eq!(var_type.as_ref().unwrap().token_type, TokenType::IntType);
+        } else {
+            panic!("expected a binding pattern");
+        }
     }
 }
 
@@ -30,8 +34,12 @@ fn let_string_basic() {
     let stmnt = &ast[0];
 
     if let Statement::Let(ls) = stmnt {
-        assert_eq!(ls.name.token_type, TokenType::Identity(String::from("s")));
-        assert_eq!(ls.var_type.token_type, TokenType::StringType);
+        if let Pattern::Binding(name, var_type) = &ls.pattern {
+            assert_eq!(name.token_type, TokenType::Identity(String::from("s")));
+            assert_eq!(var_type.as_ref().unwrap().token_type, TokenType::StringType);
+        } else {
+            panic!("expected a binding pattern");
+        }
     }
 }
 
@@ -45,11 +53,7 @@ fn return_value_int() {
 
     let stmnt = &ast[0];
 
-    if let Statement::Return(_e) = stmnt {
-        assert!(true);
-    } else {
-        assert!(false);
-    }
+    assert!(matches!(stmnt, Statement::Return(_)));
 }
 
 #[test]
@@ -62,11 +66,7 @@ fn end_test_valid() {
 
     let stmnt = &ast[0];
 
-    if let Statement::End = stmnt {
-        assert!(true);
-    } else {
-        assert!(false);
-    }
+    assert!(matches!(stmnt, Statement::End));
 }
 
 #[test]
@@ -79,11 +79,7 @@ fn prefix_test() {
 
     let stmnt = &ast[0];
 
-    if let Statement::Expr(v) = stmnt {
-        assert!(true);
-    } else {
-        assert!(false);
-    }
+    assert!(matches!(stmnt, Statement::Expr(_)));
 
     let input = "-foo";
     let lexer = Lexer::new(input);
@@ -93,9 +89,114 @@ fn prefix_test() {
 
     let stmnt = &ast[0];
 
-    if let Statement::Expr(v) = stmnt {
-        assert!(true);
+    assert!(matches!(stmnt, Statement::Expr(_)));
+}
+
+#[test]
+fn match_arms() {
+    let input = "match x:
+        [h | tl] when h > 0 -> h
+        {\"k\": v, other: 1} -> v
+        _ -> 0
+    end";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    if let Statement::Expr(Expression::Match(match_expr)) = &ast[0] {
+        assert_eq!(match_expr.arms.len(), 3);
+        assert!(match_expr.arms[0].guard.is_some());
+        assert!(matches!(&match_expr.arms[0].pattern, Pattern::List(l) if l.tail.is_some()));
+        assert!(matches!(&match_expr.arms[1].pattern, Pattern::Dict(d) if d.entries.len() == 2));
+        assert!(matches!(match_expr.arms[2].pattern, Pattern::Wildcard(_)));
     } else {
-        assert!(false);
+        panic!("expected a match expression");
     }
 }
+
+#[test]
+fn multi_line_collections() {
+    let input = "let d = {
+        a: [1,
+            2,],
+        \"b\": True,
+    }";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    assert_eq!(ast.len(), 1);
+}
+
+#[test]
+fn loop_jumps_outside_loop() {
+    let input = "fn f(list):
+        for x in list:
+            fn g():
+                break
+            end
+        end
+    end";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    assert_eq!(
+        parser.parse_program().unwrap_err(),
+        "Break outside of a loop 4.17"
+    );
+
+    let input = "while True:
+        if x:
+            continue
+        end
+    end";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    assert!(parser.parse_program().is_ok());
+}
+
+#[test]
+fn index_assignment() {
+    let input = "grid[0][1] = Array.length(row)";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    match &ast[0] {
+        Statement::IndexAssign(assign) => {
+            assert!(matches!(*assign.target.left, Expression::Index(_)));
+            assert!(matches!(assign.expression, Expression::Call(_)));
+        }
+        _ => panic!("expected an index assignment"),
+    }
+
+    let input = "Array.length = 1";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    assert_eq!(
+        parser.parse_program().unwrap_err(),
+        "Cannot assign to Identity(\"length\") 1.7"
+    );
+}
+
+#[test]
+fn recovers_after_errors() {
+    let input = "let = 2
+let x = 1
+puts(x +)
+x";
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let (ast, errors) = parser.parse_program_with_errors();
+
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].ends_with(" 1.5"));
+    assert!(errors[1].ends_with(" 3.9"));
+    assert_eq!(ast.len(), 2);
+}
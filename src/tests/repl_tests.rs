@@ -0,0 +1,236 @@
+use crate::interpreter::Interpreter;
+use crate::repl::{Repl, CONTINUATION_PROMPT, PROMPT};
+use crate::stdio::BufferIo;
+use std::cell::RefCell;
+use std::fs;
+use std::rc::Rc;
+
+fn repl() -> Repl {
+    Repl::new(Interpreter::new())
+}
+
+fn output(repl: &mut Repl, line: &str) -> Option<String> {
+    repl.eval_line(line).unwrap()
+}
+
+#[test]
+fn bindings_persist() {
+    let mut repl = repl();
+
+    assert_eq!(output(&mut repl, "let x = 2\n"), None);
+    assert_eq!(output(&mut repl, "x = x + 1\n"), None);
+    assert_eq!(output(&mut repl, "x * 2\n"), Some(String::from("6")));
+    assert_eq!(
+        output(&mut repl, "\"six\"\n"),
+        Some(String::from("\"six\""))
+    );
+    assert_eq!(output(&mut repl, "\n"), None);
+}
+
+#[test]
+fn multi_line_blocks() {
+    let mut repl = repl();
+
+    assert_eq!(output(&mut repl, "fn square(n):\n"), None);
+    assert_eq!(repl.prompt(), CONTINUATION_PROMPT);
+    assert_eq!(output(&mut repl, "  n * n\n"), None);
+    assert_eq!(output(&mut repl, "end\n"), None);
+    assert_eq!(repl.prompt(), PROMPT);
+
+    assert_eq!(output(&mut repl, "if square(3) > 5:\n"), None);
+    assert_eq!(output(&mut repl, "  \"big\"\n"), None);
+    assert_eq!(output(&mut repl, "end\n"), Some(String::from("\"big\"")));
+
+    assert_eq!(output(&mut repl, "[1,\n"), None);
+    assert_eq!(output(&mut repl, "2]\n"), Some(String::from("[1, 2]")));
+}
+
+#[test]
+fn errors_clear_pending_input() {
+    let mut repl = repl();
+
+    assert_eq!(
+        repl.eval_line("let = 1\n").unwrap_err(),
+        "<input 1>:1:5: Unexpected token expected pattern, got Assign"
+    );
+    assert_eq!(repl.prompt(), PROMPT);
+
+    assert_eq!(output(&mut repl, "fn broken():\n"), None);
+    assert_eq!(
+        repl.eval_line("\n").unwrap_err(),
+        "<input 2>:3:1: Unexpected end of input expected 'end'"
+    );
+    assert_eq!(repl.prompt(), PROMPT);
+
+    assert_eq!(
+        repl.eval_line("missing\n").unwrap_err(),
+        "<input 3>:1:1: Undefined variable missing"
+    );
+}
+
+#[test]
+fn commands() {
+    let mut repl = repl();
+
+    assert_eq!(
+        output(&mut repl, ":type [1, 2]"),
+        Some(String::from("list"))
+    );
+    assert_eq!(output(&mut repl, ":type 'a'"), Some(String::from("char")));
+    assert_eq!(
+        output(&mut repl, ":ast 1 + 2"),
+        Some(String::from("Infix Plus\n  1\n  2"))
+    );
+
+    assert_eq!(output(&mut repl, "let x = 1"), None);
+    assert_eq!(output(&mut repl, ":reset"), None);
+    assert_eq!(
+        repl.eval_line("x").unwrap_err(),
+        "<input 5>:1:1: Undefined variable x"
+    );
+    assert_eq!(
+        output(&mut repl, "List.length([1])"),
+        Some(String::from("1"))
+    );
+
+    assert_eq!(
+        repl.eval_line(":type").unwrap_err(),
+        ":type expects an expression"
+    );
+    assert_eq!(
+        repl.eval_line(":quit").unwrap_err(),
+        "Unknown command :quit, expected :type, :ast, :load or :reset"
+    );
+}
+
+#[test]
+fn type_runs_nothing() {
+    let io = Rc::new(RefCell::new(BufferIo::new("typed")));
+    let mut repl = Repl::new(Interpreter::with_io(Rc::clone(&io)));
+
+    assert_eq!(output(&mut repl, "let numbers = [/1, 2/]"), None);
+    assert_eq!(
+        output(&mut repl, ":type numbers[0] = 5\nputs 1\nnumbers"),
+        Some(String::from("array"))
+    );
+    assert_eq!(
+        output(&mut repl, ":type while True: 1 end"),
+        Some(String::from("unit"))
+    );
+    assert_eq!(
+        output(&mut repl, ":type let y = 1\ny"),
+        Some(String::from("int"))
+    );
+    assert_eq!(
+        output(&mut repl, ":type IO.read_all()"),
+        Some(String::from("unknown"))
+    );
+    assert_eq!(output(&mut repl, "numbers"), Some(String::from("[/1, 2/]")));
+    assert_eq!(
+        repl.eval_line("y").unwrap_err(),
+        "<input 7>:1:1: Undefined variable y"
+    );
+    assert_eq!(io.borrow().out, "");
+    assert_eq!(
+        output(&mut repl, "IO.read_all()"),
+        Some(String::from("\"typed\""))
+    );
+}
+
+#[test]
+fn type_uses_what_is_defined() {
+    let mut repl = repl();
+
+    assert_eq!(output(&mut repl, "let word = \"a\""), None);
+    assert_eq!(output(&mut repl, "fn half(n: int) -> int: n / 2 end"), None);
+
+    assert_eq!(
+        output(&mut repl, ":type word"),
+        Some(String::from("string"))
+    );
+    assert_eq!(
+        output(&mut repl, ":type half(4)"),
+        Some(String::from("int"))
+    );
+    assert_eq!(
+        output(&mut repl, ":type half"),
+        Some(String::from("function"))
+    );
+    assert_eq!(
+        repl.eval_line(":type word - 1").unwrap_err(),
+        "<input 6>:1:6: Unsupported operands string and int for Minus"
+    );
+    assert_eq!(
+        output(&mut repl, ":type word = 1\nword - 1"),
+        Some(String::from("int"))
+    );
+}
+
+#[test]
+fn load() {
+    let path = std::env::temp_dir().join("slice_repl_load.slice");
+    fs::write(&path, "fn double(n):\n  n * 2\nend\n").unwrap();
+
+    let mut repl = repl();
+    assert_eq!(
+        output(&mut repl, &format!(":load {}", path.display())),
+        None
+    );
+    assert_eq!(output(&mut repl, "double(4)"), Some(String::from("8")));
+
+    fs::remove_file(&path).unwrap();
+    assert!(repl
+        .eval_line(&format!(":load {}", path.display()))
+        .is_err());
+}
+
+#[test]
+fn completion() {
+    let mut repl = repl();
+    output(&mut repl, "let total = 1");
+    output(&mut repl, "let {ok: tally} = {ok: 2}");
+
+    assert_eq!(repl.complete("puts(to"), (5, vec![String::from("total")]));
+    assert_eq!(
+        repl.complete("t"),
+        (0, vec![String::from("tally"), String::from("total")])
+    );
+    assert_eq!(
+        repl.complete("x = wh"),
+        (4, vec![String::from("when"), String::from("while")])
+    );
+    assert_eq!(
+        repl.complete("List.f"),
+        (
+            5,
+            vec![
+                String::from("filter"),
+                String::from("find"),
+                String::from("fold")
+            ]
+        )
+    );
+    assert_eq!(
+        repl.complete("Char.is_d"),
+        (5, vec![String::from("is_digit?")])
+    );
+    assert_eq!(repl.complete("Env."), (4, vec![String::from("args")]));
+    assert_eq!(repl.complete("total."), (6, Vec::new()));
+}
+
+#[test]
+fn errors_are_placed_in_their_input() {
+    let mut repl = repl();
+
+    assert_eq!(output(&mut repl, "fn broken():\n"), None);
+    assert_eq!(output(&mut repl, "  missing\n"), None);
+    assert_eq!(output(&mut repl, "end\n"), None);
+    assert_eq!(
+        repl.eval_line("\n\nbroken()").unwrap_err(),
+        "<input 1>:2:3: Undefined variable missing\n    at broken (<input 2>:3:1)"
+    );
+    assert_eq!(
+        repl.eval_line(":ast 1 +").unwrap_err(),
+        "<input 3>:1:4: Unexpected token expected value, got EOF"
+    );
+}
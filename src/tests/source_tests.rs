@@ -0,0 +1,50 @@
+use crate::diagnostic::Diagnostic;
+use crate::lexer::Lexer;
+use crate::source::{FileId, SourceMap};
+use crate::token::TokenType;
+
+#[test]
+fn files_are_found_by_id_and_name() {
+    let mut sources = SourceMap::new();
+    let main = sources.add(String::from("main.slice"), String::from("let x = 1\nx"));
+    let lib = sources.add(String::from("lib.slice"), String::new());
+
+    assert_ne!(main, lib);
+    assert_eq!(sources.find("lib.slice"), Some(lib));
+    assert_eq!(sources.name(main), Some("main.slice"));
+    assert_eq!(sources.line(main, 2), Some("x"));
+    assert_eq!(sources.name(FileId::default()), None);
+
+    assert_eq!(sources.set("lib.slice", String::from("pub let y = 2")), lib);
+    assert_eq!(sources.line(lib, 1), Some("pub let y = 2"));
+    assert_eq!(sources.find("other.slice"), None);
+}
+
+#[test]
+fn tokens_resolve_to_their_file() {
+    let mut sources = SourceMap::new();
+    sources.add(String::from("main.slice"), String::new());
+    let lib = sources.add(String::from("lib.slice"), String::from("\n  y"));
+
+    let mut lexer = Lexer::new("\n  y").in_file(lib);
+    let token = lexer.next();
+    assert_eq!(token.token_type, TokenType::NewLine);
+    let token = lexer.next();
+    assert_eq!(sources.locate(&token), "lib.slice:2:3");
+
+    let token = Lexer::new("y").next();
+    assert_eq!(sources.locate(&token), "1:1");
+}
+
+#[test]
+fn diagnostics_render_with_their_file() {
+    let mut sources = SourceMap::new();
+    let file = sources.add(String::from("main.slice"), String::from("let = 1"));
+
+    let diagnostic =
+        Diagnostic::from_error("Unexpected token expected pattern, got Assign 1.5", file);
+    assert_eq!(
+        sources.render(&diagnostic),
+        "error: main.slice:1:5: Unexpected token expected pattern, got Assign"
+    );
+}
@@ -0,0 +1,99 @@
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::tree::tree;
+
+fn outline(input: &str) -> String {
+    let ast = Parser::new(Lexer::new(input)).parse_program().unwrap();
+    tree(&ast)
+}
+
+#[test]
+fn statements_and_expressions() {
+    let input = "let total = 1 + 2
+fn double(n: int) -> int:
+  n * 2
+end
+total = double(total)";
+
+    assert_eq!(
+        outline(input),
+        "Let total
+  Infix Plus
+    1
+    2
+Function double(n: int) -> int
+  Body
+    Infix Multiply
+      n
+      2
+Assign total
+  Call
+    double
+    total
+"
+    );
+}
+
+#[test]
+fn patterns_are_written_as_in_the_source() {
+    let input = "pub let {\"a\": x, \"b\": [h | t]} = {\"a\": 1, \"b\": [2]}
+match x:
+  ^h when True -> 'c'
+  _ -> \"other\"
+end";
+
+    assert_eq!(
+        outline(input),
+        "Pub Let {\"a\": x, \"b\": [h | t]}
+  Dict
+    Entry
+      \"a\"
+      1
+    Entry
+      \"b\"
+      List
+        2
+Match
+  x
+  Arm ^h
+    Guard
+      True
+    'c'
+  Arm _
+    \"other\"
+"
+    );
+}
+
+#[test]
+fn blocks_are_sections() {
+    let input = "for i in 0..3:
+  if i == 1: continue else: List.map([i], fn(v) -> v) end
+end";
+
+    assert_eq!(
+        outline(input),
+        "For i
+  Infix Range
+    0
+    3
+  Body
+    If
+      Condition
+        Infix Equal
+          i
+          1
+      Then
+        Continue
+      Else
+        Call
+          Access map
+            List
+          List
+            i
+          Function fn(v)
+            Body
+              v
+"
+    );
+}
@@ -0,0 +1,162 @@
+use crate::lexer::Lexer;
+use crate::parser::Parser;
+use crate::typecheck::{type_check, Type, Types};
+
+fn check(input: &str) -> Types {
+    let lexer = Lexer::new(input);
+
+    let mut parser = Parser::new(lexer);
+    let ast = parser.parse_program().unwrap();
+
+    type_check(&ast)
+}
+
+fn errors(input: &str) -> Vec<String> {
+    check(input)
+        .diagnostics
+        .iter()
+        .map(|diagnostic| diagnostic.to_string())
+        .collect()
+}
+
+#[test]
+fn operands_of_the_wrong_type() {
+    assert_eq!(
+        errors("1 + \"a\""),
+        ["error: Unsupported operands int and string for Plus 1.3"]
+    );
+    assert_eq!(
+        errors("let word = \"a\" + \"b\"\nword - 1"),
+        ["error: Unsupported operands string and int for Minus 2.6"]
+    );
+    assert_eq!(
+        errors("fn f(n):\n  n * True\nend"),
+        ["error: Unsupported operand bool for Multiply 2.5"]
+    );
+    assert_eq!(
+        errors("-\"a\""),
+        ["error: Unsupported operand string for Minus 1.1"]
+    );
+    assert_eq!(
+        errors("'a' < 1"),
+        ["error: Unsupported operands char and int for LessThan 1.5"]
+    );
+}
+
+#[test]
+fn well_typed_programs_pass() {
+    let input = "let total = 1 + 2 * 3
+    let word = \"a\" + \"b\"
+    let close = 'a' < 'b' and word != \"c\"
+    fn double(n: int) -> int:
+        n * 2
+    end
+    let numbers = List.map(1..=3, fn(n) -> double(n) + 1)
+    for i in 0..total:
+        puts i + double(i)
+    end
+    if close:
+        numbers
+    else:
+        [word]
+    end";
+
+    assert!(errors(input).is_empty());
+}
+
+#[test]
+fn conditions_and_loops() {
+    assert_eq!(errors("if 1: 2 end"), ["error: Expected bool, got int 1.4"]);
+    assert_eq!(
+        errors("while \"yes\":\n  break\nend"),
+        ["error: Expected bool, got string 1.7"]
+    );
+    assert_eq!(
+        errors("True and 'c'"),
+        ["error: Expected bool, got char 1.10"]
+    );
+    assert_eq!(
+        errors("for c in \"abc\":\n  puts c\nend"),
+        ["error: Cannot iterate over string 1.1"]
+    );
+}
+
+#[test]
+fn annotations_and_return_types() {
+    assert_eq!(
+        errors("let n: int = \"one\""),
+        ["error: Expected int for n, got string 1.5"]
+    );
+    assert_eq!(
+        errors("fn name() -> string:\n  1 + 1\nend"),
+        ["error: name returns int, expected string 2.5"]
+    );
+    assert_eq!(
+        errors("fn sign(n: int) -> int:\n  if n < 0:\n    return \"negative\"\n  end\n  1\nend"),
+        ["error: sign returns string, expected int 3.12"]
+    );
+    assert_eq!(
+        errors("fn half(n: int) -> int:\n  n / 2\nend\nhalf(4) + \"a\""),
+        ["error: Unsupported operands int and string for Plus 4.9"]
+    );
+}
+
+#[test]
+fn rebound_variables_are_unknown() {
+    let input = "let n = 1
+    n = \"one\"
+    n + \"two\"";
+    assert!(errors(input).is_empty());
+
+    let input = "let n = 1
+    fn show():
+        n + \"two\"
+    end
+    let n = \"one\"";
+    assert!(errors(input).is_empty());
+
+    let input = "fn f(x):
+        x + 1
+    end
+    fn g(x: int):
+        let x = \"a\"
+        x + \"b\"
+    end";
+    assert!(errors(input).is_empty());
+}
+
+#[test]
+fn unknown_types_are_never_reported() {
+    let input = "fn f(a, b):
+        a + b
+    end
+    let value = List.head([1])
+    let matched = match value:
+        1 -> \"one\"
+        _ -> 2
+    end
+    matched + value
+    f(1, 2) - 1
+    [1][0] + 1";
+
+    assert!(errors(input).is_empty());
+}
+
+#[test]
+fn binding_types() {
+    let types = check(
+        "let count = 1 + 2
+let label: string = other()
+let items = [1]
+let mixed = if count > 1: 1 else: \"many\" end
+for i in 0..count:
+  i
+end",
+    );
+
+    assert_eq!(types.binding(1, 5), Some(Type::Int));
+    assert_eq!(types.binding(2, 5), Some(Type::Str));
+    assert_eq!(types.binding(3, 5), Some(Type::List));
+    assert_eq!(types.binding(4, 5), None);
+    assert_eq!(types.binding(5, 5), Some(Type::Int));
+}
@@ -1,7 +1,10 @@
+use crate::source::FileId;
 use std::fmt;
 
-#[derive(PartialEq, Clone, Debug)]
+#[allow(clippy::upper_case_acronyms)]
+#[derive(PartialEq, Clone, Debug, Default)]
 pub enum TokenType {
+    #[default]
     TMP,
     Illegal,
     EOF,
@@ -14,20 +17,29 @@ pub enum TokenType {
     Assign,
     And,
     Or,
+    Xor,
     Not,
     Equal,
     NotEqual,
     GreaterThan,
     LessThan,
+    GreaterEqual,
+    LessEqual,
     Plus,
     If,
     Else,
     Elif,
     Minus,
+    Multiply,
     Divide,
+    Modulo,
     Comma,
     SemiColon,
     Colon,
+    Dot,
+    Range,
+    RangeInclusive,
+    Bar,
     LParen,
     RParen,
     LBrace,
@@ -39,20 +51,28 @@ pub enum TokenType {
     Function,
     Let,
     End,
+    When,
+    Match,
+    While,
+    For,
+    In,
+    Break,
+    Continue,
     Pin,
     NewLine,
+    /// Spaces and tabs, only returned by `Lexer::with_trivia`.
+    Whitespace,
+    /// A comment up to the end of its line, only returned by
+    /// `Lexer::with_trivia`.
+    Comment,
     StringType,
     BoolType,
     IntType,
     CharType,
     Output,
     Return,
-}
-
-impl Default for TokenType {
-    fn default() -> Self {
-        TokenType::TMP
-    }
+    Import,
+    Pub,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -60,6 +80,7 @@ pub struct Token {
     pub token_type: TokenType,
     pub row: u32,
     pub col: u32,
+    pub file: FileId,
 }
 
 impl Token {
@@ -68,6 +89,7 @@ impl Token {
             token_type,
             row,
             col,
+            file: FileId::default(),
         }
     }
 }
@@ -0,0 +1,226 @@
+//! A readable outline of a syntax tree, printed by `slice ast` and the
+//! REPL's `:ast`. Each node is a line, indented by two spaces under the node
+//! it belongs to, and patterns are written out as they are in the source.
+
+use crate::ast::{Expression, FunctionClause, Pattern, Statement};
+use crate::token::{Token, TokenType};
+use crate::typecheck::Type;
+
+pub fn tree(ast: &[Statement]) -> String {
+    let mut out = String::new();
+    block(ast, 0, &mut out);
+    out
+}
+
+fn line(label: &str, depth: usize, out: &mut String) {
+    out.push_str(&"  ".repeat(depth));
+    out.push_str(label);
+    out.push('\n');
+}
+
+fn block(block: &[Statement], depth: usize, out: &mut String) {
+    for statement in block {
+        self::statement(statement, depth, out);
+    }
+}
+
+/// A block under a line of its own, such as `Body`.
+fn section(label: &str, statements: &[Statement], depth: usize, out: &mut String) {
+    line(label, depth, out);
+    block(statements, depth + 1, out);
+}
+
+fn statement(statement: &Statement, depth: usize, out: &mut String) {
+    match statement {
+        Statement::Let(ls) => {
+            let public = if ls.public { "Pub " } else { "" };
+            line(
+                &format!("{}Let {}", public, pattern(&ls.pattern)),
+                depth,
+                out,
+            );
+            expression(&ls.expression, depth + 1, out);
+        }
+        Statement::Assign(assign) => {
+            line(&format!("Assign {}", token(&assign.name)), depth, out);
+            expression(&assign.expression, depth + 1, out);
+        }
+        Statement::IndexAssign(assign) => {
+            line("IndexAssign", depth, out);
+            expression(&assign.target.left, depth + 1, out);
+            expression(&assign.target.index, depth + 1, out);
+            expression(&assign.expression, depth + 1, out);
+        }
+        Statement::Function(clause) => function(clause, depth, out),
+        Statement::While(while_statement) => {
+            line("While", depth, out);
+            expression(&while_statement.condition, depth + 1, out);
+            section("Body", &while_statement.body, depth + 1, out);
+        }
+        Statement::For(for_statement) => {
+            line(
+                &format!("For {}", pattern(&for_statement.pattern)),
+                depth,
+                out,
+            );
+            expression(&for_statement.iterable, depth + 1, out);
+            section("Body", &for_statement.body, depth + 1, out);
+        }
+        Statement::Break(_) => line("Break", depth, out),
+        Statement::Continue(_) => line("Continue", depth, out),
+        Statement::Return(value) => {
+            line("Return", depth, out);
+            expression(value, depth + 1, out);
+        }
+        Statement::Expr(value) => expression(value, depth, out),
+        Statement::Import(import) => line(&format!("Import {}", token(&import.module)), depth, out),
+        Statement::End => line("End", depth, out),
+    }
+}
+
+/// A function clause as its header, `Function name(parameters) -> type`,
+/// with its guard and body under it. Anonymous functions are named `fn`.
+fn function(clause: &FunctionClause, depth: usize, out: &mut String) {
+    let name = match &clause.name.token_type {
+        TokenType::Identity(name) => name.as_str(),
+        _ => "fn",
+    };
+    let parameters: Vec<String> = clause.parameters.iter().map(pattern).collect();
+    let mut header = format!("Function {}({})", name, parameters.join(", "));
+    if let Some(return_type) = &clause.return_type {
+        header.push_str(&format!(" -> {}", Type::of_annotation(return_type)));
+    }
+    if clause.public {
+        header.insert_str(0, "Pub ");
+    }
+
+    line(&header, depth, out);
+    if let Some(guard) = &clause.guard {
+        line("Guard", depth + 1, out);
+        expression(guard, depth + 2, out);
+    }
+    section("Body", &clause.body, depth + 1, out);
+}
+
+fn expression(expression: &Expression, depth: usize, out: &mut String) {
+    let child = depth + 1;
+
+    match expression {
+        Expression::Value(value) => line(&token(value), depth, out),
+        Expression::Prefix(prefix) => {
+            line(&format!("Prefix {}", prefix.operator), depth, out);
+            self::expression(&prefix.right, child, out);
+        }
+        Expression::Infix(infix) => {
+            line(&format!("Infix {}", infix.operator), depth, out);
+            self::expression(&infix.left, child, out);
+            self::expression(&infix.right, child, out);
+        }
+        Expression::Call(call) => {
+            line("Call", depth, out);
+            self::expression(&call.function, child, out);
+            for argument in &call.arguments {
+                self::expression(argument, child, out);
+            }
+        }
+        Expression::If(if_expr) => {
+            line("If", depth, out);
+            for (condition, body) in &if_expr.branches {
+                line("Condition", child, out);
+                self::expression(condition, child + 1, out);
+                section("Then", body, child, out);
+            }
+            if let Some(alternative) = &if_expr.alternative {
+                section("Else", alternative, child, out);
+            }
+        }
+        Expression::Match(match_expr) => {
+            line("Match", depth, out);
+            self::expression(&match_expr.subject, child, out);
+            for arm in &match_expr.arms {
+                line(&format!("Arm {}", pattern(&arm.pattern)), child, out);
+                if let Some(guard) = &arm.guard {
+                    line("Guard", child + 1, out);
+                    self::expression(guard, child + 2, out);
+                }
+                self::expression(&arm.body, child + 1, out);
+            }
+        }
+        Expression::List(list) => {
+            line("List", depth, out);
+            for element in &list.elements {
+                self::expression(element, child, out);
+            }
+            if let Some(tail) = &list.tail {
+                line("Tail", child, out);
+                self::expression(tail, child + 1, out);
+            }
+        }
+        Expression::Array(array) => {
+            line("Array", depth, out);
+            for element in &array.elements {
+                self::expression(element, child, out);
+            }
+        }
+        Expression::Dict(dict) => {
+            line("Dict", depth, out);
+            for (key, value) in &dict.entries {
+                line("Entry", child, out);
+                self::expression(key, child + 1, out);
+                self::expression(value, child + 1, out);
+            }
+        }
+        Expression::Index(index) => {
+            line("Index", depth, out);
+            self::expression(&index.left, child, out);
+            self::expression(&index.index, child, out);
+        }
+        Expression::Access(access) => {
+            line(&format!("Access {}", token(&access.name)), depth, out);
+            self::expression(&access.left, child, out);
+        }
+        Expression::Function(clause) => function(clause, depth, out),
+    }
+}
+
+/// A pattern as it is written in the source.
+fn pattern(pattern: &Pattern) -> String {
+    match pattern {
+        Pattern::Wildcard(_) => String::from("_"),
+        Pattern::Binding(name, None) => token(name),
+        Pattern::Binding(name, Some(kind)) => {
+            format!("{}: {}", token(name), Type::of_annotation(kind))
+        }
+        Pattern::Literal(literal) => token(literal),
+        Pattern::Pin(name) => format!("^{}", token(name)),
+        Pattern::List(list) => {
+            let elements: Vec<String> = list.elements.iter().map(self::pattern).collect();
+            match &list.tail {
+                Some(tail) => format!("[{} | {}]", elements.join(", "), self::pattern(tail)),
+                None => format!("[{}]", elements.join(", ")),
+            }
+        }
+        Pattern::Dict(dict) => {
+            let entries: Vec<String> = dict
+                .entries
+                .iter()
+                .map(|(key, value)| format!("{}: {}", token(key), self::pattern(value)))
+                .collect();
+            format!("{{{}}}", entries.join(", "))
+        }
+    }
+}
+
+/// A name or literal as it is written in the source, and other tokens by
+/// their type.
+fn token(token: &Token) -> String {
+    match &token.token_type {
+        TokenType::Identity(name) => name.clone(),
+        TokenType::Int(i) => i.to_string(),
+        TokenType::Str(s) => format!("{:?}", s),
+        TokenType::Character(c) => format!("{:?}", c),
+        TokenType::True => String::from("True"),
+        TokenType::False => String::from("False"),
+        other => format!("{:?}", other),
+    }
+}
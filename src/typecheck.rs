@@ -0,0 +1,731 @@
+//! A static type check. Types are worked out from literals, operators,
+//! annotations and declared return types, and an expression whose operands
+//! have types the interpreter would reject is reported as an error.
+//!
+//! Anything that cannot be known without running the program is `Unknown`
+//! and never reported: the results of builtins, match arms and indexing,
+//! the elements of collections, and variables that may be rebound to a
+//! value of another type, because they are assigned or bound by `let` more
+//! than once somewhere in the program.
+
+use crate::ast::{Expression, FunctionClause, InfixExpr, Pattern, PrefixExpr, Statement};
+use crate::diagnostic::Diagnostic;
+use crate::token::{Token, TokenType};
+use crate::value::Value;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum Type {
+    Int,
+    Str,
+    Char,
+    Bool,
+    List,
+    Array,
+    Dict,
+    Range,
+    Function,
+    Unit,
+    Unknown,
+}
+
+impl Type {
+    /// The type a `: type` annotation names.
+    pub fn of_annotation(annotation: &Token) -> Self {
+        match annotation.token_type {
+            TokenType::IntType => Type::Int,
+            TokenType::StringType => Type::Str,
+            TokenType::CharType => Type::Char,
+            TokenType::BoolType => Type::Bool,
+            _ => Type::Unknown,
+        }
+    }
+
+    fn known(self) -> Option<Self> {
+        match self {
+            Type::Unknown => None,
+            known => Some(known),
+        }
+    }
+}
+
+/// Written as `Value::type_name` writes the type of a value.
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Type::Int => "int",
+            Type::Str => "string",
+            Type::Char => "char",
+            Type::Bool => "bool",
+            Type::List => "list",
+            Type::Array => "array",
+            Type::Dict => "dict",
+            Type::Range => "range",
+            Type::Function => "function",
+            Type::Unit => "unit",
+            Type::Unknown => "unknown",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// The errors found, and the known type of each variable, by the row and
+/// column of the name that binds it.
+#[derive(Default)]
+pub struct Types {
+    pub diagnostics: Vec<Diagnostic>,
+    bindings: HashMap<(u32, u32), Type>,
+}
+
+impl Types {
+    /// The type of the variable bound at `row`.`col`, if it is known.
+    pub fn binding(&self, row: u32, col: u32) -> Option<Type> {
+        self.bindings.get(&(row, col)).copied()
+    }
+}
+
+pub fn type_check(ast: &[Statement]) -> Types {
+    let mut bound = HashMap::new();
+    count_bindings(ast, &mut bound);
+
+    let mut checker = Checker {
+        bound,
+        scopes: vec![HashMap::new()],
+        returns: Vec::new(),
+        types: Types::default(),
+    };
+    checker.block(ast);
+
+    checker.types
+}
+
+/// The type of the value of a program, such as an expression entered in the
+/// REPL, checked after the variables in `globals` are defined. Nothing is
+/// run, so the type is `Unknown` wherever the type check would not know it.
+/// The errors found when the program is not well typed.
+pub fn type_of(ast: &[Statement], globals: &[(String, Value)]) -> Result<Type, Vec<Diagnostic>> {
+    let mut bound = HashMap::new();
+    count_bindings(ast, &mut bound);
+
+    let known = globals
+        .iter()
+        .map(|(name, value)| {
+            let entry = match bound.contains_key(name) {
+                true => Entry::unknown(),
+                false => Entry::of_value(value),
+            };
+            (name.clone(), entry)
+        })
+        .collect();
+
+    let mut checker = Checker {
+        bound,
+        scopes: vec![known, HashMap::new()],
+        returns: Vec::new(),
+        types: Types::default(),
+    };
+    let kind = checker.block(ast);
+
+    match checker.types.diagnostics {
+        diagnostics if diagnostics.is_empty() => Ok(kind),
+        diagnostics => Err(diagnostics),
+    }
+}
+
+/// Counts the `let` bindings of each name, counting an assignment as two so
+/// an assigned name is always unstable.
+fn count_bindings(block: &[Statement], counts: &mut HashMap<String, usize>) {
+    for statement in block {
+        match statement {
+            Statement::Let(ls) => {
+                let mut names = Vec::new();
+                ls.pattern.bound_names(&mut names);
+                for name in names {
+                    *counts.entry(name).or_insert(0) += 1;
+                }
+            }
+            Statement::Assign(assign) => {
+                if let TokenType::Identity(name) = &assign.name.token_type {
+                    *counts.entry(name.clone()).or_insert(0) += 2;
+                }
+            }
+            Statement::Function(clause) => count_bindings(&clause.body, counts),
+            Statement::While(while_statement) => count_bindings(&while_statement.body, counts),
+            Statement::For(for_statement) => count_bindings(&for_statement.body, counts),
+            _ => {}
+        }
+
+        let mut expressions = Vec::new();
+        statement_expressions(statement, &mut expressions);
+        for expression in expressions {
+            count_expression_bindings(expression, counts);
+        }
+    }
+}
+
+fn statement_expressions<'a>(statement: &'a Statement, out: &mut Vec<&'a Expression>) {
+    match statement {
+        Statement::Let(ls) => out.push(&ls.expression),
+        Statement::Assign(assign) => out.push(&assign.expression),
+        Statement::IndexAssign(assign) => {
+            out.push(&assign.target.left);
+            out.push(&assign.target.index);
+            out.push(&assign.expression);
+        }
+        Statement::Function(clause) => out.extend(&clause.guard),
+        Statement::While(while_statement) => out.push(&while_statement.condition),
+        Statement::For(for_statement) => out.push(&for_statement.iterable),
+        Statement::Return(expression) | Statement::Expr(expression) => out.push(expression),
+        Statement::Break(_) | Statement::Continue(_) | Statement::Import(_) | Statement::End => {}
+    }
+}
+
+/// Finds the blocks inside an expression, such as the branches of an `if`.
+fn count_expression_bindings(expression: &Expression, counts: &mut HashMap<String, usize>) {
+    match expression {
+        Expression::Value(_) => {}
+        Expression::Prefix(prefix) => count_expression_bindings(&prefix.right, counts),
+        Expression::Infix(infix) => {
+            count_expression_bindings(&infix.left, counts);
+            count_expression_bindings(&infix.right, counts);
+        }
+        Expression::Call(call) => {
+            count_expression_bindings(&call.function, counts);
+            for argument in &call.arguments {
+                count_expression_bindings(argument, counts);
+            }
+        }
+        Expression::If(if_expr) => {
+            for (condition, block) in &if_expr.branches {
+                count_expression_bindings(condition, counts);
+                count_bindings(block, counts);
+            }
+            if let Some(block) = &if_expr.alternative {
+                count_bindings(block, counts);
+            }
+        }
+        Expression::Match(match_expr) => {
+            count_expression_bindings(&match_expr.subject, counts);
+            for arm in &match_expr.arms {
+                if let Some(guard) = &arm.guard {
+                    count_expression_bindings(guard, counts);
+                }
+                count_expression_bindings(&arm.body, counts);
+            }
+        }
+        Expression::List(list) => {
+            for element in list.elements.iter().chain(list.tail.as_deref()) {
+                count_expression_bindings(element, counts);
+            }
+        }
+        Expression::Array(array) => {
+            for element in &array.elements {
+                count_expression_bindings(element, counts);
+            }
+        }
+        Expression::Dict(dict) => {
+            for (key, value) in &dict.entries {
+                count_expression_bindings(key, counts);
+                count_expression_bindings(value, counts);
+            }
+        }
+        Expression::Index(index) => {
+            count_expression_bindings(&index.left, counts);
+            count_expression_bindings(&index.index, counts);
+        }
+        Expression::Access(access) => count_expression_bindings(&access.left, counts),
+        Expression::Function(clause) => count_bindings(&clause.body, counts),
+    }
+}
+
+#[derive(Clone, Copy)]
+struct Entry {
+    kind: Type,
+    /// For a function whose clauses all take the same number of arguments
+    /// and declare the same return type, those two.
+    returns: Option<(usize, Type)>,
+}
+
+impl Entry {
+    fn unknown() -> Self {
+        Entry {
+            kind: Type::Unknown,
+            returns: None,
+        }
+    }
+
+    fn of_value(value: &Value) -> Self {
+        let kind = match value {
+            Value::Int(_) => Type::Int,
+            Value::Str(_) => Type::Str,
+            Value::Char(_) => Type::Char,
+            Value::Bool(_) => Type::Bool,
+            Value::List(_) => Type::List,
+            Value::Array(_) => Type::Array,
+            Value::Dict(_) => Type::Dict,
+            Value::Range(_, _) => Type::Range,
+            Value::Function(_) | Value::Builtin(_) | Value::Partial(_) => Type::Function,
+            Value::Unit => Type::Unit,
+            Value::Float(_) | Value::Module(_) | Value::Namespace(_) => Type::Unknown,
+        };
+        let returns = match value {
+            Value::Function(function) => {
+                clause_returns(function.clauses.iter().map(|clause| clause.as_ref()))
+            }
+            _ => None,
+        };
+
+        Entry { kind, returns }
+    }
+}
+
+/// The number of arguments and the return type that every clause of a
+/// function declares, if they agree.
+fn clause_returns<'a>(
+    mut clauses: impl Iterator<Item = &'a FunctionClause>,
+) -> Option<(usize, Type)> {
+    let returns = |clause: &FunctionClause| {
+        let annotation = clause.return_type.as_ref()?;
+        Some((clause.parameters.len(), Type::of_annotation(annotation)))
+    };
+
+    let first = returns(clauses.next()?);
+    match clauses.all(|clause| returns(clause) == first) {
+        true => first,
+        false => None,
+    }
+}
+
+struct Checker {
+    /// How many times each name is bound by `let`, with assignments counting
+    /// twice.
+    bound: HashMap<String, usize>,
+    scopes: Vec<HashMap<String, Entry>>,
+    /// The types of the `return` statements of each function being checked.
+    returns: Vec<Vec<(Type, Token)>>,
+    types: Types,
+}
+
+impl Checker {
+    /// Defines a name in the current scope, or as `Unknown` when it may be
+    /// rebound to another type: when it is bound by `let` more than `lets`
+    /// times or assigned anywhere in the program.
+    fn define(&mut self, name: &str, entry: Entry, lets: usize) {
+        let entry = if self.bound.get(name).is_some_and(|count| *count > lets) {
+            Entry::unknown()
+        } else {
+            entry
+        };
+
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name.to_string(), entry);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<Entry> {
+        self.scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.get(name).copied())
+    }
+
+    fn error(&mut self, message: String, token: &Token) {
+        self.types
+            .diagnostics
+            .push(Diagnostic::error(message, token));
+    }
+
+    fn scoped<T>(&mut self, f: impl FnOnce(&mut Self) -> T) -> T {
+        self.scopes.push(HashMap::new());
+        let result = f(self);
+        self.scopes.pop();
+        result
+    }
+
+    /// Checks a block in the current scope and gives the type of its value,
+    /// which is that of its last statement.
+    fn block(&mut self, block: &[Statement]) -> Type {
+        self.functions(block);
+
+        let mut kind = Type::Unit;
+        for statement in block {
+            kind = self.statement(statement);
+        }
+        kind
+    }
+
+    /// Defines the functions of a block before its statements, with the
+    /// return type their clauses agree on.
+    fn functions(&mut self, block: &[Statement]) {
+        let mut functions: Vec<(&str, Vec<&FunctionClause>)> = Vec::new();
+
+        for statement in block {
+            if let Statement::Function(clause) = statement {
+                if let TokenType::Identity(name) = &clause.name.token_type {
+                    match functions.iter_mut().find(|(n, _)| *n == name.as_str()) {
+                        Some((_, clauses)) => clauses.push(clause),
+                        None => functions.push((name, vec![clause])),
+                    }
+                }
+            }
+        }
+
+        for (name, clauses) in functions {
+            self.define(
+                name,
+                Entry {
+                    kind: Type::Function,
+                    returns: clause_returns(clauses.into_iter()),
+                },
+                0,
+            );
+        }
+    }
+
+    fn statement(&mut self, statement: &Statement) -> Type {
+        match statement {
+            Statement::Let(ls) => {
+                let kind = self.expression(&ls.expression);
+                self.bind(&ls.pattern, kind, 1);
+                Type::Unit
+            }
+            Statement::Assign(assign) => {
+                self.expression(&assign.expression);
+                Type::Unit
+            }
+            Statement::IndexAssign(assign) => {
+                self.expression(&assign.target.left);
+                self.expression(&assign.target.index);
+                self.expression(&assign.expression);
+                Type::Unit
+            }
+            Statement::Function(clause) => {
+                self.clause(clause);
+                Type::Unit
+            }
+            Statement::While(while_statement) => {
+                self.condition(&while_statement.condition);
+                self.scoped(|checker| checker.block(&while_statement.body));
+                Type::Unit
+            }
+            Statement::For(for_statement) => {
+                let iterable = self.expression(&for_statement.iterable);
+                if let Some(kind) = iterable.known() {
+                    if !matches!(kind, Type::List | Type::Array | Type::Dict | Type::Range) {
+                        self.error(
+                            format!("Cannot iterate over {}", kind),
+                            &for_statement.token,
+                        );
+                    }
+                }
+
+                let element = match iterable {
+                    Type::Range => Type::Int,
+                    _ => Type::Unknown,
+                };
+                self.scoped(|checker| {
+                    checker.bind(&for_statement.pattern, element, 0);
+                    checker.block(&for_statement.body)
+                });
+                Type::Unit
+            }
+            Statement::Return(expression) => {
+                let kind = self.expression(expression);
+                if let Some(returns) = self.returns.last_mut() {
+                    returns.push((kind, expression.token().clone()));
+                }
+                Type::Unknown
+            }
+            Statement::Expr(expression) => self.expression(expression),
+            Statement::Import(import) => {
+                self.define(&import.name(), Entry::unknown(), 0);
+                Type::Unit
+            }
+            Statement::Break(_) | Statement::Continue(_) | Statement::End => Type::Unknown,
+        }
+    }
+
+    /// Checks a function clause in a scope of its own holding its
+    /// parameters. A body whose value or `return` has a known type other
+    /// than the declared return type is an error.
+    fn clause(&mut self, clause: &FunctionClause) {
+        self.returns.push(Vec::new());
+
+        let kind = self.scoped(|checker| {
+            for parameter in &clause.parameters {
+                checker.bind(parameter, Type::Unknown, 0);
+            }
+            if let Some(guard) = &clause.guard {
+                checker.condition(guard);
+            }
+            checker.block(&clause.body)
+        });
+
+        let mut returned = self.returns.pop().unwrap_or_default();
+        if let Some(Statement::Expr(last)) = clause.body.last() {
+            returned.push((kind, last.token().clone()));
+        }
+
+        let declared = match clause.return_type.as_ref().map(Type::of_annotation) {
+            Some(declared) if declared != Type::Unknown => declared,
+            _ => return,
+        };
+
+        for (kind, token) in returned {
+            if kind.known().is_some_and(|kind| kind != declared) {
+                self.error(
+                    format!(
+                        "{} returns {}, expected {}",
+                        name(&clause.name),
+                        kind,
+                        declared
+                    ),
+                    &token,
+                );
+            }
+        }
+    }
+
+    /// Defines the names a pattern binds. A single name is given `kind`, or
+    /// the type it is annotated with.
+    fn bind(&mut self, pattern: &Pattern, kind: Type, lets: usize) {
+        match pattern {
+            Pattern::Binding(token, annotation) => {
+                if let (Some(annotation), Some(actual)) = (annotation, kind.known()) {
+                    let expected = Type::of_annotation(annotation);
+                    if expected.known().is_some_and(|expected| expected != actual) {
+                        self.error(
+                            format!("Expected {} for {}, got {}", expected, name(token), actual),
+                            token,
+                        );
+                    }
+                }
+
+                let kind = match annotation {
+                    Some(annotation) => Type::of_annotation(annotation),
+                    None => kind,
+                };
+
+                if let TokenType::Identity(name) = &token.token_type {
+                    self.define(
+                        name,
+                        Entry {
+                            kind,
+                            returns: None,
+                        },
+                        lets,
+                    );
+                    if let Some(Entry { kind, .. }) = self.lookup(name) {
+                        if kind.known().is_some() {
+                            self.types.bindings.insert((token.row, token.col), kind);
+                        }
+                    }
+                }
+            }
+            Pattern::List(list) => {
+                for element in list.elements.iter().chain(list.tail.as_deref()) {
+                    self.bind(element, Type::Unknown, lets);
+                }
+            }
+            Pattern::Dict(dict) => {
+                for (_, pattern) in &dict.entries {
+                    self.bind(pattern, Type::Unknown, lets);
+                }
+            }
+            Pattern::Wildcard(_) | Pattern::Literal(_) | Pattern::Pin(_) => {}
+        }
+    }
+
+    fn condition(&mut self, expression: &Expression) {
+        if let Some(kind) = self.expression(expression).known() {
+            if kind != Type::Bool {
+                self.error(format!("Expected bool, got {}", kind), expression.token());
+            }
+        }
+    }
+
+    fn expression(&mut self, expression: &Expression) -> Type {
+        match expression {
+            Expression::Value(token) => match &token.token_type {
+                TokenType::Int(_) => Type::Int,
+                TokenType::Str(_) => Type::Str,
+                TokenType::Character(_) => Type::Char,
+                TokenType::True | TokenType::False => Type::Bool,
+                TokenType::Identity(name) => self.lookup(name).map_or(Type::Unknown, |e| e.kind),
+                _ => Type::Unknown,
+            },
+            Expression::Prefix(prefix) => self.prefix(prefix),
+            Expression::Infix(infix) => self.infix(infix),
+            Expression::Call(call) => {
+                self.expression(&call.function);
+                for argument in &call.arguments {
+                    self.expression(argument);
+                }
+
+                let returns = match call.function.as_ref() {
+                    Expression::Value(Token {
+                        token_type: TokenType::Identity(name),
+                        ..
+                    }) => self.lookup(name).and_then(|entry| entry.returns),
+                    _ => None,
+                };
+
+                match returns {
+                    Some((arity, kind)) if arity == call.arguments.len() => kind,
+                    Some((arity, _)) if arity > call.arguments.len() => Type::Function,
+                    _ => Type::Unknown,
+                }
+            }
+            Expression::If(if_expr) => {
+                let mut kinds = Vec::new();
+                for (condition, block) in &if_expr.branches {
+                    self.condition(condition);
+                    kinds.push(self.scoped(|checker| checker.block(block)));
+                }
+                match &if_expr.alternative {
+                    Some(block) => kinds.push(self.scoped(|checker| checker.block(block))),
+                    None => kinds.push(Type::Unknown),
+                }
+
+                agreed(&kinds)
+            }
+            Expression::Match(match_expr) => {
+                self.expression(&match_expr.subject);
+                for arm in &match_expr.arms {
+                    self.scoped(|checker| {
+                        checker.bind(&arm.pattern, Type::Unknown, 0);
+                        if let Some(guard) = &arm.guard {
+                            checker.condition(guard);
+                        }
+                        checker.expression(&arm.body);
+                    });
+                }
+                Type::Unknown
+            }
+            Expression::List(list) => {
+                for element in list.elements.iter().chain(list.tail.as_deref()) {
+                    self.expression(element);
+                }
+                Type::List
+            }
+            Expression::Array(array) => {
+                for element in &array.elements {
+                    self.expression(element);
+                }
+                Type::Array
+            }
+            Expression::Dict(dict) => {
+                for (key, value) in &dict.entries {
+                    self.expression(key);
+                    self.expression(value);
+                }
+                Type::Dict
+            }
+            Expression::Index(index) => {
+                self.expression(&index.left);
+                self.expression(&index.index);
+                Type::Unknown
+            }
+            Expression::Access(access) => {
+                self.expression(&access.left);
+                Type::Unknown
+            }
+            Expression::Function(clause) => {
+                self.clause(clause);
+                Type::Function
+            }
+        }
+    }
+
+    fn prefix(&mut self, prefix: &PrefixExpr) -> Type {
+        let right = self.expression(&prefix.right);
+        let (operand, result) = match prefix.operator.token_type {
+            TokenType::Not => (Type::Bool, Type::Bool),
+            TokenType::Minus => (Type::Int, Type::Int),
+            _ => return Type::Unknown,
+        };
+
+        if right.known().is_some_and(|right| right != operand) {
+            self.error(
+                format!("Unsupported operand {} for {}", right, prefix.operator),
+                &prefix.operator,
+            );
+        }
+
+        result
+    }
+
+    /// An operator is an error when no combination of operand types it
+    /// accepts fits the known types of its operands. Its result is known
+    /// when every combination that fits gives the same type.
+    fn infix(&mut self, infix: &InfixExpr) -> Type {
+        use Type::*;
+
+        let operator = &infix.operator;
+        if let TokenType::And | TokenType::Or = operator.token_type {
+            self.condition(&infix.left);
+            self.condition(&infix.right);
+            return Bool;
+        }
+
+        let left = self.expression(&infix.left);
+        let right = self.expression(&infix.right);
+
+        let accepted: &[(Type, Type, Type)] = match operator.token_type {
+            TokenType::Equal | TokenType::NotEqual => return Bool,
+            TokenType::Xor => &[(Bool, Bool, Bool)],
+            TokenType::Plus => &[(Int, Int, Int), (Str, Str, Str)],
+            TokenType::Minus | TokenType::Multiply | TokenType::Divide | TokenType::Modulo => {
+                &[(Int, Int, Int)]
+            }
+            TokenType::Range | TokenType::RangeInclusive => &[(Int, Int, Range)],
+            TokenType::GreaterThan
+            | TokenType::LessThan
+            | TokenType::GreaterEqual
+            | TokenType::LessEqual => &[(Int, Int, Bool), (Str, Str, Bool), (Char, Char, Bool)],
+            _ => return Unknown,
+        };
+
+        let fits = |accepted: Type, actual: Type| actual == Unknown || actual == accepted;
+        let results: Vec<Type> = accepted
+            .iter()
+            .filter(|(l, r, _)| fits(*l, left) && fits(*r, right))
+            .map(|(_, _, result)| *result)
+            .collect();
+
+        if results.is_empty() {
+            let message = match (left.known(), right.known()) {
+                (Some(left), Some(right)) => {
+                    format!(
+                        "Unsupported operands {} and {} for {}",
+                        left, right, operator
+                    )
+                }
+                (Some(known), None) | (None, Some(known)) => {
+                    format!("Unsupported operand {} for {}", known, operator)
+                }
+                (None, None) => return Unknown,
+            };
+            self.error(message, operator);
+            return Unknown;
+        }
+
+        agreed(&results)
+    }
+}
+
+/// The type all of `kinds` share, or `Unknown` when they differ.
+fn agreed(kinds: &[Type]) -> Type {
+    match kinds.split_first() {
+        Some((first, rest)) if rest.iter().all(|kind| kind == first) => *first,
+        _ => Type::Unknown,
+    }
+}
+
+fn name(token: &Token) -> &str {
+    match &token.token_type {
+        TokenType::Identity(name) => name,
+        _ => "fn",
+    }
+}
@@ -0,0 +1,341 @@
+use crate::ast::FunctionClause;
+use crate::builtins::{Builtin, Module};
+use crate::dict::{Dict, Key};
+use crate::environment::Env;
+use crate::list::List;
+use crate::token::{Token, TokenType};
+use std::cell::RefCell;
+use std::cmp::Ordering;
+use std::fmt;
+use std::rc::Rc;
+
+#[derive(Clone, Debug)]
+pub enum Value {
+    Int(isize),
+    /// Only produced by decoding JSON for now.
+    Float(f64),
+    Str(String),
+    Char(char),
+    Bool(bool),
+    List(List),
+    /// A mutable array, shared by every value that refers to it.
+    Array(Rc<RefCell<Vec<Value>>>),
+    Dict(Rc<Dict>),
+    /// Integers from the first bound up to but excluding the second.
+    Range(isize, isize),
+    Function(Rc<Function>),
+    Builtin(&'static Builtin),
+    Module(&'static Module),
+    /// A file loaded by `import`.
+    Namespace(Rc<Namespace>),
+    /// A function applied to fewer arguments than any of its clauses take.
+    Partial(Rc<Partial>),
+    Unit,
+}
+
+impl Value {
+    /// Converts a literal token into its runtime value.
+    pub fn from_literal(token: &Token) -> Option<Self> {
+        match &token.token_type {
+            TokenType::Int(i) => Some(Value::Int(*i)),
+            TokenType::Str(s) => Some(Value::Str(s.clone())),
+            TokenType::Character(c) => Some(Value::Char(*c)),
+            TokenType::True => Some(Value::Bool(true)),
+            TokenType::False => Some(Value::Bool(false)),
+            _ => None,
+        }
+    }
+
+    pub fn type_name(&self) -> &'static str {
+        match self {
+            Value::Int(_) => "int",
+            Value::Float(_) => "float",
+            Value::Str(_) => "string",
+            Value::Char(_) => "char",
+            Value::Bool(_) => "bool",
+            Value::List(_) => "list",
+            Value::Array(_) => "array",
+            Value::Dict(_) => "dict",
+            Value::Range(_, _) => "range",
+            Value::Function(_) | Value::Builtin(_) | Value::Partial(_) => "function",
+            Value::Module(_) | Value::Namespace(_) => "module",
+            Value::Unit => "unit",
+        }
+    }
+
+    /// Whether the value satisfies a type annotation such as `int`.
+    pub fn is_type(&self, type_token: &Token) -> bool {
+        matches!(
+            (self, &type_token.token_type),
+            (Value::Int(_), TokenType::IntType)
+                | (Value::Str(_), TokenType::StringType)
+                | (Value::Char(_), TokenType::CharType)
+                | (Value::Bool(_), TokenType::BoolType)
+        )
+    }
+
+    /// Looks up a dictionary entry by key. Unhashable keys are never found.
+    pub fn dict_get(&self, key: &Value) -> Option<&Value> {
+        match (self, Key::from_value(key)) {
+            (Value::Dict(dict), Some(key)) => dict.get(&key),
+            _ => None,
+        }
+    }
+
+    /// Iterates the elements `for` loops see: list elements in order, the
+    /// elements an array has when the loop starts, dictionary entries as
+    /// `[key, value]` lists in insertion order, and the integers of a range,
+    /// produced lazily.
+    pub fn iter(&self) -> Option<Box<dyn Iterator<Item = Value>>> {
+        match self {
+            Value::List(list) => Some(Box::new(list.clone().into_iter())),
+            Value::Array(array) => Some(Box::new(array.borrow().clone().into_iter())),
+            Value::Dict(dict) => {
+                let dict = Rc::clone(dict);
+                Some(Box::new((0..dict.len()).map(move |i| {
+                    let (key, value) = &dict.entries()[i];
+                    Value::List(List::new().prepend(value.clone()).prepend(key.to_value()))
+                })))
+            }
+            Value::Range(start, end) => Some(Box::new((*start..*end).map(Value::Int))),
+            _ => None,
+        }
+    }
+
+    /// Orders ints, floats, strings and characters with values of the same type, and
+    /// lists of them element by element. `None` for anything else.
+    pub fn compare(&self, other: &Value) -> Option<Ordering> {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => Some(a.cmp(b)),
+            (Value::Float(a), Value::Float(b)) => a.partial_cmp(b),
+            (Value::Str(a), Value::Str(b)) => Some(a.cmp(b)),
+            (Value::Char(a), Value::Char(b)) => Some(a.cmp(b)),
+            (Value::List(a), Value::List(b)) => {
+                for (a, b) in a.iter().zip(b.iter()) {
+                    match a.compare(b)? {
+                        Ordering::Equal => {}
+                        ordering => return Some(ordering),
+                    }
+                }
+                Some(a.len().cmp(&b.len()))
+            }
+            _ => None,
+        }
+    }
+
+    /// The value as it would be written in source, so strings and characters
+    /// inside collections are quoted.
+    pub fn repr(&self) -> String {
+        match self {
+            Value::Str(s) => format!("{:?}", s),
+            Value::Char(c) => format!("{:?}", c),
+            _ => self.to_string(),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::Float(a), Value::Float(b)) => a == b,
+            (Value::Str(a), Value::Str(b)) => a == b,
+            (Value::Char(a), Value::Char(b)) => a == b,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Array(a), Value::Array(b)) => {
+                Rc::ptr_eq(a, b)
+                    || visit(array_key(a, Some(b)), || *a.borrow() == *b.borrow()).unwrap_or(true)
+            }
+            (Value::Dict(a), Value::Dict(b)) => a == b,
+            (Value::Range(a, b), Value::Range(c, d)) => a == c && b == d,
+            (Value::Function(a), Value::Function(b)) => Rc::ptr_eq(a, b),
+            (Value::Builtin(a), Value::Builtin(b)) => std::ptr::eq(*a, *b),
+            (Value::Module(a), Value::Module(b)) => std::ptr::eq(*a, *b),
+            (Value::Namespace(a), Value::Namespace(b)) => Rc::ptr_eq(a, b),
+            (Value::Partial(a), Value::Partial(b)) => Rc::ptr_eq(a, b),
+            (Value::Unit, Value::Unit) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Int(i) => write!(f, "{}", i),
+            Value::Float(x) => write!(f, "{:?}", x),
+            Value::Str(s) => write!(f, "{}", s),
+            Value::Char(c) => write!(f, "{}", c),
+            Value::Bool(true) => write!(f, "True"),
+            Value::Bool(false) => write!(f, "False"),
+            Value::List(list) => {
+                let elements: Vec<String> = list.iter().map(Value::repr).collect();
+                write!(f, "[{}]", elements.join(", "))
+            }
+            Value::Array(array) => {
+                let elements = visit(array_key(array, None), || {
+                    array.borrow().iter().map(Value::repr).collect::<Vec<_>>()
+                });
+                match elements {
+                    Some(elements) => write!(f, "[/{}/]", elements.join(", ")),
+                    None => write!(f, "[/.../]"),
+                }
+            }
+            Value::Dict(dict) => {
+                let entries: Vec<String> = dict
+                    .entries()
+                    .iter()
+                    .map(|(key, value)| format!("{}: {}", key.to_value().repr(), value.repr()))
+                    .collect();
+                write!(f, "{{{}}}", entries.join(", "))
+            }
+            Value::Range(start, end) => write!(f, "{}..{}", start, end),
+            Value::Function(function) if function.name == "fn" => write!(f, "<fn>"),
+            Value::Function(function) => write!(f, "<fn {}>", function.name),
+            Value::Builtin(builtin) => write!(f, "<fn {}>", builtin),
+            Value::Module(module) => write!(f, "<module {}>", module.name),
+            Value::Namespace(namespace) => write!(f, "<module {}>", namespace.name),
+            Value::Partial(partial) => {
+                let name = match &partial.function {
+                    Value::Builtin(builtin) => builtin.to_string(),
+                    Value::Function(function) => function.name.clone(),
+                    other => other.to_string(),
+                };
+                let arguments: Vec<String> = partial.arguments.iter().map(Value::repr).collect();
+                write!(f, "<fn {}({}, ..)>", name, arguments.join(", "))
+            }
+            Value::Unit => write!(f, "()"),
+        }
+    }
+}
+
+thread_local! {
+    /// The arrays being displayed, encoded or compared further up the stack.
+    static VISITING: RefCell<Vec<(usize, usize)>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Identifies an array being visited, or a pair of arrays being compared.
+pub fn array_key(
+    array: &Rc<RefCell<Vec<Value>>>,
+    other: Option<&Rc<RefCell<Vec<Value>>>>,
+) -> (usize, usize) {
+    (
+        Rc::as_ptr(array) as usize,
+        other.map_or(0, |other| Rc::as_ptr(other) as usize),
+    )
+}
+
+/// Runs `f` while `key` is being visited, or gives `None` if it already is,
+/// so an array that holds itself is not descended into forever.
+pub fn visit<R>(key: (usize, usize), f: impl FnOnce() -> R) -> Option<R> {
+    let entered = VISITING.with(|visiting| {
+        let mut visiting = visiting.borrow_mut();
+        let entered = !visiting.contains(&key);
+        if entered {
+            visiting.push(key);
+        }
+        entered
+    });
+    if !entered {
+        return None;
+    }
+
+    let result = f();
+    VISITING.with(|visiting| visiting.borrow_mut().pop());
+    Some(result)
+}
+
+/// Converts an index into a position in a sequence of `len` elements.
+pub fn index_bounds(index: isize, len: usize) -> Option<usize> {
+    if index >= 0 && (index as usize) < len {
+        Some(index as usize)
+    } else {
+        None
+    }
+}
+
+/// Converts a range into positions in a sequence of `len` elements. Both
+/// bounds must lie within the sequence; a range whose end is before its start
+/// is empty.
+pub fn slice_bounds(start: isize, end: isize, len: usize) -> Option<(usize, usize)> {
+    if start < 0 || end < 0 || start as usize > len || end as usize > len {
+        return None;
+    }
+
+    let start = start as usize;
+    Some((start, (end as usize).max(start)))
+}
+
+/// A named function and every clause defined for it so far, in source order.
+/// Anonymous functions are named `fn` and have a single clause.
+#[derive(Debug)]
+pub struct Function {
+    pub name: String,
+    pub clauses: Vec<Rc<FunctionClause>>,
+    pub env: Env,
+}
+
+/// The top-level scope of an imported file. Only the names it marks `pub`
+/// can be read from outside.
+pub struct Namespace {
+    pub name: String,
+    pub env: Env,
+    pub exports: Vec<String>,
+}
+
+impl Namespace {
+    /// The current value of an exported name.
+    pub fn get(&self, name: &str) -> Option<Value> {
+        if self.exports.iter().any(|export| export == name) {
+            self.env.borrow().get_local(name)
+        } else {
+            None
+        }
+    }
+}
+
+impl fmt::Debug for Namespace {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Namespace({})", self.name)
+    }
+}
+
+/// The arguments a function has been applied to so far. Calling it appends
+/// the new arguments and dispatches on the combined list.
+#[derive(Debug)]
+pub struct Partial {
+    /// A `Value::Function` or `Value::Builtin`.
+    pub function: Value,
+    pub arguments: Vec<Value>,
+}
+
+impl Function {
+    pub fn takes(&self, arity: usize) -> bool {
+        self.clauses
+            .iter()
+            .any(|clause| clause.parameters.len() == arity)
+    }
+
+    /// The arities of the function's clauses, smallest first.
+    pub fn arities(&self) -> Vec<usize> {
+        let mut arities: Vec<usize> = self
+            .clauses
+            .iter()
+            .map(|clause| clause.parameters.len())
+            .collect();
+        arities.sort_unstable();
+        arities.dedup();
+        arities
+    }
+}
+
+pub fn type_name_of(type_token: &Token) -> &'static str {
+    match type_token.token_type {
+        TokenType::IntType => "int",
+        TokenType::StringType => "string",
+        TokenType::CharType => "char",
+        TokenType::BoolType => "bool",
+        _ => "unknown",
+    }
+}